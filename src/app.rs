@@ -1,32 +1,287 @@
 //! Main module for the UI (app) mode of the BMW Finder application.
 //! Contains the UI mode execution logic and associated display functions.
 
-use std::collections::HashMap;
+use std::io::{self, IsTerminal};
 
-use crate::bmw::search::search;
-use crate::config::Configuration;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::widgets::{Block, Paragraph, Row, Table, TableState};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::config::{Configuration, OutputField, SortKey, SortOrder};
+use crate::legacy::{filter_and_sort_vehicles, format_discount_cell, text_cell};
+use crate::source::VehicleSource;
 use crate::vehicle::Vehicle;
 
+const HELP: &str = "↑/↓ navigate · Enter select link · q quit";
+
 /// Runs the UI mode of the application.
-pub async fn run(configuration: &Configuration) {
-    match search(configuration).await {
-        Ok(vehicles) => print_ui_output(configuration, &vehicles),
+/// Falls back to the plain summary when stdout is not a terminal.
+pub async fn run(source: &impl VehicleSource, configuration: &Configuration) {
+    match source.search(configuration).await {
+        Ok(vehicles) => {
+            let vehicles = filter_and_sort_vehicles(&vehicles, configuration);
+            if !io::stdout().is_terminal() {
+                print_ui_output(configuration, &vehicles);
+                return;
+            }
+            match run_tui(configuration, &vehicles) {
+                Ok(links) => links.iter().for_each(|link| println!("{}", link)),
+                Err(e) => eprintln!("Error in terminal UI: {}", e),
+            }
+        }
         Err(e) => {
             eprintln!("Error during search: {}", e);
         }
     }
 }
 
+/// Runs the interactive table and returns the links selected with Enter.
+fn run_tui(configuration: &Configuration, vehicles: &[&Vehicle]) -> io::Result<Vec<String>> {
+    let mut terminal = ratatui::init();
+    let result = App::new(configuration, vehicles).run(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+/// State of the interactive vehicle table.
+struct App<'a> {
+    configuration: &'a Configuration,
+    vehicles: &'a [&'a Vehicle],
+    state: TableState,
+    selected_links: Vec<String>,
+    status: String,
+}
+
+impl<'a> App<'a> {
+    fn new(configuration: &'a Configuration, vehicles: &'a [&'a Vehicle]) -> Self {
+        let mut state = TableState::default();
+        if !vehicles.is_empty() {
+            state.select(Some(0));
+        }
+        Self {
+            configuration,
+            vehicles,
+            state,
+            selected_links: Vec::new(),
+            status: String::from(HELP),
+        }
+    }
+
+    fn run(mut self, terminal: &mut DefaultTerminal) -> io::Result<Vec<String>> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            if let Event::Key(key) = event::read()?
+                && key.kind == KeyEventKind::Press
+                && self.handle_key(key.code)
+            {
+                return Ok(self.selected_links);
+            }
+        }
+    }
+
+    /// Handles a key press, returns true when the UI should quit.
+    fn handle_key(&mut self, code: KeyCode) -> bool {
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => return true,
+            KeyCode::Down | KeyCode::Char('j') => self.select_next(),
+            KeyCode::Up | KeyCode::Char('k') => self.select_previous(),
+            KeyCode::Enter => self.select_link(),
+            _ => {}
+        }
+        false
+    }
+
+    fn select_next(&mut self) {
+        if let Some(selected) = self.state.selected() {
+            self.state
+                .select(Some((selected + 1).min(self.vehicles.len() - 1)));
+        }
+    }
+
+    fn select_previous(&mut self) {
+        if let Some(selected) = self.state.selected() {
+            self.state.select(Some(selected.saturating_sub(1)));
+        }
+    }
+
+    fn select_link(&mut self) {
+        if let Some(vehicle) = self.state.selected().and_then(|i| self.vehicles.get(i)) {
+            let link = vehicle.get_link(self.configuration.locale());
+            self.status = format!("Selected: {}", link);
+            if !self.selected_links.contains(&link) {
+                self.selected_links.push(link);
+            }
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [table_area, status_area] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(3)]).areas(frame.area());
+
+        let locale = self.configuration.locale();
+        let rows = self.vehicles.iter().map(|vehicle| {
+            Row::new([
+                vehicle.vss_id.to_string(),
+                text_cell(OutputField::Price, vehicle, self.configuration),
+                format_discount_cell(vehicle.get_discount_percentage(), locale),
+                vehicle.get_link(locale),
+            ])
+        });
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(36),
+                Constraint::Length(12),
+                Constraint::Length(10),
+                Constraint::Min(20),
+            ],
+        )
+        .header(
+            Row::new(["Id", "Price", "Discount", "Link"])
+                .style(Style::new().add_modifier(Modifier::BOLD)),
+        )
+        .block(Block::bordered().title(format!(" {} vehicles ", self.vehicles.len())))
+        .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+
+        frame.render_stateful_widget(table, table_area, &mut self.state);
+        frame.render_widget(
+            Paragraph::new(self.status.as_str()).block(Block::bordered()),
+            status_area,
+        );
+    }
+}
+
 /// Displays the search parameters and the number of vehicles found in UI mode.
-pub fn print_ui_output(configuration: &Configuration, vehicles: &HashMap<uuid::Uuid, Vehicle>) {
+pub fn print_ui_output(configuration: &Configuration, vehicles: &[&Vehicle]) {
     println!("Search parameters:");
-    println!("  Condition: {:?}", configuration.condition);
+    println!("  Condition: {:?}", configuration.conditions());
     println!("  Models: {}", configuration.models().join(", "));
+    println!("  Locale: {}", configuration.locale());
     if let Some(limit) = configuration.limit {
         println!("  Limit: {}", limit);
     }
+    if let Some(limit_per_model) = configuration.limit_per_model() {
+        println!("  Limit per model: {}", limit_per_model);
+    }
     if let Some(equipment_names) = configuration.equipment_names() {
         println!("  Equipment names: {}", equipment_names.join(", "));
     }
+    if let Some(equipment_codes) = configuration.equipment_codes() {
+        println!("  Equipment codes: {}", equipment_codes.join(", "));
+    }
+    if let Some(equipment_regex) = configuration.equipment_regex() {
+        println!("  Equipment regex: {}", equipment_regex);
+    }
+    if let Some(equipment_locale) = configuration.equipment_locale() {
+        println!("  Equipment locale: {}", equipment_locale);
+    }
+    if let Some(excluded_names) = configuration.excluded_equipment_names() {
+        println!("  Excluded equipment: {}", excluded_names.join(", "));
+    }
+    if let Some(min_price) = configuration.min_price() {
+        println!("  Min price: {:.2}", min_price);
+    }
+    if let Some(max_price) = configuration.max_price() {
+        println!("  Max price: {:.2}", max_price);
+    }
+    if let Some(min_discount) = configuration.min_discount() {
+        println!("  Min discount: {:.2} %", min_discount);
+    }
+    if configuration.discounted_only() {
+        println!("  Discounted only");
+    }
+    if let Some(min_savings) = configuration.min_savings() {
+        println!("  Min savings: {:.2}", min_savings);
+    }
+    if let Some(min_score) = configuration.min_score() {
+        println!("  Min score: {:.2}", min_score);
+    }
+    if configuration.fields().contains(&OutputField::Net) {
+        println!("  VAT: {:.2} %", configuration.vat());
+    }
+    if let Some(max_mileage) = configuration.max_mileage() {
+        println!("  Max mileage: {} km", max_mileage);
+    }
+    if let Some(registered_after) = configuration.registered_after() {
+        println!("  Registered after: {}", registered_after);
+    }
+    if let Some(fuel) = configuration.fuel() {
+        println!("  Fuel: {:?}", fuel);
+    }
+    if let Some(min_power) = configuration.min_power() {
+        println!("  Min power: {} hp", min_power);
+    }
+    if let Some(transmission) = configuration.transmission() {
+        println!("  Transmission: {:?}", transmission);
+    }
+    if let Some(color) = configuration.color() {
+        println!("  Color: {}", color);
+    }
+    if let Some(body_type) = configuration.body_type() {
+        println!("  Body type: {}", body_type);
+    }
+    if let Some(dealer) = configuration.dealer() {
+        println!("  Dealer: {}", dealer);
+    }
+    let sort: Vec<(SortKey, SortOrder)> = configuration
+        .sort_keys()
+        .iter()
+        .map(|&sort_key| (sort_key, configuration.sort_order(sort_key)))
+        .collect();
+    println!("  Sort: {:?}", sort);
     println!("Filtered vehicles found: {}", vehicles.len());
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Args;
+    use crate::vehicle::test_support::{from_json, vehicle_json};
+    use clap::Parser;
+
+    fn vehicle() -> Vehicle {
+        from_json(vehicle_json(42000.0, None))
+    }
+
+    #[test]
+    fn should_navigate_within_bounds() {
+        let configuration =
+            Configuration::new(Args::parse_from(["test"])).expect("Invalid configuration");
+        let (first, second) = (vehicle(), vehicle());
+        let vehicles = [&first, &second];
+        let mut app = App::new(&configuration, &vehicles);
+
+        app.handle_key(KeyCode::Up);
+        assert_eq!(app.state.selected(), Some(0));
+        app.handle_key(KeyCode::Down);
+        app.handle_key(KeyCode::Down);
+        assert_eq!(app.state.selected(), Some(1));
+    }
+
+    #[test]
+    fn should_select_link_on_enter() {
+        let configuration =
+            Configuration::new(Args::parse_from(["test"])).expect("Invalid configuration");
+        let (first, second) = (vehicle(), vehicle());
+        let vehicles = [&first, &second];
+        let mut app = App::new(&configuration, &vehicles);
+
+        app.handle_key(KeyCode::Down);
+        app.handle_key(KeyCode::Enter);
+
+        assert_eq!(app.selected_links, vec![second.get_link("fr-fr")]);
+    }
+
+    #[test]
+    fn should_quit_on_q() {
+        let configuration =
+            Configuration::new(Args::parse_from(["test"])).expect("Invalid configuration");
+        let vehicles: [&Vehicle; 0] = [];
+        let mut app = App::new(&configuration, &vehicles);
+
+        assert!(!app.handle_key(KeyCode::Down));
+        assert!(app.handle_key(KeyCode::Char('q')));
+    }
+}
@@ -0,0 +1,102 @@
+//! Bundled list of marketing model ranges accepted by the search API.
+//! The list only covers common ranges, any other code can still be passed with --model.
+
+/// A marketing model range code with its human-friendly name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModelRange {
+    pub code: &'static str,
+    pub name: &'static str,
+}
+
+const fn model(code: &'static str, name: &'static str) -> ModelRange {
+    ModelRange { code, name }
+}
+
+pub const MODEL_RANGES: &[ModelRange] = &[
+    model("i4_G26E", "BMW i4 Gran Coupé"),
+    model("i5_G60E", "BMW i5 Berline"),
+    model("i5_G61E", "BMW i5 Touring"),
+    model("i7_G70E", "BMW i7"),
+    model("iX_I20", "BMW iX"),
+    model("iX1_U11E", "BMW iX1"),
+    model("iX2_U10E", "BMW iX2"),
+    model("iX3_G08E", "BMW iX3"),
+    model("1_F70", "BMW Série 1"),
+    model("2_U06", "BMW Série 2 Active Tourer"),
+    model("2_G42", "BMW Série 2 Coupé"),
+    model("3_G20", "BMW Série 3 Berline"),
+    model("3_G21", "BMW Série 3 Touring"),
+    model("4_G22", "BMW Série 4 Coupé"),
+    model("4_G26", "BMW Série 4 Gran Coupé"),
+    model("5_G60", "BMW Série 5 Berline"),
+    model("5_G61", "BMW Série 5 Touring"),
+    model("X1_U11", "BMW X1"),
+    model("X2_U10", "BMW X2"),
+    model("X3_G45", "BMW X3"),
+    model("X5_G05", "BMW X5"),
+    model("X6_G06", "BMW X6"),
+    model("X7_G07", "BMW X7"),
+    model("XM_G09", "BMW XM"),
+    model("Z4_G29", "BMW Z4 Roadster"),
+];
+
+/// Friendly name of a bundled model range code, e.g. `BMW iX1` for `iX1_U11E`.
+pub fn model_name(code: &str) -> Option<&'static str> {
+    MODEL_RANGES
+        .iter()
+        .find(|model_range| model_range.code == code)
+        .map(|model_range| model_range.name)
+}
+
+/// Prints the bundled model ranges, one per line.
+pub fn print_model_ranges() {
+    for model_range in MODEL_RANGES {
+        println!("{: <10} {}", model_range.code, model_range.name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn should_not_be_empty() {
+        assert!(!MODEL_RANGES.is_empty());
+    }
+
+    #[test]
+    fn should_find_model_name_of_bundled_range() {
+        assert_eq!(model_name("iX1_U11E"), Some("BMW iX1"));
+        assert_eq!(model_name("Unknown_X99"), None);
+    }
+
+    #[test]
+    fn should_have_well_formed_codes() {
+        for model_range in MODEL_RANGES {
+            let (range, chassis) = model_range
+                .code
+                .split_once('_')
+                .unwrap_or_else(|| panic!("Missing chassis in {}", model_range.code));
+            assert!(!range.is_empty(), "Empty range in {}", model_range.code);
+            assert!(
+                chassis.chars().all(|c| c.is_ascii_alphanumeric()) && !chassis.is_empty(),
+                "Invalid chassis in {}",
+                model_range.code
+            );
+            assert!(!model_range.name.trim().is_empty());
+        }
+    }
+
+    #[test]
+    fn should_have_unique_codes() {
+        let codes: HashSet<&str> = MODEL_RANGES.iter().map(|model| model.code).collect();
+
+        assert_eq!(codes.len(), MODEL_RANGES.len());
+    }
+
+    #[test]
+    fn should_contain_default_model() {
+        assert!(MODEL_RANGES.iter().any(|model| model.code == "iX2_U10E"));
+    }
+}
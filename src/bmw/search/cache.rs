@@ -0,0 +1,142 @@
+//! Disk cache of the search responses, keyed by request URL and body.
+//! Saves API calls when the same search is run again within the TTL.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use reqwest::Url;
+use tracing::warn;
+
+use super::dto::{SearchRequest, SearchResponse};
+
+/// Directory of cached responses and how long they stay fresh.
+pub struct ResponseCache<'a> {
+    dir: &'a Path,
+    ttl: Duration,
+}
+
+impl<'a> ResponseCache<'a> {
+    pub fn new(dir: &'a Path, ttl: Duration) -> Self {
+        Self { dir, ttl }
+    }
+
+    /// Cache entry of a request, named after the hash of its URL and body.
+    pub fn entry(&self, url: &Url, body: &SearchRequest) -> CacheEntry {
+        let body = serde_json::to_vec(body).unwrap_or_default();
+        let hash = fnv1a(&[url.as_str().as_bytes(), &[0], &body]);
+        CacheEntry {
+            path: self.dir.join(format!("{:016x}.json", hash)),
+            ttl: self.ttl,
+        }
+    }
+}
+
+/// 64-bit FNV-1a hash of the concatenated parts. Unlike the standard library
+/// hashers, its output never changes between Rust releases, so the cache files
+/// keep their names across toolchain upgrades.
+fn fnv1a(parts: &[&[u8]]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    parts
+        .iter()
+        .flat_map(|part| part.iter())
+        .fold(OFFSET_BASIS, |hash, &byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+        })
+}
+
+/// Cached response of a single request.
+pub struct CacheEntry {
+    path: PathBuf,
+    ttl: Duration,
+}
+
+impl CacheEntry {
+    /// The cached response, None when it is missing, expired or unreadable.
+    pub fn read(&self) -> Option<SearchResponse> {
+        let modified = fs::metadata(&self.path).and_then(|m| m.modified()).ok()?;
+        let age = SystemTime::now()
+            .duration_since(modified)
+            .unwrap_or_default();
+        if age > self.ttl {
+            return None;
+        }
+        serde_json::from_slice(&fs::read(&self.path).ok()?).ok()
+    }
+
+    /// Stores the response body, a failure only logs a warning.
+    pub fn write(&self, body: &[u8]) {
+        let result = match self.path.parent() {
+            Some(dir) => fs::create_dir_all(dir).and_then(|_| fs::write(&self.path, body)),
+            None => fs::write(&self.path, body),
+        };
+        if let Err(e) = result {
+            warn!(path = %self.path.display(), error = %e, "failed to cache search response");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(model: &str) -> SearchRequest {
+        serde_json::from_value(serde_json::json!({
+            "searchContext": [{ "model": { "marketingModelRange": { "value": [model] } } }]
+        }))
+        .expect("Failed to build request")
+    }
+
+    fn cache_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("bmw_finder_cache_{}", uuid::Uuid::new_v4()))
+    }
+
+    #[test]
+    fn should_read_back_a_fresh_entry() {
+        let dir = cache_dir();
+        let cache = ResponseCache::new(&dir, Duration::from_secs(60));
+        let url = Url::parse("http://localhost/fr-fr/stocklocator").expect("Invalid URL");
+        let entry = cache.entry(&url, &request("iX1_U11E"));
+
+        assert!(entry.read().is_none());
+        entry.write(br#"{ "hits": [], "metadata": { "totalCount": 7 } }"#);
+
+        let page = entry.read().expect("Entry should be cached");
+        assert_eq!(page.metadata.total_count, 7);
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn should_ignore_an_expired_entry() {
+        let dir = cache_dir();
+        let cache = ResponseCache::new(&dir, Duration::ZERO);
+        let url = Url::parse("http://localhost/fr-fr/stocklocator").expect("Invalid URL");
+        let entry = cache.entry(&url, &request("iX1_U11E"));
+
+        entry.write(br#"{ "hits": [], "metadata": { "totalCount": 7 } }"#);
+        std::thread::sleep(Duration::from_millis(10));
+
+        assert!(entry.read().is_none());
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn should_use_different_entries_for_different_requests() {
+        let dir = cache_dir();
+        let cache = ResponseCache::new(&dir, Duration::from_secs(60));
+        let url = Url::parse("http://localhost/fr-fr/stocklocator").expect("Invalid URL");
+
+        assert_ne!(
+            cache.entry(&url, &request("iX1_U11E")).path,
+            cache.entry(&url, &request("i4_G26E")).path
+        );
+    }
+
+    #[test]
+    fn should_hash_with_fnv1a() {
+        assert_eq!(fnv1a(&[]), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(&[b"a"]), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(fnv1a(&[b"foo", b"bar"]), fnv1a(&[b"foobar"]));
+    }
+}
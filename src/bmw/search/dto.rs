@@ -15,6 +15,16 @@ pub struct SearchContext {
     pub model: Option<SearchModel>,
     #[serde(rename = "vssIds", skip_serializing_if = "Option::is_none")]
     pub vss_ids: Option<FilterWithValues>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<SearchLocation>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchLocation {
+    #[serde(rename = "postalCode")]
+    pub postal_code: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub radius: Option<u32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -54,6 +64,7 @@ pub enum SortOrder {
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct SearchResponse {
+    #[serde(default)]
     pub hits: Vec<Hit>,
     pub metadata: Metadata,
 }
@@ -61,6 +72,17 @@ pub struct SearchResponse {
 #[derive(Debug, Clone, Deserialize)]
 pub struct Hit {
     pub vehicle: Vehicle,
+    #[serde(default)]
+    pub score: Option<f32>,
+}
+
+impl Hit {
+    /// The vehicle of the hit, carrying its relevance score.
+    pub fn into_vehicle(self) -> Vehicle {
+        let mut vehicle = self.vehicle;
+        vehicle.set_score(self.score);
+        vehicle
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -0,0 +1,20 @@
+//! Errors returned by the BMW search API client.
+
+use reqwest::StatusCode;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum SearchError {
+    /// The request could not be sent or the response body could not be read.
+    #[error("HTTP request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    /// The API answered with a non-success status.
+    #[error("API returned status {0}")]
+    Status(StatusCode),
+    /// The response body is not a valid search response.
+    #[error("Failed to deserialize the search response: {0}")]
+    Deserialize(#[from] serde_json::Error),
+    /// The search URL could not be built from the configuration.
+    #[error("Failed to build the search URL: {0}")]
+    UrlBuild(#[from] url::ParseError),
+}
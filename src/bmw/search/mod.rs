@@ -1,126 +1,253 @@
 //! BMW API search logic
 // Handles vehicle search logic, API requests, and result aggregation.
 
-use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
 
 use anyhow::Result;
-use futures::{StreamExt, TryStreamExt, stream};
-use reqwest::{Client, Url};
+use chrono::{DateTime, Utc};
+use futures::{Stream, StreamExt, TryStreamExt, future, stream};
+use reqwest::header::RETRY_AFTER;
+use reqwest::{Client, Proxy, StatusCode, Url};
+use tokio_util::sync::CancellationToken;
+use tracing::{Instrument, debug, error, info, info_span, warn};
 use uuid::Uuid;
 
-use crate::config::{Condition, Configuration};
+use crate::config::{self, Brand, Condition, Configuration};
+use crate::progress::PageProgress;
 use crate::vehicle::Vehicle;
+pub mod cache;
 pub mod dto;
+pub mod error;
+pub mod retry;
 
+use self::cache::{CacheEntry, ResponseCache};
 use self::dto::*;
+pub use self::error::SearchError;
+use self::retry::RetryPolicy;
 
-const NEW_CAR_URL: &str = "https://stolo-data-service.prod.stolo.eu-central-1.aws.bmw.cloud/vehiclesearch/search/fr-fr/stocklocator";
-const USED_CAR_URL: &str = "https://stolo-data-service.prod.stolo.eu-central-1.aws.bmw.cloud/vehiclesearch/search/fr-fr/stocklocator_uc";
-const MAX_RESULT: u32 = 50;
-const CONCURRENT_REQUESTS: usize = 5;
+const NEW_CAR_PATH: &str = "stocklocator";
+const USED_CAR_PATH: &str = "stocklocator_uc";
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+/// User agent of the requests unless --user-agent is given.
+pub const DEFAULT_USER_AGENT: &str = concat!("bmw-finder/", env!("CARGO_PKG_VERSION"));
 
 // === Public API ===
 
-/// Search vehicles according to the configuration.
-pub async fn search(configuration: &Configuration) -> Result<HashMap<uuid::Uuid, Vehicle>> {
-    let client = Client::new();
-    let request_body: SearchRequest = SearchRequest {
-        search_context: vec![SearchContext {
-            model: Some(SearchModel {
-                marketing_model_range: FilterWithValues {
-                    value: configuration.models().to_vec(),
-                },
-            }),
-            vss_ids: None,
-        }],
-        results_context: Some(ResultsContext {
-            sort: vec![Sort {
-                by: SortBy::Price,
-                order: SortOrder::Asc,
-            }],
-        }),
+/// Builds the HTTP client shared by every request of the run.
+/// Responses are requested compressed, the search payloads being large JSON documents.
+/// The configured proxy replaces the `HTTPS_PROXY` and `HTTP_PROXY` ones.
+/// Requests identify the tool and its version unless another user agent is configured.
+pub fn build_client(configuration: &Configuration) -> Result<Client> {
+    let mut builder = Client::builder()
+        .user_agent(configuration.user_agent().unwrap_or(DEFAULT_USER_AGENT))
+        .timeout(configuration.timeout())
+        .gzip(true)
+        .deflate(true);
+    if let Some(proxy) = configuration.proxy() {
+        builder = builder.proxy(Proxy::all(proxy.as_str())?);
+    }
+    builder.build().map_err(anyhow::Error::from)
+}
+
+/// Search vehicles according to the configuration until the token is cancelled.
+/// The first pages, which give the total counts, are always fetched. On cancellation the
+/// requests of the remaining pages are dropped and the vehicles fetched so far are returned.
+#[tracing::instrument(skip_all, fields(models = ?configuration.models()))]
+pub async fn search(
+    client: &Client,
+    configuration: &Configuration,
+    cancel: &CancellationToken,
+) -> Result<HashMap<uuid::Uuid, Vehicle>, SearchError> {
+    let request_body = build_search_request(configuration);
+    let (mut vehicles, total_counts) =
+        fetch_first_pages(client, configuration, &request_body).await?;
+    let calls = plan_calls(configuration, &request_body, &total_counts);
+    info!(pages = calls.len(), "pages planned");
+
+    let progress = PageProgress::new(calls.len(), configuration);
+    let pages = match configuration.best_effort() {
+        true => Ok(fetch_pages_best_effort(client, configuration, &calls, &progress, cancel).await),
+        false => fetch_pages(client, configuration, &calls, &progress, cancel).await,
     };
+    progress.finish();
+    vehicles.extend(pages?);
+    if cancel.is_cancelled() {
+        warn!(
+            vehicles = vehicles.len(),
+            "search interrupted, keeping the vehicles collected so far"
+        );
+    }
+    for vehicle in &mut vehicles {
+        vehicle.set_market_from_locale(configuration.locale());
+    }
 
-    let total_count = get_total_count(&client, configuration.condition, request_body.clone()).await;
-    let calls = determine_calls_needed(configuration, request_body.clone(), total_count);
+    let vehicles = index_by_vss_id(vehicles);
+    info!(vehicles = vehicles.len(), "vehicles collected");
+    Ok(vehicles)
+}
 
-    let vehicles = stream::iter(&calls)
-        .map(|call| {
-            query_search(
-                &client,
-                call.condition,
-                call.max_result,
-                call.start_index,
-                call.body.clone(),
-            )
+/// Search vehicles according to the configuration, yielding them as their page
+/// arrives. Vehicles returned by several pages are yielded once, in no particular order.
+pub fn search_stream<'a>(
+    client: &'a Client,
+    configuration: &'a Configuration,
+) -> impl Stream<Item = Result<Vehicle, SearchError>> + 'a {
+    let mut seen = HashSet::new();
+    stream::once(stream_pages(client, configuration))
+        .try_flatten()
+        .try_filter(move |vehicle| future::ready(seen.insert(vehicle.vss_id)))
+        .map_ok(|mut vehicle| {
+            vehicle.set_market_from_locale(configuration.locale());
+            vehicle
         })
-        .buffer_unordered(CONCURRENT_REQUESTS)
-        .try_fold(
-            Vec::with_capacity(calls.len() * (MAX_RESULT as usize)),
-            |mut acc, resp| async move {
-                let SearchResponse { hits, .. } = resp;
-                acc.extend(hits.into_iter().map(|hit| hit.vehicle));
-                Ok(acc)
-            },
-        )
-        .await
-        .map_err(|_| anyhow::anyhow!("Error in one of the requests"))?;
+}
 
-    let vehicles_map: HashMap<Uuid, Vehicle> =
-        vehicles.into_iter().map(|v| (v.vss_id, v)).collect();
-    Ok(vehicles_map)
+/// Counts the vehicles of the search from the total count of every condition,
+/// after the start index and capped to the limit, with a single one-vehicle request
+/// per condition.
+pub async fn count(client: &Client, configuration: &Configuration) -> Result<u32, SearchError> {
+    let request_body = build_search_request(configuration);
+    let mut count = 0;
+    for condition in configuration.conditions() {
+        let page =
+            query_search(client, configuration, condition, 1, 0, request_body.clone()).await?;
+        count += capped_total_count(configuration, page.metadata.total_count);
+    }
+    Ok(count)
+}
+
+/// Prints the requests a search would send after the first page of every condition,
+/// without sending them. The first pages are still fetched to get the total counts.
+pub async fn print_dry_run(
+    client: &Client,
+    configuration: &Configuration,
+) -> Result<(), SearchError> {
+    let request_body = build_search_request(configuration);
+    let (_, total_counts) = fetch_first_pages(client, configuration, &request_body).await?;
+    for (condition, total_count) in &total_counts {
+        println!("{:?}: {} vehicles found", condition, total_count);
+    }
+    let calls = plan_calls(configuration, &request_body, &total_counts);
+    println!("Planned requests: {}", calls.len());
+    for (start_index, url) in planned_urls(configuration, &calls)? {
+        println!("  start index {}: POST {}", start_index, url);
+    }
+    Ok(())
 }
 
-/// Search a vehicle by its VSS ID.
-#[allow(dead_code)]
+/// Search a vehicle by its VSS ID, in every searched condition until it is found.
 pub async fn search_by_vss_id(
+    client: &Client,
     configuration: &Configuration,
     vss_id: &Uuid,
-) -> Result<Option<Vehicle>> {
-    let client = Client::new();
+) -> Result<Option<Vehicle>, SearchError> {
     let request_body: SearchRequest = SearchRequest {
         search_context: vec![SearchContext {
             model: None,
             vss_ids: Some(FilterWithValues {
                 value: vec![vss_id.to_string()],
             }),
+            location: None,
         }],
         results_context: None,
     };
 
-    let response = query_search(&client, configuration.condition, 1, 0, request_body).await;
-
-    match response {
-        Ok(res) if res.hits.is_empty() => Ok(None),
-        Ok(res) if res.hits.first().is_some() => Ok(Some(res.hits[0].vehicle.clone())),
-        Err(e) => {
-            return Err(e);
+    for condition in configuration.conditions() {
+        let response =
+            query_search(client, configuration, condition, 1, 0, request_body.clone()).await?;
+        if let Some(hit) = response.hits.into_iter().next() {
+            let mut vehicle = hit.into_vehicle();
+            vehicle.set_market_from_locale(configuration.locale());
+            return Ok(Some(vehicle));
         }
-        _ => Err(anyhow::anyhow!("Unexpected response format")),
     }
+    Ok(None)
 }
 
 // === Private helpers ===
 
+/// Fetches the first page of every searched condition, which gives its total count,
+/// so only the following pages need to be planned.
+async fn fetch_first_pages(
+    client: &Client,
+    configuration: &Configuration,
+    request_body: &SearchRequest,
+) -> Result<(Vec<Vehicle>, Vec<(Condition, u32)>), SearchError> {
+    let mut vehicles = Vec::new();
+    let mut total_counts = Vec::new();
+    for condition in configuration.conditions() {
+        let first_page = query_search(
+            client,
+            configuration,
+            condition,
+            first_page_size(configuration),
+            configuration.start_index(),
+            request_body.clone(),
+        )
+        .await?;
+        info!(
+            ?condition,
+            total_count = first_page.metadata.total_count,
+            "total count"
+        );
+        total_counts.push((condition, first_page.metadata.total_count));
+        vehicles.extend(first_page.hits.into_iter().map(Hit::into_vehicle));
+    }
+    Ok((vehicles, total_counts))
+}
+
+fn build_search_request(configuration: &Configuration) -> SearchRequest {
+    SearchRequest {
+        search_context: vec![SearchContext {
+            model: Some(SearchModel {
+                marketing_model_range: FilterWithValues {
+                    value: configuration.models().to_vec(),
+                },
+            }),
+            vss_ids: None,
+            location: configuration
+                .postal_code()
+                .map(|postal_code| SearchLocation {
+                    postal_code: String::from(postal_code),
+                    radius: configuration.radius(),
+                }),
+        }],
+        results_context: Some(ResultsContext {
+            sort: vec![Sort {
+                by: SortBy::Price,
+                order: match configuration.sort_order(config::SortKey::Price) {
+                    config::SortOrder::Asc => SortOrder::Asc,
+                    config::SortOrder::Desc => SortOrder::Desc,
+                },
+            }],
+        }),
+    }
+}
+
 fn build_search_url(
+    search_url: &str,
+    brand: Brand,
     condition: Condition,
+    locale: &str,
     max_result: u32,
+    page_size: u32,
     start_index: Option<u32>,
-) -> Result<Url> {
-    let base_url = match condition {
-        Condition::New => NEW_CAR_URL,
-        Condition::Used => USED_CAR_URL,
-    };
+) -> Result<Url, SearchError> {
+    let base_url = format!(
+        "{}/{}/{}",
+        search_url.trim_end_matches('/'),
+        locale,
+        match condition {
+            Condition::New => NEW_CAR_PATH,
+            Condition::Used => USED_CAR_PATH,
+        }
+    );
 
     let params = [
-        ("brand", "BMW"),
-        (
-            "maxResults",
-            match max_result {
-                x if x > MAX_RESULT => &MAX_RESULT.to_string(),
-                x => &x.to_string(),
-            },
-        ),
+        ("brand", brand.as_param()),
+        ("maxResults", &max_result.min(page_size).to_string()),
         (
             "startIndex",
             match start_index {
@@ -130,42 +257,299 @@ fn build_search_url(
         ),
     ];
 
-    Url::parse_with_params(base_url, &params).map_err(anyhow::Error::from)
+    Ok(Url::parse_with_params(&base_url, &params)?)
 }
 
 async fn query_search(
     client: &Client,
+    configuration: &Configuration,
     condition: Condition,
     max_result: u32,
     start_index: u32,
     body: SearchRequest,
-) -> Result<SearchResponse> {
-    let response: reqwest::Response = client
-        .post(build_search_url(condition, max_result, Some(start_index))?)
-        .json(&body)
-        .send()
-        .await?;
+) -> Result<SearchResponse, SearchError> {
+    let url = build_search_url(
+        configuration.base_url(),
+        configuration.brand(),
+        condition,
+        configuration.locale(),
+        max_result,
+        configuration.page_size(),
+        Some(start_index),
+    )?;
+    let cache_entry = configuration
+        .cache_dir()
+        .map(|dir| ResponseCache::new(dir, configuration.cache_ttl()).entry(&url, &body));
+    let span = info_span!("query_search", %url, ?condition, start_index);
+    async move {
+        if let Some(page) = cache_entry.as_ref().and_then(CacheEntry::read) {
+            debug!(hits = page.hits.len(), "search page read from cache");
+            return Ok(page);
+        }
+        let started = Instant::now();
+        let result = match post_with_retry(client, url, &body, configuration.retry_policy()).await {
+            Ok(response) => {
+                debug!(status = %response.status(), "search response received");
+                read_search_response(response, cache_entry.as_ref()).await
+            }
+            Err(e) => Err(e),
+        };
+        let duration_ms = started.elapsed().as_millis() as u64;
+        match &result {
+            Ok(page) => debug!(duration_ms, hits = page.hits.len(), "search page fetched"),
+            Err(e) => error!(duration_ms, error = %e, "search request failed"),
+        }
+        result
+    }
+    .instrument(span)
+    .await
+}
+
+/// Reads the response body, telling transport errors apart from malformed JSON.
+/// Valid responses are stored in the cache entry, if any.
+async fn read_search_response(
+    response: reqwest::Response,
+    cache_entry: Option<&CacheEntry>,
+) -> Result<SearchResponse, SearchError> {
+    let body = response.bytes().await?;
+    let page = serde_json::from_slice(&body)?;
+    if let Some(entry) = cache_entry {
+        entry.write(&body);
+    }
+    Ok(page)
+}
+
+/// Posts the search request, retrying connection errors, 5xx and 429 responses
+/// with the backoff of the retry policy, or after the Retry-After delay of a 429 response.
+/// Other error statuses are returned right away.
+async fn post_with_retry(
+    client: &Client,
+    url: Url,
+    body: &SearchRequest,
+    retry_policy: &RetryPolicy,
+) -> Result<reqwest::Response, SearchError> {
+    let mut attempt = 0;
+    loop {
+        let result = client.post(url.clone()).json(body).send().await;
+        let retriable = match &result {
+            Ok(response) => is_retriable_status(response.status()),
+            Err(e) => e.is_connect() || e.is_timeout(),
+        };
+
+        if !retriable || attempt >= retry_policy.retries() {
+            let response = result?;
+            if !response.status().is_success() {
+                return Err(SearchError::Status(response.status()));
+            }
+            return Ok(response);
+        }
+
+        let delay = match &result {
+            Ok(response) if response.status() == StatusCode::TOO_MANY_REQUESTS => {
+                retry_after_delay(response)
+            }
+            _ => None,
+        };
+        let delay = delay.unwrap_or_else(|| retry_policy.delay(attempt));
+        debug!(attempt, ?delay, "retrying search request");
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+/// Delay requested by the Retry-After header of the response, if any.
+fn retry_after_delay(response: &reqwest::Response) -> Option<Duration> {
+    let value = response.headers().get(RETRY_AFTER)?.to_str().ok()?;
+    parse_retry_after(value, Utc::now())
+}
+
+/// Parses a Retry-After value in seconds or as an HTTP date, capped to MAX_RETRY_AFTER.
+fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    let value = value.trim();
+    let delay = match value.parse::<u64>() {
+        Ok(seconds) => Duration::from_secs(seconds),
+        Err(_) => {
+            let date = DateTime::parse_from_rfc2822(value).ok()?;
+            (date.with_timezone(&Utc) - now)
+                .to_std()
+                .unwrap_or(Duration::ZERO)
+        }
+    };
+    Some(delay.min(MAX_RETRY_AFTER))
+}
+
+fn is_retriable_status(status: StatusCode) -> bool {
+    status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+}
 
-    if !response.status().is_success() {
-        return Err(anyhow::anyhow!("Error: {}", response.status()));
+/// Total count of a condition after the start index, capped to the limit and to the
+/// hard cap of --max-results-total, with a warning when the hard cap truncates it.
+fn capped_total_count(configuration: &Configuration, total_count: u32) -> u32 {
+    let remaining_count = total_count.saturating_sub(configuration.start_index());
+    let count = configuration
+        .limit
+        .map_or(remaining_count, |limit| remaining_count.min(limit));
+    let max_results_total = configuration.max_results_total();
+    if count > max_results_total {
+        warn!(
+            total_count,
+            max_results_total, "total count truncated by --max-results-total"
+        );
+        return max_results_total;
     }
+    count
+}
+
+/// Size of the first page, which never fetches more than the limit or the hard cap.
+fn first_page_size(configuration: &Configuration) -> u32 {
+    configuration
+        .limit
+        .map_or(configuration.page_size(), |limit| {
+            limit.min(configuration.page_size())
+        })
+        .min(configuration.max_results_total())
+}
+
+fn query_call(
+    client: &Client,
+    configuration: &Configuration,
+    call: &CallDefinition,
+) -> impl Future<Output = Result<SearchResponse, SearchError>> {
+    query_search(
+        client,
+        configuration,
+        call.condition,
+        call.max_result,
+        call.start_index,
+        call.body.clone(),
+    )
+}
 
-    response
-        .json::<SearchResponse>()
+/// Fetches every planned page, failing as soon as one of them fails.
+/// Stops at cancellation with the vehicles of the pages fetched so far.
+async fn fetch_pages(
+    client: &Client,
+    configuration: &Configuration,
+    calls: &[CallDefinition],
+    progress: &PageProgress,
+    cancel: &CancellationToken,
+) -> Result<Vec<Vehicle>, SearchError> {
+    stream::iter(calls)
+        .map(|call| query_call(client, configuration, call))
+        .buffer_unordered(configuration.concurrency())
+        .take_until(cancel.cancelled())
+        .inspect(|_| progress.inc())
+        .try_fold(
+            Vec::with_capacity(calls.len() * (configuration.page_size() as usize)),
+            |mut acc, resp| async move {
+                let SearchResponse { hits, .. } = resp;
+                acc.extend(hits.into_iter().map(Hit::into_vehicle));
+                Ok(acc)
+            },
+        )
         .await
-        .map_err(anyhow::Error::from)
 }
 
-async fn get_total_count(client: &Client, condition: Condition, body: SearchRequest) -> u32 {
-    let response = query_search(client, condition, 1, 0, body).await;
+/// Fetches the first pages, then streams the vehicles of the other pages as they arrive.
+/// Failed pages are skipped in best effort mode and end the stream otherwise.
+async fn stream_pages<'a>(
+    client: &'a Client,
+    configuration: &'a Configuration,
+) -> Result<impl Stream<Item = Result<Vehicle, SearchError>> + 'a, SearchError> {
+    let request_body = build_search_request(configuration);
+    let (vehicles, total_counts) = fetch_first_pages(client, configuration, &request_body).await?;
+    let calls = plan_calls(configuration, &request_body, &total_counts);
+    info!(pages = calls.len(), "pages planned");
+
+    let pages = stream::iter(calls)
+        .map(move |call| async move {
+            let page = query_call(client, configuration, &call).await;
+            (call, page)
+        })
+        .buffer_unordered(configuration.concurrency())
+        .filter_map(move |(call, page)| {
+            future::ready(match page {
+                Err(e) if configuration.best_effort() => {
+                    warn!(
+                        condition = ?call.condition,
+                        start_index = call.start_index,
+                        error = %e,
+                        "skipping page"
+                    );
+                    None
+                }
+                page => Some(page),
+            })
+        })
+        .map_ok(|SearchResponse { hits, .. }| {
+            stream::iter(hits.into_iter().map(|hit| Ok(hit.into_vehicle())))
+        })
+        .try_flatten();
+    Ok(stream::iter(vehicles.into_iter().map(Ok)).chain(pages))
+}
+
+/// Fetches every planned page, keeping the vehicles of the pages that succeeded.
+/// Stops at cancellation with the vehicles of the pages fetched so far.
+async fn fetch_pages_best_effort(
+    client: &Client,
+    configuration: &Configuration,
+    calls: &[CallDefinition],
+    progress: &PageProgress,
+    cancel: &CancellationToken,
+) -> Vec<Vehicle> {
+    let pages: Vec<(&CallDefinition, Result<SearchResponse, SearchError>)> = stream::iter(calls)
+        .map(|call| async move { (call, query_call(client, configuration, call).await) })
+        .buffer_unordered(configuration.concurrency())
+        .take_until(cancel.cancelled())
+        .inspect(|_| progress.inc())
+        .collect()
+        .await;
+    keep_successful_pages(pages)
+}
+
+/// Merges the vehicles of the successful pages and logs the failed ones.
+fn keep_successful_pages(
+    pages: Vec<(&CallDefinition, Result<SearchResponse, SearchError>)>,
+) -> Vec<Vehicle> {
+    pages
+        .into_iter()
+        .flat_map(|(call, page)| match page {
+            Ok(SearchResponse { hits, .. }) => hits,
+            Err(e) => {
+                warn!(
+                    condition = ?call.condition,
+                    start_index = call.start_index,
+                    error = %e,
+                    "skipping page"
+                );
+                vec![]
+            }
+        })
+        .map(Hit::into_vehicle)
+        .collect()
+}
 
-    match response {
-        Ok(res) => res.metadata.total_count,
-        Err(e) => {
-            eprintln!("Error fetching total count: {:?}", e);
-            return 0;
+/// Indexes vehicles by VSS ID, so a vehicle returned by several pages or by both
+/// the new and used endpoints is kept once, with its lowest price. A duplicate
+/// without a price never replaces one with a price.
+fn index_by_vss_id(vehicles: Vec<Vehicle>) -> HashMap<Uuid, Vehicle> {
+    let mut indexed: HashMap<Uuid, Vehicle> = HashMap::with_capacity(vehicles.len());
+    for vehicle in vehicles {
+        match indexed.entry(vehicle.vss_id) {
+            Entry::Occupied(mut entry)
+                if vehicle.get_price().is_some_and(|price| {
+                    entry.get().get_price().is_none_or(|kept| price < kept)
+                }) =>
+            {
+                entry.insert(vehicle);
+            }
+            Entry::Occupied(_) => {}
+            Entry::Vacant(entry) => {
+                entry.insert(vehicle);
+            }
         }
     }
+    indexed
 }
 
 struct CallDefinition {
@@ -175,29 +559,68 @@ struct CallDefinition {
     body: SearchRequest,
 }
 
+/// URLs of the planned calls, with their start index.
+fn planned_urls(
+    configuration: &Configuration,
+    calls: &[CallDefinition],
+) -> Result<Vec<(u32, Url)>, SearchError> {
+    calls
+        .iter()
+        .map(|call| {
+            let url = build_search_url(
+                configuration.base_url(),
+                configuration.brand(),
+                call.condition,
+                configuration.locale(),
+                call.max_result,
+                configuration.page_size(),
+                Some(call.start_index),
+            )?;
+            Ok((call.start_index, url))
+        })
+        .collect()
+}
+
+/// Plans the calls following the first page of every searched condition,
+/// the limit applying to each condition.
+fn plan_calls(
+    configuration: &Configuration,
+    body: &SearchRequest,
+    total_counts: &[(Condition, u32)],
+) -> Vec<CallDefinition> {
+    total_counts
+        .iter()
+        .flat_map(|&(condition, total_count)| {
+            determine_calls_needed(configuration, condition, body.clone(), total_count)
+                .into_iter()
+                .skip(1)
+        })
+        .collect()
+}
+
 fn determine_calls_needed(
     configuration: &Configuration,
+    condition: Condition,
     body: SearchRequest,
     total_count: u32,
 ) -> Vec<CallDefinition> {
-    let max = match configuration.limit {
-        Some(l) if total_count > l => l,
-        _ => total_count,
-    };
+    let max = capped_total_count(configuration, total_count);
 
     if max < 1 {
         return vec![];
     }
 
-    let step = if max > MAX_RESULT { MAX_RESULT } else { max };
-
-    // split into chunks of MAX_RESULT
-    (0..max)
-        .step_by(step as usize)
+    // split the window [start, start + max) into chunks of the page size,
+    // the last one only fetching the remaining count
+    let page_size = configuration.page_size();
+    let start = configuration.start_index();
+    let end = start.saturating_add(max);
+    (start..end)
+        .step_by(page_size as usize)
         .map(|start_index| CallDefinition {
-            condition: configuration.condition,
+            condition,
             start_index,
-            max_result: step,
+            max_result: (end - start_index).min(page_size),
             body: body.clone(),
         })
         .collect()
@@ -208,10 +631,39 @@ fn determine_calls_needed(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::{Args, DEFAULT_BASE_URL, DEFAULT_PAGE_SIZE};
+    use crate::vehicle::test_support::{from_json, vehicle_json};
+    use clap::Parser;
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use std::io::Write;
+    use tracing_test::traced_test;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn empty_request() -> SearchRequest {
+        SearchRequest {
+            search_context: vec![],
+            results_context: None,
+        }
+    }
+
+    fn empty_response() -> serde_json::Value {
+        serde_json::json!({ "hits": [], "metadata": { "totalCount": 0 } })
+    }
 
     #[test]
     fn test_build_search_url_with_defaults() {
-        let url = build_search_url(Condition::New, 42, None).expect("Failed to build default URL");
+        let url = build_search_url(
+            DEFAULT_BASE_URL,
+            Brand::Bmw,
+            Condition::New,
+            "fr-fr",
+            42,
+            DEFAULT_PAGE_SIZE,
+            None,
+        )
+        .expect("Failed to build default URL");
         assert_eq!(
             url.as_str(),
             "https://stolo-data-service.prod.stolo.eu-central-1.aws.bmw.cloud/vehiclesearch/search/fr-fr/stocklocator?brand=BMW&maxResults=42&startIndex=0"
@@ -220,37 +672,147 @@ mod tests {
 
     #[test]
     fn test_build_search_url_for_new_cars() {
-        let url =
-            build_search_url(Condition::New, 42, None).expect("Failed to build URL for new cars");
-        assert!(url.as_str().starts_with(NEW_CAR_URL));
+        let url = build_search_url(
+            DEFAULT_BASE_URL,
+            Brand::Bmw,
+            Condition::New,
+            "fr-fr",
+            42,
+            DEFAULT_PAGE_SIZE,
+            None,
+        )
+        .expect("Failed to build URL for new cars");
+        assert!(
+            url.as_str()
+                .starts_with(&format!("{}/fr-fr/stocklocator?", DEFAULT_BASE_URL))
+        );
     }
 
     #[test]
     fn test_build_search_url_for_used_cars() {
-        let url =
-            build_search_url(Condition::Used, 42, None).expect("Failed to build URL for used cars");
-        assert!(url.as_str().starts_with(USED_CAR_URL));
+        let url = build_search_url(
+            DEFAULT_BASE_URL,
+            Brand::Bmw,
+            Condition::Used,
+            "fr-fr",
+            42,
+            DEFAULT_PAGE_SIZE,
+            None,
+        )
+        .expect("Failed to build URL for used cars");
+        assert!(
+            url.as_str()
+                .starts_with(&format!("{}/fr-fr/stocklocator_uc?", DEFAULT_BASE_URL))
+        );
     }
 
     #[test]
     fn test_build_search_url_with_max_results() {
-        let url = build_search_url(Condition::New, 109, None)
-            .expect("Failed to build URL with max_result");
+        let url = build_search_url(
+            DEFAULT_BASE_URL,
+            Brand::Bmw,
+            Condition::New,
+            "fr-fr",
+            109,
+            DEFAULT_PAGE_SIZE,
+            None,
+        )
+        .expect("Failed to build URL with max_result");
         assert_eq!(
             url.as_str(),
             "https://stolo-data-service.prod.stolo.eu-central-1.aws.bmw.cloud/vehiclesearch/search/fr-fr/stocklocator?brand=BMW&maxResults=50&startIndex=0"
         );
     }
+    #[test]
+    fn test_build_search_url_with_page_size() {
+        let url = build_search_url(
+            DEFAULT_BASE_URL,
+            Brand::Bmw,
+            Condition::New,
+            "fr-fr",
+            109,
+            100,
+            None,
+        )
+        .expect("Failed to build URL with page size");
+        assert_eq!(
+            url.as_str(),
+            "https://stolo-data-service.prod.stolo.eu-central-1.aws.bmw.cloud/vehiclesearch/search/fr-fr/stocklocator?brand=BMW&maxResults=100&startIndex=0"
+        );
+    }
+
     #[test]
     fn test_build_search_url_with_start_index() {
-        let url = build_search_url(Condition::New, 42, Some(42000))
-            .expect("Failed to build URL with start index 42000");
+        let url = build_search_url(
+            DEFAULT_BASE_URL,
+            Brand::Bmw,
+            Condition::New,
+            "fr-fr",
+            42,
+            DEFAULT_PAGE_SIZE,
+            Some(42000),
+        )
+        .expect("Failed to build URL with start index 42000");
         assert_eq!(
             url.as_str(),
             "https://stolo-data-service.prod.stolo.eu-central-1.aws.bmw.cloud/vehiclesearch/search/fr-fr/stocklocator?brand=BMW&maxResults=42&startIndex=42000"
         );
     }
 
+    #[test]
+    fn test_build_search_url_for_de_de_locale() {
+        let url = build_search_url(
+            DEFAULT_BASE_URL,
+            Brand::Bmw,
+            Condition::New,
+            "de-de",
+            42,
+            DEFAULT_PAGE_SIZE,
+            None,
+        )
+        .expect("Failed to build URL for de-de locale");
+        assert_eq!(
+            url.as_str(),
+            "https://stolo-data-service.prod.stolo.eu-central-1.aws.bmw.cloud/vehiclesearch/search/de-de/stocklocator?brand=BMW&maxResults=42&startIndex=0"
+        );
+    }
+
+    #[test]
+    fn test_build_search_url_for_es_es_locale() {
+        let url = build_search_url(
+            DEFAULT_BASE_URL,
+            Brand::Bmw,
+            Condition::Used,
+            "es-es",
+            42,
+            DEFAULT_PAGE_SIZE,
+            None,
+        )
+        .expect("Failed to build URL for es-es locale");
+        assert_eq!(
+            url.as_str(),
+            "https://stolo-data-service.prod.stolo.eu-central-1.aws.bmw.cloud/vehiclesearch/search/es-es/stocklocator_uc?brand=BMW&maxResults=42&startIndex=0"
+        );
+    }
+
+    #[test]
+    fn test_build_search_url_for_mini() {
+        let url = build_search_url(
+            DEFAULT_BASE_URL,
+            Brand::Mini,
+            Condition::New,
+            "fr-fr",
+            42,
+            DEFAULT_PAGE_SIZE,
+            None,
+        )
+        .expect("Failed to build URL for MINI");
+        assert_eq!(
+            url.as_str(),
+            "https://stolo-data-service.prod.stolo.eu-central-1.aws.bmw.cloud/vehiclesearch/search/fr-fr/stocklocator?brand=MINI&maxResults=42&startIndex=0"
+        );
+    }
+
     #[test]
     fn test_search_request_serialize() {
         let expected_json = r#"{"searchContext":[{"model":{"marketingModelRange":{"value":["iX2_U10E"]}}}],"resultsContext":{"sort":[{"by":"PRICE","order":"ASC"}]}}"#;
@@ -262,6 +824,7 @@ mod tests {
                     },
                 }),
                 vss_ids: None,
+                location: None,
             }],
             results_context: Some(ResultsContext {
                 sort: vec![Sort {
@@ -287,6 +850,7 @@ mod tests {
                     },
                 }),
                 vss_ids: None,
+                location: None,
             }],
             results_context: Some(ResultsContext {
                 sort: vec![Sort {
@@ -300,4 +864,1195 @@ mod tests {
 
         assert_eq!(request_json, expected_json);
     }
+
+    #[test]
+    fn test_build_search_request_with_default_sort_order() {
+        let configuration = Configuration::new(Args::parse_from(["test", "--model", "iX2_U10E"]))
+            .expect("Invalid configuration");
+
+        let request_json = serde_json::to_string(&build_search_request(&configuration))
+            .expect("Failed to serialize request");
+
+        assert_eq!(
+            request_json,
+            r#"{"searchContext":[{"model":{"marketingModelRange":{"value":["iX2_U10E"]}}}],"resultsContext":{"sort":[{"by":"PRICE","order":"ASC"}]}}"#
+        );
+    }
+
+    #[test]
+    fn test_build_search_request_with_desc_sort_order() {
+        let configuration = Configuration::new(Args::parse_from([
+            "test",
+            "--model",
+            "iX2_U10E",
+            "--sort-order",
+            "desc",
+        ]))
+        .expect("Invalid configuration");
+
+        let request_json = serde_json::to_string(&build_search_request(&configuration))
+            .expect("Failed to serialize request");
+
+        assert!(request_json.contains(r#""order":"DESC""#));
+    }
+
+    #[test]
+    fn test_build_search_request_with_postal_code_and_radius() {
+        let configuration = Configuration::new(Args::parse_from([
+            "test",
+            "--model",
+            "iX2_U10E",
+            "--postal-code",
+            "69002",
+            "--radius",
+            "50",
+        ]))
+        .expect("Invalid configuration");
+
+        let request_json = serde_json::to_string(&build_search_request(&configuration))
+            .expect("Failed to serialize request");
+
+        assert!(request_json.contains(r#""location":{"postalCode":"69002","radius":50}"#));
+    }
+
+    #[test]
+    fn test_build_search_request_with_postal_code_only() {
+        let configuration =
+            Configuration::new(Args::parse_from(["test", "--postal-code", "69002"]))
+                .expect("Invalid configuration");
+
+        let request_json = serde_json::to_string(&build_search_request(&configuration))
+            .expect("Failed to serialize request");
+
+        assert!(request_json.contains(r#""location":{"postalCode":"69002"}"#));
+    }
+
+    fn retry_policy(retries: u32) -> RetryPolicy {
+        RetryPolicy::new(retries, retry::DEFAULT_BASE_DELAY, retry::DEFAULT_MAX_DELAY)
+    }
+
+    #[test]
+    fn test_is_retriable_status() {
+        assert!(is_retriable_status(StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(is_retriable_status(StatusCode::BAD_GATEWAY));
+        assert!(is_retriable_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(!is_retriable_status(StatusCode::NOT_FOUND));
+        assert!(!is_retriable_status(StatusCode::BAD_REQUEST));
+    }
+
+    #[tokio::test]
+    async fn test_post_with_retry_succeeds_after_two_failures() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(500))
+            .up_to_n_times(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(empty_response()))
+            .mount(&server)
+            .await;
+        let url = Url::parse(&server.uri()).expect("Failed to parse mock server URL");
+
+        let response =
+            post_with_retry(&Client::new(), url, &empty_request(), &retry_policy(3)).await;
+
+        assert!(response.is_ok());
+        let requests = server
+            .received_requests()
+            .await
+            .expect("No recorded requests");
+        assert_eq!(requests.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_post_with_retry_honors_retry_after() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "1"))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(empty_response()))
+            .mount(&server)
+            .await;
+        let url = Url::parse(&server.uri()).expect("Failed to parse mock server URL");
+
+        let started = std::time::Instant::now();
+        let response =
+            post_with_retry(&Client::new(), url, &empty_request(), &retry_policy(3)).await;
+
+        assert!(response.is_ok());
+        assert!(started.elapsed() >= Duration::from_secs(1));
+        let requests = server
+            .received_requests()
+            .await
+            .expect("No recorded requests");
+        assert_eq!(requests.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_retry_after_seconds() {
+        assert_eq!(
+            parse_retry_after("2", Utc::now()),
+            Some(Duration::from_secs(2))
+        );
+        assert_eq!(parse_retry_after("3600", Utc::now()), Some(MAX_RETRY_AFTER));
+    }
+
+    #[test]
+    fn test_parse_retry_after_http_date() {
+        let now = DateTime::parse_from_rfc2822("Wed, 21 Oct 2015 07:28:00 GMT")
+            .expect("Invalid date")
+            .with_timezone(&Utc);
+
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:05 GMT", now),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:27:00 GMT", now),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn test_parse_retry_after_invalid_value() {
+        assert_eq!(parse_retry_after("soon", Utc::now()), None);
+    }
+
+    #[tokio::test]
+    async fn test_post_with_retry_gives_up_after_retries() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&server)
+            .await;
+        let url = Url::parse(&server.uri()).expect("Failed to parse mock server URL");
+
+        let response =
+            post_with_retry(&Client::new(), url, &empty_request(), &retry_policy(1)).await;
+
+        assert!(response.is_err());
+        let requests = server
+            .received_requests()
+            .await
+            .expect("No recorded requests");
+        assert_eq!(requests.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_post_with_retry_times_out_on_slow_endpoint() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(empty_response())
+                    .set_delay(Duration::from_secs(5)),
+            )
+            .mount(&server)
+            .await;
+        let url = Url::parse(&server.uri()).expect("Failed to parse mock server URL");
+        let configuration = Configuration::new(Args::parse_from(["test", "--timeout", "1"]))
+            .expect("Invalid configuration");
+        let client = build_client(&configuration).expect("Failed to build client");
+
+        let started = std::time::Instant::now();
+        let response = post_with_retry(&client, url, &empty_request(), &retry_policy(0)).await;
+
+        assert!(started.elapsed() < Duration::from_secs(3));
+        let error = response.expect_err("Request should time out");
+        assert!(matches!(error, SearchError::Http(ref e) if e.is_timeout()));
+    }
+
+    #[tokio::test]
+    async fn test_search_decompresses_gzip_responses() {
+        let server = MockServer::start().await;
+        let body = serde_json::to_vec(&search_response(
+            &["67e55044-10b1-426f-9247-bb680e5fe0c8"],
+            1,
+        ))
+        .expect("Failed to serialize response");
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(&body)
+            .expect("Failed to compress response");
+        mount_page(
+            &server,
+            "0",
+            ResponseTemplate::new(200)
+                .insert_header("Content-Encoding", "gzip")
+                .insert_header("Content-Type", "application/json")
+                .set_body_bytes(encoder.finish().expect("Failed to compress response")),
+        )
+        .await;
+        let configuration = mock_configuration(&server, &[]);
+        let client = build_client(&configuration).expect("Failed to build client");
+
+        let vehicles = search(&client, &configuration, &CancellationToken::new())
+            .await
+            .expect("Search should succeed");
+
+        assert_eq!(vehicles.len(), 1);
+        let requests = server
+            .received_requests()
+            .await
+            .expect("No recorded requests");
+        let accept_encoding = requests[0]
+            .headers
+            .get("accept-encoding")
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        assert!(accept_encoding.contains("gzip"));
+        assert!(accept_encoding.contains("deflate"));
+    }
+
+    async fn received_user_agent(args: &[&str]) -> String {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(empty_response()))
+            .mount(&server)
+            .await;
+        let url = Url::parse(&server.uri()).expect("Failed to parse mock server URL");
+        let configuration = Configuration::new(Args::parse_from(
+            std::iter::once("test").chain(args.iter().copied()),
+        ))
+        .expect("Invalid configuration");
+        let client = build_client(&configuration).expect("Failed to build client");
+
+        post_with_retry(&client, url, &empty_request(), &retry_policy(0))
+            .await
+            .expect("Request should succeed");
+
+        let requests = server
+            .received_requests()
+            .await
+            .expect("No recorded requests");
+        requests[0]
+            .headers
+            .get("user-agent")
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    #[tokio::test]
+    async fn test_build_client_sends_default_user_agent() {
+        let user_agent = received_user_agent(&[]).await;
+
+        assert_eq!(user_agent, DEFAULT_USER_AGENT);
+        assert!(user_agent.starts_with("bmw-finder/"));
+    }
+
+    #[tokio::test]
+    async fn test_build_client_sends_configured_user_agent() {
+        assert_eq!(
+            received_user_agent(&["--user-agent", "my-dashboard/2.0"]).await,
+            "my-dashboard/2.0"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search_goes_through_the_proxy() {
+        let proxy = MockServer::start().await;
+        mount_page(
+            &proxy,
+            "0",
+            ResponseTemplate::new(200).set_body_json(search_response(
+                &["67e55044-10b1-426f-9247-bb680e5fe0c8"],
+                1,
+            )),
+        )
+        .await;
+        let proxy_uri = proxy.uri();
+        let configuration = Configuration::new(Args::parse_from([
+            "test",
+            "--base-url",
+            "http://stolo.invalid",
+            "--proxy",
+            &proxy_uri,
+            "--retries",
+            "0",
+        ]))
+        .expect("Invalid configuration");
+        let client = build_client(&configuration).expect("Failed to build client");
+
+        let vehicles = search(&client, &configuration, &CancellationToken::new())
+            .await
+            .expect("Search should go through the proxy");
+
+        assert_eq!(vehicles.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_post_with_retry_does_not_retry_client_errors() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+        let url = Url::parse(&server.uri()).expect("Failed to parse mock server URL");
+
+        let response =
+            post_with_retry(&Client::new(), url, &empty_request(), &retry_policy(3)).await;
+
+        assert!(matches!(
+            response,
+            Err(SearchError::Status(StatusCode::NOT_FOUND))
+        ));
+        let requests = server
+            .received_requests()
+            .await
+            .expect("No recorded requests");
+        assert_eq!(requests.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_read_search_response_fails_on_malformed_json() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("{ \"hits\": ["))
+            .mount(&server)
+            .await;
+        let url = Url::parse(&server.uri()).expect("Failed to parse mock server URL");
+        let response = post_with_retry(&Client::new(), url, &empty_request(), &retry_policy(0))
+            .await
+            .expect("Request should succeed");
+
+        let result = read_search_response(response, None).await;
+
+        assert!(matches!(result, Err(SearchError::Deserialize(_))));
+    }
+
+    #[tokio::test]
+    async fn test_read_search_response_parses_valid_json() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(empty_response()))
+            .mount(&server)
+            .await;
+        let url = Url::parse(&server.uri()).expect("Failed to parse mock server URL");
+        let response = post_with_retry(&Client::new(), url, &empty_request(), &retry_policy(0))
+            .await
+            .expect("Request should succeed");
+
+        let result = read_search_response(response, None)
+            .await
+            .expect("Response should be parsed");
+
+        assert!(result.hits.is_empty());
+        assert_eq!(result.metadata.total_count, 0);
+    }
+
+    fn vehicle(vss_id: &str, usage_state: &str) -> Vehicle {
+        let mut value = vehicle_json(42000.0, None);
+        value["vssId"] = serde_json::json!(vss_id);
+        value["ordering"]["orderData"]["usageState"] = serde_json::json!(usage_state);
+        from_json(value)
+    }
+
+    #[test]
+    fn test_index_by_vss_id_deduplicates_vehicles() {
+        let vehicle = vehicle("67e55044-10b1-426f-9247-bb680e5fe0c8", "NEW");
+
+        let vehicles = index_by_vss_id(vec![vehicle.clone(), vehicle.clone()]);
+
+        assert_eq!(vehicles.len(), 1);
+        assert!(vehicles.contains_key(&vehicle.vss_id));
+    }
+
+    #[test]
+    fn test_index_by_vss_id_merges_new_and_used_vehicles() {
+        let new_vehicle = vehicle("67e55044-10b1-426f-9247-bb680e5fe0c8", "NEW");
+        let used_vehicle = vehicle("0b4f0b5e-6ad7-4f6c-9d1d-6a8a2c8b7f01", "USED");
+
+        let vehicles = index_by_vss_id(vec![new_vehicle.clone(), used_vehicle.clone()]);
+
+        assert_eq!(vehicles.len(), 2);
+        assert!(vehicles[&new_vehicle.vss_id].is_new());
+        assert!(!vehicles[&used_vehicle.vss_id].is_new());
+    }
+
+    #[test]
+    fn test_index_by_vss_id_keeps_the_cheaper_duplicate() {
+        let vss_id = "67e55044-10b1-426f-9247-bb680e5fe0c8";
+        let priced = |price: f32| {
+            let mut value =
+                serde_json::to_value(vehicle(vss_id, "NEW")).expect("Failed to serialize vehicle");
+            value["price"]["vehicleGrossPrice"] = serde_json::json!(price);
+            serde_json::from_value::<Vehicle>(value).expect("Failed to build vehicle")
+        };
+
+        for vehicles in [
+            vec![priced(42000.0), priced(39000.0)],
+            vec![priced(39000.0), priced(42000.0)],
+        ] {
+            let vehicles = index_by_vss_id(vehicles);
+
+            assert_eq!(vehicles.len(), 1);
+            assert_eq!(
+                vehicles.values().next().and_then(Vehicle::get_price),
+                Some(39000.0)
+            );
+        }
+    }
+
+    #[test]
+    fn test_index_by_vss_id_keeps_the_priced_duplicate() {
+        let vss_id = "67e55044-10b1-426f-9247-bb680e5fe0c8";
+        let with_price = |price: Option<f32>| {
+            let mut value =
+                serde_json::to_value(vehicle(vss_id, "NEW")).expect("Failed to serialize vehicle");
+            value["price"] = serde_json::json!({ "vehicleGrossPrice": price });
+            serde_json::from_value::<Vehicle>(value).expect("Failed to build vehicle")
+        };
+
+        for vehicles in [
+            vec![with_price(None), with_price(Some(42000.0))],
+            vec![with_price(Some(42000.0)), with_price(None)],
+        ] {
+            let vehicles = index_by_vss_id(vehicles);
+
+            assert_eq!(
+                vehicles.values().next().and_then(Vehicle::get_price),
+                Some(42000.0)
+            );
+        }
+    }
+
+    fn mock_configuration(server: &MockServer, args: &[&str]) -> Configuration {
+        let uri = server.uri();
+        Configuration::new(Args::parse_from(
+            ["test", "--base-url", &uri, "--retries", "0"]
+                .into_iter()
+                .chain(args.iter().copied()),
+        ))
+        .expect("Invalid configuration")
+    }
+
+    fn search_response(vss_ids: &[&str], total_count: u32) -> serde_json::Value {
+        let hits: Vec<serde_json::Value> = vss_ids
+            .iter()
+            .map(|vss_id| {
+                serde_json::json!({
+                    "vehicle": serde_json::to_value(vehicle(vss_id, "NEW"))
+                        .expect("Failed to serialize vehicle"),
+                    "score": 1.0,
+                })
+            })
+            .collect();
+        serde_json::json!({ "hits": hits, "metadata": { "totalCount": total_count } })
+    }
+
+    async fn mount_page(server: &MockServer, start_index: &str, response: ResponseTemplate) {
+        Mock::given(method("POST"))
+            .and(path("/fr-fr/stocklocator"))
+            .and(query_param("startIndex", start_index))
+            .respond_with(response)
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_search_against_mock_server() {
+        let server = MockServer::start().await;
+        mount_page(
+            &server,
+            "0",
+            ResponseTemplate::new(200).set_body_json(search_response(
+                &[
+                    "67e55044-10b1-426f-9247-bb680e5fe0c8",
+                    "0b4f0b5e-6ad7-4f6c-9d1d-6a8a2c8b7f01",
+                ],
+                2,
+            )),
+        )
+        .await;
+
+        let vehicles = search(
+            &Client::new(),
+            &mock_configuration(&server, &[]),
+            &CancellationToken::new(),
+        )
+        .await
+        .expect("Search should succeed");
+
+        let mut vss_ids: Vec<String> = vehicles.keys().map(Uuid::to_string).collect();
+        vss_ids.sort();
+        assert_eq!(
+            vss_ids,
+            vec![
+                "0b4f0b5e-6ad7-4f6c-9d1d-6a8a2c8b7f01",
+                "67e55044-10b1-426f-9247-bb680e5fe0c8"
+            ]
+        );
+        assert!(
+            vehicles
+                .values()
+                .all(|vehicle| vehicle.get_score() == Some(1.0))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search_fetches_first_page_at_start_index() {
+        let server = MockServer::start().await;
+        mount_page(
+            &server,
+            "100",
+            ResponseTemplate::new(200).set_body_json(search_response(
+                &["67e55044-10b1-426f-9247-bb680e5fe0c8"],
+                101,
+            )),
+        )
+        .await;
+
+        let vehicles = search(
+            &Client::new(),
+            &mock_configuration(&server, &["--start-index", "100"]),
+            &CancellationToken::new(),
+        )
+        .await
+        .expect("Search should succeed");
+
+        assert_eq!(vehicles.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_search_fails_when_first_page_request_fails() {
+        let server = MockServer::start().await;
+        mount_page(&server, "0", ResponseTemplate::new(500)).await;
+
+        let result = search(
+            &Client::new(),
+            &mock_configuration(&server, &[]),
+            &CancellationToken::new(),
+        )
+        .await;
+
+        assert!(matches!(
+            result,
+            Err(SearchError::Status(StatusCode::INTERNAL_SERVER_ERROR))
+        ));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_search_logs_failed_request_as_error() {
+        let server = MockServer::start().await;
+        mount_page(&server, "0", ResponseTemplate::new(404)).await;
+
+        let result = search(
+            &Client::new(),
+            &mock_configuration(&server, &[]),
+            &CancellationToken::new(),
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert!(logs_contain("ERROR"));
+        assert!(logs_contain("search request failed"));
+    }
+
+    #[tokio::test]
+    async fn test_count_sums_capped_total_counts() {
+        let server = MockServer::start().await;
+        mount_page(
+            &server,
+            "0",
+            ResponseTemplate::new(200).set_body_json(search_response(
+                &["67e55044-10b1-426f-9247-bb680e5fe0c8"],
+                120,
+            )),
+        )
+        .await;
+
+        let count = count(
+            &Client::new(),
+            &mock_configuration(&server, &["--limit", "100"]),
+        )
+        .await
+        .expect("Count should succeed");
+
+        assert_eq!(count, 100);
+    }
+
+    #[tokio::test]
+    async fn test_search_reads_fresh_pages_from_cache() {
+        let server = MockServer::start().await;
+        mount_page(
+            &server,
+            "0",
+            ResponseTemplate::new(200).set_body_json(search_response(
+                &["67e55044-10b1-426f-9247-bb680e5fe0c8"],
+                1,
+            )),
+        )
+        .await;
+        let cache_dir = std::env::temp_dir().join(format!("bmw_finder_cache_{}", Uuid::new_v4()));
+        let cache_dir_arg = cache_dir.display().to_string();
+        let configuration = mock_configuration(&server, &["--cache-dir", &cache_dir_arg]);
+
+        let first = search(&Client::new(), &configuration, &CancellationToken::new())
+            .await
+            .expect("Search should succeed");
+        let second = search(&Client::new(), &configuration, &CancellationToken::new())
+            .await
+            .expect("Cached search should succeed");
+
+        assert_eq!(first.len(), 1);
+        assert_eq!(second.len(), 1);
+        let requests = server
+            .received_requests()
+            .await
+            .expect("No recorded requests");
+        assert_eq!(requests.len(), 1);
+        std::fs::remove_dir_all(&cache_dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_search_refreshes_expired_cache_entries() {
+        let server = MockServer::start().await;
+        mount_page(
+            &server,
+            "0",
+            ResponseTemplate::new(200).set_body_json(search_response(
+                &["67e55044-10b1-426f-9247-bb680e5fe0c8"],
+                1,
+            )),
+        )
+        .await;
+        let cache_dir = std::env::temp_dir().join(format!("bmw_finder_cache_{}", Uuid::new_v4()));
+        let cache_dir_arg = cache_dir.display().to_string();
+        let configuration = mock_configuration(
+            &server,
+            &["--cache-dir", &cache_dir_arg, "--cache-ttl", "0"],
+        );
+
+        for _ in 0..2 {
+            search(&Client::new(), &configuration, &CancellationToken::new())
+                .await
+                .expect("Search should succeed");
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let requests = server
+            .received_requests()
+            .await
+            .expect("No recorded requests");
+        assert_eq!(requests.len(), 2);
+        std::fs::remove_dir_all(&cache_dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_search_only_requests_needed_pages() {
+        let server = MockServer::start().await;
+        for start_index in ["0", "50", "100"] {
+            mount_page(
+                &server,
+                start_index,
+                ResponseTemplate::new(200).set_body_json(search_response(&[], 120)),
+            )
+            .await;
+        }
+
+        search(
+            &Client::new(),
+            &mock_configuration(&server, &[]),
+            &CancellationToken::new(),
+        )
+        .await
+        .expect("Search should succeed");
+
+        let requests = server
+            .received_requests()
+            .await
+            .expect("No recorded requests");
+        assert_eq!(requests.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_search_stream_yields_every_vehicle_once() {
+        let server = MockServer::start().await;
+        mount_page(
+            &server,
+            "0",
+            ResponseTemplate::new(200).set_body_json(search_response(
+                &[
+                    "67e55044-10b1-426f-9247-bb680e5fe0c8",
+                    "0b4f0b5e-6ad7-4f6c-9d1d-6a8a2c8b7f01",
+                ],
+                120,
+            )),
+        )
+        .await;
+        mount_page(
+            &server,
+            "50",
+            ResponseTemplate::new(200).set_body_json(search_response(
+                &[
+                    "0b4f0b5e-6ad7-4f6c-9d1d-6a8a2c8b7f01",
+                    "3f2b8c1e-9a4d-4e7b-8c6f-1d2e3f4a5b6c",
+                ],
+                120,
+            )),
+        )
+        .await;
+        mount_page(
+            &server,
+            "100",
+            ResponseTemplate::new(200).set_body_json(search_response(
+                &["a1b2c3d4-e5f6-4a7b-8c9d-0e1f2a3b4c5d"],
+                120,
+            )),
+        )
+        .await;
+
+        let configuration = mock_configuration(&server, &[]);
+        let vehicles: Vec<Vehicle> = search_stream(&Client::new(), &configuration)
+            .try_collect()
+            .await
+            .expect("Stream should succeed");
+
+        let mut vss_ids: Vec<String> = vehicles.iter().map(|v| v.vss_id.to_string()).collect();
+        vss_ids.sort();
+        assert_eq!(
+            vss_ids,
+            vec![
+                "0b4f0b5e-6ad7-4f6c-9d1d-6a8a2c8b7f01",
+                "3f2b8c1e-9a4d-4e7b-8c6f-1d2e3f4a5b6c",
+                "67e55044-10b1-426f-9247-bb680e5fe0c8",
+                "a1b2c3d4-e5f6-4a7b-8c9d-0e1f2a3b4c5d"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search_stream_fails_without_best_effort() {
+        let server = MockServer::start().await;
+        mount_pages_with_failure(&server).await;
+
+        let configuration = mock_configuration(&server, &[]);
+        let result: Result<Vec<Vehicle>, SearchError> =
+            search_stream(&Client::new(), &configuration)
+                .try_collect()
+                .await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_search_stream_best_effort_skips_failed_pages() {
+        let server = MockServer::start().await;
+        mount_pages_with_failure(&server).await;
+
+        let configuration = mock_configuration(&server, &["--best-effort"]);
+        let vehicles: Vec<Vehicle> = search_stream(&Client::new(), &configuration)
+            .try_collect()
+            .await
+            .expect("Best effort stream should succeed");
+
+        assert_eq!(vehicles.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_search_returns_the_collected_vehicles_when_cancelled() {
+        let server = MockServer::start().await;
+        mount_page(
+            &server,
+            "0",
+            ResponseTemplate::new(200).set_body_json(search_response(
+                &["67e55044-10b1-426f-9247-bb680e5fe0c8"],
+                150,
+            )),
+        )
+        .await;
+        mount_page(
+            &server,
+            "50",
+            ResponseTemplate::new(200).set_body_json(search_response(
+                &["0b4f0b5e-6ad7-4f6c-9d1d-6a8a2c8b7f01"],
+                150,
+            )),
+        )
+        .await;
+        mount_page(
+            &server,
+            "100",
+            ResponseTemplate::new(200)
+                .set_body_json(search_response(
+                    &["9d3c1a2b-4e5f-4a6b-8c7d-0e1f2a3b4c5d"],
+                    150,
+                ))
+                .set_delay(Duration::from_secs(10)),
+        )
+        .await;
+        let cancel = CancellationToken::new();
+        let trigger = cancel.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(500)).await;
+            trigger.cancel();
+        });
+
+        let started = Instant::now();
+        let vehicles = search(&Client::new(), &mock_configuration(&server, &[]), &cancel)
+            .await
+            .expect("Cancelled search should succeed");
+
+        assert!(started.elapsed() < Duration::from_secs(5));
+        let mut vss_ids: Vec<String> = vehicles.keys().map(Uuid::to_string).collect();
+        vss_ids.sort();
+        assert_eq!(
+            vss_ids,
+            vec![
+                "0b4f0b5e-6ad7-4f6c-9d1d-6a8a2c8b7f01",
+                "67e55044-10b1-426f-9247-bb680e5fe0c8"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search_returns_the_first_pages_when_already_cancelled() {
+        let server = MockServer::start().await;
+        mount_page(
+            &server,
+            "0",
+            ResponseTemplate::new(200).set_body_json(search_response(
+                &["67e55044-10b1-426f-9247-bb680e5fe0c8"],
+                150,
+            )),
+        )
+        .await;
+        for start_index in ["50", "100"] {
+            mount_page(
+                &server,
+                start_index,
+                ResponseTemplate::new(200).set_body_json(search_response(
+                    &["0b4f0b5e-6ad7-4f6c-9d1d-6a8a2c8b7f01"],
+                    150,
+                )),
+            )
+            .await;
+        }
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+
+        let vehicles = search(&Client::new(), &mock_configuration(&server, &[]), &cancel)
+            .await
+            .expect("Cancelled search should succeed");
+
+        let vss_ids: Vec<String> = vehicles.keys().map(Uuid::to_string).collect();
+        assert_eq!(vss_ids, vec!["67e55044-10b1-426f-9247-bb680e5fe0c8"]);
+    }
+
+    async fn mount_pages_with_failure(server: &MockServer) {
+        mount_page(
+            server,
+            "0",
+            ResponseTemplate::new(200).set_body_json(search_response(
+                &["67e55044-10b1-426f-9247-bb680e5fe0c8"],
+                150,
+            )),
+        )
+        .await;
+        mount_page(server, "50", ResponseTemplate::new(500)).await;
+        mount_page(
+            server,
+            "100",
+            ResponseTemplate::new(200).set_body_json(search_response(
+                &["0b4f0b5e-6ad7-4f6c-9d1d-6a8a2c8b7f01"],
+                150,
+            )),
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_search_best_effort_returns_partial_results() {
+        let server = MockServer::start().await;
+        mount_pages_with_failure(&server).await;
+
+        let vehicles = search(
+            &Client::new(),
+            &mock_configuration(&server, &["--best-effort"]),
+            &CancellationToken::new(),
+        )
+        .await
+        .expect("Best effort search should succeed");
+
+        assert_eq!(vehicles.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_search_fails_fast_without_best_effort() {
+        let server = MockServer::start().await;
+        mount_pages_with_failure(&server).await;
+
+        let result = search(
+            &Client::new(),
+            &mock_configuration(&server, &[]),
+            &CancellationToken::new(),
+        )
+        .await;
+
+        assert!(matches!(
+            result,
+            Err(SearchError::Status(StatusCode::INTERNAL_SERVER_ERROR))
+        ));
+    }
+
+    #[test]
+    fn test_keep_successful_pages_returns_partial_results() {
+        let calls = determine_calls_needed(
+            &Configuration::new(Args::parse_from(["test"])).expect("Invalid configuration"),
+            Condition::New,
+            empty_request(),
+            150,
+        );
+        let page = |vss_id: &str| -> SearchResponse {
+            serde_json::from_value(serde_json::json!({
+                "hits": [{ "vehicle": serde_json::to_value(vehicle(vss_id, "NEW")).expect("Failed to serialize vehicle") }],
+                "metadata": { "totalCount": 150 },
+            }))
+            .expect("Failed to build response")
+        };
+        let pages = vec![
+            (&calls[0], Ok(page("67e55044-10b1-426f-9247-bb680e5fe0c8"))),
+            (
+                &calls[1],
+                Err(SearchError::Status(StatusCode::INTERNAL_SERVER_ERROR)),
+            ),
+            (&calls[2], Ok(page("0b4f0b5e-6ad7-4f6c-9d1d-6a8a2c8b7f01"))),
+        ];
+
+        let vehicles = keep_successful_pages(pages);
+
+        assert_eq!(
+            vehicles
+                .iter()
+                .map(|vehicle| vehicle.vss_id.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "67e55044-10b1-426f-9247-bb680e5fe0c8",
+                "0b4f0b5e-6ad7-4f6c-9d1d-6a8a2c8b7f01"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_plan_calls_for_both_conditions() {
+        let configuration =
+            Configuration::new(Args::parse_from(["test", "--all"])).expect("Invalid configuration");
+        let total_counts: Vec<(Condition, u32)> = configuration
+            .conditions()
+            .into_iter()
+            .zip([125, 70])
+            .collect();
+
+        let calls: Vec<(Condition, u32, u32)> =
+            plan_calls(&configuration, &empty_request(), &total_counts)
+                .iter()
+                .map(|call| (call.condition, call.start_index, call.max_result))
+                .collect();
+
+        assert_eq!(
+            calls,
+            vec![
+                (Condition::New, 50, 50),
+                (Condition::New, 100, 25),
+                (Condition::Used, 50, 20),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_planned_urls_for_total_count_and_limit() {
+        let configuration = Configuration::new(Args::parse_from([
+            "test",
+            "--base-url",
+            "http://localhost:8080",
+            "--limit",
+            "120",
+        ]))
+        .expect("Invalid configuration");
+        let calls = plan_calls(&configuration, &empty_request(), &[(Condition::New, 300)]);
+
+        let urls: Vec<(u32, String)> = planned_urls(&configuration, &calls)
+            .expect("Failed to build planned URLs")
+            .into_iter()
+            .map(|(start_index, url)| (start_index, url.to_string()))
+            .collect();
+
+        assert_eq!(
+            urls,
+            vec![
+                (
+                    50,
+                    String::from(
+                        "http://localhost:8080/fr-fr/stocklocator?brand=BMW&maxResults=50&startIndex=50"
+                    )
+                ),
+                (
+                    100,
+                    String::from(
+                        "http://localhost:8080/fr-fr/stocklocator?brand=BMW&maxResults=20&startIndex=100"
+                    )
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_plan_calls_only_plans_pages_after_the_first() {
+        let configuration =
+            Configuration::new(Args::parse_from(["test"])).expect("Invalid configuration");
+
+        let calls: Vec<u32> =
+            plan_calls(&configuration, &empty_request(), &[(Condition::New, 120)])
+                .iter()
+                .map(|call| call.start_index)
+                .collect();
+
+        assert_eq!(calls, vec![50, 100]);
+    }
+
+    #[test]
+    fn test_plan_calls_without_more_pages() {
+        let configuration = Configuration::new(Args::parse_from(["test", "--limit", "30"]))
+            .expect("Invalid configuration");
+
+        assert!(plan_calls(&configuration, &empty_request(), &[(Condition::New, 1000)]).is_empty());
+    }
+
+    #[test]
+    fn test_first_page_size_does_not_exceed_limit() {
+        let configuration = |args: &[&str]| {
+            Configuration::new(Args::parse_from(
+                std::iter::once("test").chain(args.iter().copied()),
+            ))
+            .expect("Invalid configuration")
+        };
+
+        assert_eq!(first_page_size(&configuration(&[])), DEFAULT_PAGE_SIZE);
+        assert_eq!(first_page_size(&configuration(&["--limit", "20"])), 20);
+        assert_eq!(
+            first_page_size(&configuration(&["--limit", "120"])),
+            DEFAULT_PAGE_SIZE
+        );
+        assert_eq!(
+            first_page_size(&configuration(&["--limit", "120", "--page-size", "100"])),
+            100
+        );
+    }
+
+    fn planned_calls(args: &[&str], total_count: u32) -> Vec<(u32, u32)> {
+        let configuration = Configuration::new(Args::parse_from(
+            std::iter::once("test").chain(args.iter().copied()),
+        ))
+        .expect("Invalid configuration");
+        determine_calls_needed(
+            &configuration,
+            configuration.condition,
+            empty_request(),
+            total_count,
+        )
+        .iter()
+        .map(|call| (call.start_index, call.max_result))
+        .collect()
+    }
+
+    #[test]
+    fn test_determine_calls_needed_does_not_overfetch_limit_of_120() {
+        let calls = planned_calls(&["--limit", "120"], 1000);
+
+        assert_eq!(calls, vec![(0, 50), (50, 50), (100, 20)]);
+        assert_eq!(calls.iter().map(|(_, max)| max).sum::<u32>(), 120);
+    }
+
+    #[test]
+    fn test_determine_calls_needed_with_limit_of_100() {
+        let calls = planned_calls(&["--limit", "100"], 1000);
+
+        assert_eq!(calls, vec![(0, 50), (50, 50)]);
+        assert_eq!(calls.iter().map(|(_, max)| max).sum::<u32>(), 100);
+    }
+
+    #[test]
+    fn test_determine_calls_needed_with_limit_of_49() {
+        let calls = planned_calls(&["--limit", "49"], 1000);
+
+        assert_eq!(calls, vec![(0, 49)]);
+    }
+
+    #[test]
+    fn test_determine_calls_needed_with_page_size() {
+        let calls = planned_calls(&["--page-size", "100", "--limit", "250"], 1000);
+
+        assert_eq!(calls, vec![(0, 100), (100, 100), (200, 50)]);
+    }
+
+    #[test]
+    fn test_determine_calls_needed_without_limit() {
+        let calls = planned_calls(&[], 75);
+
+        assert_eq!(calls, vec![(0, 50), (50, 25)]);
+    }
+
+    #[test]
+    #[traced_test]
+    fn test_determine_calls_needed_respects_max_results_total_without_limit() {
+        let calls = planned_calls(&[], 100_000);
+
+        assert_eq!(calls.len(), 10);
+        assert_eq!(calls.iter().map(|(_, max)| max).sum::<u32>(), 500);
+        assert!(logs_contain("total count truncated by --max-results-total"));
+    }
+
+    #[test]
+    fn test_determine_calls_needed_with_lower_max_results_total() {
+        let calls = planned_calls(&["--max-results-total", "120", "--limit", "200"], 1000);
+
+        assert_eq!(calls, vec![(0, 50), (50, 50), (100, 20)]);
+    }
+
+    #[test]
+    fn test_first_page_size_does_not_exceed_max_results_total() {
+        let configuration =
+            Configuration::new(Args::parse_from(["test", "--max-results-total", "20"]))
+                .expect("Invalid configuration");
+
+        assert_eq!(first_page_size(&configuration), 20);
+    }
+
+    #[test]
+    fn test_determine_calls_needed_with_no_results() {
+        assert!(planned_calls(&["--limit", "10"], 0).is_empty());
+    }
+
+    #[test]
+    fn test_determine_calls_needed_with_start_index_and_limit() {
+        let calls = planned_calls(&["--start-index", "100", "--limit", "120"], 1000);
+
+        assert_eq!(calls, vec![(100, 50), (150, 50), (200, 20)]);
+    }
+
+    #[test]
+    fn test_determine_calls_needed_with_start_index_near_total_count() {
+        let calls = planned_calls(&["--start-index", "60", "--limit", "50"], 75);
+
+        assert_eq!(calls, vec![(60, 15)]);
+    }
+
+    #[test]
+    fn test_determine_calls_needed_with_start_index_without_limit() {
+        let calls = planned_calls(&["--start-index", "25"], 150);
+
+        assert_eq!(calls, vec![(25, 50), (75, 50), (125, 25)]);
+    }
+
+    #[test]
+    fn test_determine_calls_needed_with_start_index_beyond_total_count() {
+        assert!(planned_calls(&["--start-index", "80", "--limit", "10"], 75).is_empty());
+        assert!(planned_calls(&["--start-index", "75"], 75).is_empty());
+    }
 }
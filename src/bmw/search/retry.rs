@@ -0,0 +1,93 @@
+//! Retry policy of the search requests.
+//! Failed requests are retried with an exponential backoff, capped and jittered.
+
+use std::time::Duration;
+
+/// Delay before the first retry, doubled on every following one.
+pub const DEFAULT_BASE_DELAY: Duration = Duration::from_millis(250);
+/// Longest backoff between two retries, before jitter.
+pub const DEFAULT_MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// How many times a failed request is retried, and how long to wait in between.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RetryPolicy {
+    retries: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+}
+
+impl RetryPolicy {
+    pub fn new(retries: u32, base_delay: Duration, max_delay: Duration) -> Self {
+        Self {
+            retries,
+            base_delay,
+            max_delay,
+        }
+    }
+
+    /// Number of retries after the first attempt.
+    pub fn retries(&self) -> u32 {
+        self.retries
+    }
+
+    /// Backoff before the retry following the given attempt, starting at 0:
+    /// `min(base * 2^attempt, max)`.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        self.base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay)
+    }
+
+    /// Backoff of the attempt plus up to 50% of random jitter.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let backoff = self.backoff(attempt);
+        let jitter = rand::random_range(0..=backoff.as_millis() as u64 / 2);
+        backoff + Duration::from_millis(jitter)
+    }
+}
+
+/// No retries, failed requests being reported right away.
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new(0, DEFAULT_BASE_DELAY, DEFAULT_MAX_DELAY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy::new(5, Duration::from_millis(100), Duration::from_millis(1000))
+    }
+
+    #[test]
+    fn should_not_retry_by_default() {
+        assert_eq!(RetryPolicy::default().retries(), 0);
+    }
+
+    #[test]
+    fn should_double_backoff_on_every_attempt() {
+        assert_eq!(policy().backoff(0), Duration::from_millis(100));
+        assert_eq!(policy().backoff(1), Duration::from_millis(200));
+        assert_eq!(policy().backoff(2), Duration::from_millis(400));
+        assert_eq!(policy().backoff(3), Duration::from_millis(800));
+    }
+
+    #[test]
+    fn should_clamp_backoff_at_max_delay() {
+        assert_eq!(policy().backoff(4), Duration::from_millis(1000));
+        assert_eq!(policy().backoff(10), Duration::from_millis(1000));
+        assert_eq!(policy().backoff(u32::MAX), Duration::from_millis(1000));
+    }
+
+    #[test]
+    fn should_add_up_to_half_of_backoff_as_jitter() {
+        for attempt in 0..6 {
+            let backoff = policy().backoff(attempt);
+            let delay = policy().delay(attempt);
+            assert!(delay >= backoff);
+            assert!(delay <= backoff + backoff / 2);
+        }
+    }
+}
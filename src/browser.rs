@@ -0,0 +1,71 @@
+//! Browser module for the BMW Finder application.
+//! Opens the links of the best matching vehicles in the default browser.
+
+use anyhow::Result;
+
+use crate::vehicle::Vehicle;
+
+/// Opens the links of the first `count` vehicles, in their sorted order.
+/// A missing browser backend only logs a warning.
+pub fn open_vehicles(vehicles: &[&Vehicle], count: usize, locale: &str) {
+    for link in links_to_open(vehicles, count, locale) {
+        if let Err(e) = open_link(&link) {
+            eprintln!("Warning: failed to open {}: {:#}", link, e);
+        }
+    }
+}
+
+/// Links of the first `count` vehicles, fewer when there are not enough vehicles.
+fn links_to_open(vehicles: &[&Vehicle], count: usize, locale: &str) -> Vec<String> {
+    vehicles
+        .iter()
+        .take(count)
+        .map(|vehicle| vehicle.get_link(locale))
+        .collect()
+}
+
+#[cfg(feature = "open")]
+fn open_link(link: &str) -> Result<()> {
+    open::that(link)?;
+    Ok(())
+}
+
+#[cfg(not(feature = "open"))]
+fn open_link(_link: &str) -> Result<()> {
+    anyhow::bail!("built without the open feature")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vehicle::test_support::{from_json, vehicle_json};
+
+    fn vehicle(price: f32) -> Vehicle {
+        from_json(vehicle_json(price, None))
+    }
+
+    #[test]
+    fn should_choose_the_first_vehicles_in_order() {
+        let (first, second, third) = (vehicle(100.0), vehicle(200.0), vehicle(300.0));
+
+        assert_eq!(
+            links_to_open(&[&first, &second, &third], 2, "fr-fr"),
+            vec![first.get_link("fr-fr"), second.get_link("fr-fr")]
+        );
+    }
+
+    #[test]
+    fn should_cap_to_the_number_of_vehicles() {
+        let vehicle = vehicle(100.0);
+
+        assert_eq!(
+            links_to_open(&[&vehicle], 5, "fr-fr"),
+            vec![vehicle.get_link("fr-fr")]
+        );
+    }
+
+    #[test]
+    fn should_choose_nothing_without_vehicles() {
+        assert!(links_to_open(&[], 1, "fr-fr").is_empty());
+    }
+}
@@ -0,0 +1,51 @@
+//! Color module for the BMW Finder application.
+//! Strips the ANSI colors of the text output according to the configured color mode.
+
+use anstream::{AutoStream, ColorChoice, stream::RawStream};
+
+use crate::config::ColorMode;
+
+/// Wraps a writer so the colors written to it are kept or stripped as configured.
+/// In auto mode, colors are kept for terminals only and never when `NO_COLOR` is set.
+pub fn color_stream<W: RawStream>(writer: W, mode: ColorMode) -> AutoStream<W> {
+    AutoStream::new(
+        writer,
+        match mode {
+            ColorMode::Auto => ColorChoice::Auto,
+            ColorMode::Always => ColorChoice::AlwaysAnsi,
+            ColorMode::Never => ColorChoice::Never,
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use owo_colors::OwoColorize;
+    use std::io::Write;
+
+    fn render(mode: ColorMode) -> String {
+        let mut stream = color_stream(Vec::new(), mode);
+        write!(stream, "{} | {}", "-25,00 %".green(), "42 000,00 €".bold())
+            .expect("Failed to write output");
+        String::from_utf8(stream.into_inner()).expect("Invalid UTF-8 output")
+    }
+
+    #[test]
+    fn should_strip_ansi_sequences_with_never() {
+        let output = render(ColorMode::Never);
+
+        assert!(!output.contains('\x1b'));
+        assert_eq!(output, "-25,00 % | 42 000,00 €");
+    }
+
+    #[test]
+    fn should_keep_ansi_sequences_with_always() {
+        assert!(render(ColorMode::Always).contains('\x1b'));
+    }
+
+    #[test]
+    fn should_not_color_buffers_with_auto() {
+        assert!(!render(ColorMode::Auto).contains('\x1b'));
+    }
+}
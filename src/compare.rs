@@ -0,0 +1,238 @@
+//! Compare module for the BMW Finder application.
+//! Prints two vehicles side by side to help choosing between them.
+
+use std::io::{self, Write};
+
+use anyhow::{Context, Result, bail};
+use uuid::Uuid;
+
+use crate::config::{Configuration, OutputField};
+use crate::legacy::{text_cell, text_header};
+use crate::source::VehicleSource;
+use crate::vehicle::Vehicle;
+
+/// Fields compared, in order.
+const COMPARED_FIELDS: [OutputField; 16] = [
+    OutputField::Id,
+    OutputField::Model,
+    OutputField::Body,
+    OutputField::Price,
+    OutputField::Discount,
+    OutputField::Savings,
+    OutputField::Mileage,
+    OutputField::Registration,
+    OutputField::Power,
+    OutputField::Fuel,
+    OutputField::Transmission,
+    OutputField::Color,
+    OutputField::Interior,
+    OutputField::Dealer,
+    OutputField::Status,
+    OutputField::Link,
+];
+
+/// Fetches both vehicles and prints them side by side.
+pub async fn run(
+    source: &impl VehicleSource,
+    configuration: &Configuration,
+    vss_ids: (Uuid, Uuid),
+) -> Result<()> {
+    let (first, second) = find_vehicles(source, configuration, vss_ids).await?;
+    write_comparison(&first, &second, configuration, &mut io::stdout().lock())?;
+    Ok(())
+}
+
+/// Fetches both vehicles, failing with the IDs that were not found.
+async fn find_vehicles(
+    source: &impl VehicleSource,
+    configuration: &Configuration,
+    (first_id, second_id): (Uuid, Uuid),
+) -> Result<(Vehicle, Vehicle)> {
+    let (first, second) = futures::try_join!(
+        source.find(configuration, &first_id),
+        source.find(configuration, &second_id)
+    )
+    .context("Error during search")?;
+    match (first, second) {
+        (Some(first), Some(second)) => Ok((first, second)),
+        (None, None) => bail!("No vehicle found for {} and {}", first_id, second_id),
+        (None, Some(_)) => bail!("No vehicle found for {}", first_id),
+        (Some(_), None) => bail!("No vehicle found for {}", second_id),
+    }
+}
+
+/// Writes one row per compared field, rows whose values differ being marked with `*`.
+/// The equipment only one of the vehicles has is listed last.
+fn write_comparison(
+    first: &Vehicle,
+    second: &Vehicle,
+    configuration: &Configuration,
+    writer: &mut impl Write,
+) -> io::Result<()> {
+    let mut rows: Vec<_> = COMPARED_FIELDS
+        .iter()
+        .map(|&field| {
+            (
+                text_header(field),
+                text_cell(field, first, configuration),
+                text_cell(field, second, configuration),
+            )
+        })
+        .collect();
+    rows.extend(equipment_rows(first, second, configuration));
+
+    let width = rows
+        .iter()
+        .map(|(_, first, _)| first.chars().count())
+        .max()
+        .unwrap_or_default();
+    for (label, first, second) in rows {
+        let marker = if first == second { ' ' } else { '*' };
+        writeln!(
+            writer,
+            "{} {: <14} {: <width$} {}",
+            marker,
+            label,
+            first,
+            second,
+            width = width
+        )?;
+    }
+    Ok(())
+}
+
+/// Rows of the equipment only one of the vehicles has, a dash on the other side.
+fn equipment_rows<'a>(
+    first: &'a Vehicle,
+    second: &'a Vehicle,
+    configuration: &Configuration,
+) -> Vec<(&'static str, String, String)> {
+    let locale = configuration.equipment_locale();
+    let first_equipments = first.get_equipments(locale);
+    let second_equipments = second.get_equipments(locale);
+    let only_in = |equipments: &[(&'a str, Option<&'a str>)], other: &Vehicle| {
+        equipments
+            .iter()
+            .filter(|(code, _)| !other.has_equipment_code(code))
+            .map(|(code, name)| match name {
+                Some(name) => format!("{} {}", code, name),
+                None => code.to_string(),
+            })
+            .collect::<Vec<_>>()
+    };
+    let dash = || String::from("-");
+
+    only_in(&first_equipments, second)
+        .into_iter()
+        .map(|equipment| (equipment, dash()))
+        .chain(
+            only_in(&second_equipments, first)
+                .into_iter()
+                .map(|equipment| (dash(), equipment)),
+        )
+        .enumerate()
+        .map(|(index, (first, second))| {
+            let label = if index == 0 { "Equipment" } else { "" };
+            (label, first, second)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Args;
+    use crate::source::FakeSource;
+    use crate::vehicle::test_support::{from_json, vehicle_json};
+    use clap::Parser;
+    use serde_json::json;
+
+    const FIRST_ID: &str = "67e55044-10b1-426f-9247-bb680e5fe0c8";
+    const SECOND_ID: &str = "0b4f0b5e-6ad7-4f6c-9d1d-6a8a2c8b7f01";
+
+    fn vehicle(vss_id: &str, price: f32, equipments: serde_json::Value) -> Vehicle {
+        let mut value = vehicle_json(price, None);
+        value["vssId"] = json!(vss_id);
+        value["vehicleSpecification"] = json!({
+            "modelAndOption": { "equipments": equipments },
+            "marketingModelRange": "iX1_U11E",
+        });
+        from_json(value)
+    }
+
+    fn configuration() -> Configuration {
+        Configuration::new(Args::parse_from(["test"])).expect("Invalid configuration")
+    }
+
+    fn ids() -> (Uuid, Uuid) {
+        (
+            Uuid::parse_str(FIRST_ID).expect("Invalid VSS ID"),
+            Uuid::parse_str(SECOND_ID).expect("Invalid VSS ID"),
+        )
+    }
+
+    #[tokio::test]
+    async fn should_compare_both_vehicles() {
+        let source = FakeSource::new(vec![
+            vehicle(
+                FIRST_ID,
+                45000.0,
+                json!({ "S402A": { "name": { "fr_FR": "Toit panoramique" } } }),
+            ),
+            vehicle(SECOND_ID, 42000.0, json!({})),
+        ]);
+        let configuration = configuration();
+        let (first, second) = find_vehicles(&source, &configuration, ids())
+            .await
+            .expect("Both vehicles should be found");
+        let mut output = Vec::new();
+
+        write_comparison(&first, &second, &configuration, &mut output)
+            .expect("Failed to write comparison");
+
+        let output = String::from_utf8(output).expect("Invalid UTF-8 output");
+        let line = |label: &str| {
+            output
+                .lines()
+                .find(|line| line[2..].starts_with(label))
+                .unwrap_or_else(|| panic!("Missing {} row in {}", label, output))
+                .to_string()
+        };
+        assert!(line("Id").contains(FIRST_ID));
+        assert!(line("Id").contains(SECOND_ID));
+        assert!(line("Price").starts_with('*'));
+        assert!(line("Price").contains("45 000,00 €"));
+        assert!(line("Price").contains("42 000,00 €"));
+        assert!(line("Model").starts_with(' '));
+        assert!(line("Equipment").starts_with('*'));
+        assert!(line("Equipment").contains("S402A Toit panoramique"));
+    }
+
+    #[tokio::test]
+    async fn should_report_missing_vehicle() {
+        let source = FakeSource::new(vec![vehicle(FIRST_ID, 45000.0, json!({}))]);
+
+        let error = find_vehicles(&source, &configuration(), ids())
+            .await
+            .expect_err("Missing vehicle should be reported");
+
+        assert_eq!(
+            error.to_string(),
+            format!("No vehicle found for {}", SECOND_ID)
+        );
+    }
+
+    #[tokio::test]
+    async fn should_report_both_missing_vehicles() {
+        let source = FakeSource::new(vec![]);
+
+        let error = find_vehicles(&source, &configuration(), ids())
+            .await
+            .expect_err("Missing vehicles should be reported");
+
+        assert_eq!(
+            error.to_string(),
+            format!("No vehicle found for {} and {}", FIRST_ID, SECOND_ID)
+        );
+    }
+}
@@ -1,7 +1,33 @@
 //! Configuration module for the BMW Finder application.
 //! Handles CLI argument parsing, configuration struct, and output mode logic.
 
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use chrono::NaiveDate;
 use clap::Parser;
+use regex::Regex;
+use url::Url;
+use uuid::Uuid;
+
+use crate::bmw::search::retry::RetryPolicy;
+use crate::currency::{DisplayCurrency, parse_currency_code, parse_rate};
+use crate::vehicle::{FuelType, Transmission};
+
+/// Search endpoint of the BMW stock locator API.
+pub const DEFAULT_BASE_URL: &str =
+    "https://stolo-data-service.prod.stolo.eu-central-1.aws.bmw.cloud/vehiclesearch/search";
+
+/// Page size of the BMW stock locator API.
+pub const DEFAULT_PAGE_SIZE: u32 = 50;
+
+/// Largest page size kept from --page-size, bigger values are capped to it.
+pub const MAX_PAGE_SIZE: u32 = 500;
+
+/// Default hard cap on the vehicles fetched for each condition.
+pub const DEFAULT_MAX_RESULTS_TOTAL: u32 = 500;
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Condition {
@@ -14,6 +40,10 @@ pub enum OutputMode {
     Ui,
     Text,
     Json,
+    Ndjson,
+    Csv,
+    Html,
+    Rss,
 }
 
 impl std::str::FromStr for OutputMode {
@@ -23,13 +53,230 @@ impl std::str::FromStr for OutputMode {
             "ui" => Ok(OutputMode::Ui),
             "text" => Ok(OutputMode::Text),
             "json" => Ok(OutputMode::Json),
+            "ndjson" => Ok(OutputMode::Ndjson),
+            "csv" => Ok(OutputMode::Csv),
+            "html" => Ok(OutputMode::Html),
+            "rss" => Ok(OutputMode::Rss),
             _ => Err(format!("Invalid output mode: {}", s)),
         }
     }
 }
 
+/// Brand of the stock locator, which serves BMW and MINI vehicles.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Brand {
+    Bmw,
+    Mini,
+}
+
+impl Brand {
+    /// Value of the `brand` query parameter.
+    pub fn as_param(&self) -> &'static str {
+        match self {
+            Brand::Bmw => "BMW",
+            Brand::Mini => "MINI",
+        }
+    }
+}
+
+impl std::str::FromStr for Brand {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "bmw" => Ok(Brand::Bmw),
+            "mini" => Ok(Brand::Mini),
+            _ => Err(format!("Invalid brand: {}", s)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+impl std::str::FromStr for SortOrder {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "asc" => Ok(SortOrder::Asc),
+            "desc" => Ok(SortOrder::Desc),
+            _ => Err(format!("Invalid sort order: {}", s)),
+        }
+    }
+}
+
+/// Key the vehicles are sorted by, vehicles without a value coming last.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortKey {
+    Price,
+    Discount,
+    Mileage,
+}
+
+impl std::str::FromStr for SortKey {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "price" => Ok(SortKey::Price),
+            "discount" => Ok(SortKey::Discount),
+            "mileage" => Ok(SortKey::Mileage),
+            _ => Err(format!("Invalid sort key: {}", s)),
+        }
+    }
+}
+
+impl SortKey {
+    /// Order used without `--sort-order`: the lowest price and mileage first,
+    /// the biggest discounts first.
+    pub fn default_order(self) -> SortOrder {
+        match self {
+            SortKey::Price | SortKey::Mileage => SortOrder::Asc,
+            SortKey::Discount => SortOrder::Desc,
+        }
+    }
+}
+
+/// When the text output is colored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorMode {
+    Auto,
+    Always,
+    Never,
+}
+
+impl std::str::FromStr for ColorMode {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "auto" => Ok(ColorMode::Auto),
+            "always" => Ok(ColorMode::Always),
+            "never" => Ok(ColorMode::Never),
+            _ => Err(format!("Invalid color mode: {}", s)),
+        }
+    }
+}
+
+/// Key the text output groups the vehicles by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GroupBy {
+    Model,
+}
+
+impl std::str::FromStr for GroupBy {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "model" => Ok(GroupBy::Model),
+            _ => Err(format!("Invalid group key: {}", s)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WebhookFormat {
+    Raw,
+    Discord,
+}
+
+impl std::str::FromStr for WebhookFormat {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "raw" => Ok(WebhookFormat::Raw),
+            "discord" => Ok(WebhookFormat::Discord),
+            _ => Err(format!("Invalid webhook format: {}", s)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EquipmentMatch {
+    All,
+    Any,
+}
+
+impl std::str::FromStr for EquipmentMatch {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "all" => Ok(EquipmentMatch::All),
+            "any" => Ok(EquipmentMatch::Any),
+            _ => Err(format!("Invalid equipment match: {}", s)),
+        }
+    }
+}
+
+/// Column of the text and CSV outputs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputField {
+    Id,
+    Price,
+    Net,
+    Discount,
+    Savings,
+    Link,
+    Mileage,
+    Power,
+    Fuel,
+    Transmission,
+    Color,
+    Interior,
+    Registration,
+    Score,
+    Dealer,
+    Model,
+    Status,
+    Image,
+    Body,
+}
+
+impl OutputField {
+    /// Columns printed when no field list is given.
+    pub const DEFAULT: [OutputField; 5] = [
+        OutputField::Id,
+        OutputField::Price,
+        OutputField::Discount,
+        OutputField::Savings,
+        OutputField::Link,
+    ];
+}
+
+impl std::str::FromStr for OutputField {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "id" => Ok(OutputField::Id),
+            "price" => Ok(OutputField::Price),
+            "net" => Ok(OutputField::Net),
+            "discount" => Ok(OutputField::Discount),
+            "savings" => Ok(OutputField::Savings),
+            "link" => Ok(OutputField::Link),
+            "mileage" => Ok(OutputField::Mileage),
+            "power" => Ok(OutputField::Power),
+            "fuel" => Ok(OutputField::Fuel),
+            "transmission" => Ok(OutputField::Transmission),
+            "color" => Ok(OutputField::Color),
+            "interior" => Ok(OutputField::Interior),
+            "registration" => Ok(OutputField::Registration),
+            "score" => Ok(OutputField::Score),
+            "dealer" => Ok(OutputField::Dealer),
+            "model" => Ok(OutputField::Model),
+            "status" => Ok(OutputField::Status),
+            "image" => Ok(OutputField::Image),
+            "body" => Ok(OutputField::Body),
+            _ => Err(format!(
+                "Invalid field: {} (expected id, price, net, discount, savings, link, mileage, power, fuel, transmission, color, interior, registration, score, dealer, model, status, image or body)",
+                s
+            )),
+        }
+    }
+}
+
 type ModelList = Vec<String>;
 type EquipmentNameList = Vec<String>;
+type EquipmentCodeList = Vec<String>;
 
 #[derive(Clone, Debug)]
 pub struct Configuration {
@@ -38,6 +285,76 @@ pub struct Configuration {
     output: OutputMode,
     models: ModelList,
     equipment_names: Option<EquipmentNameList>,
+    min_price: Option<f32>,
+    max_price: Option<f32>,
+    min_discount: Option<f32>,
+    max_mileage: Option<u32>,
+    registered_after: Option<NaiveDate>,
+    fuel: Option<FuelType>,
+    sort_keys: Vec<SortKey>,
+    sort_order: Option<SortOrder>,
+    output_file: Option<PathBuf>,
+    output_dir: Option<PathBuf>,
+    locale: String,
+    retry_policy: RetryPolicy,
+    concurrency: usize,
+    timeout: Duration,
+    watch: Option<Duration>,
+    state_file: Option<PathBuf>,
+    notify: bool,
+    webhook: Option<String>,
+    webhook_format: WebhookFormat,
+    list_models: bool,
+    min_power: Option<u32>,
+    transmission: Option<Transmission>,
+    color: Option<String>,
+    all_conditions: bool,
+    best_effort: bool,
+    base_url: String,
+    min_savings: Option<f32>,
+    equipment_codes: Option<EquipmentCodeList>,
+    equipment_match: EquipmentMatch,
+    excluded_equipment_names: Option<EquipmentNameList>,
+    equipment_regex: Option<Regex>,
+    equipment_locale: Option<String>,
+    discounted_only: bool,
+    verbose: u8,
+    dry_run: bool,
+    fields: Vec<OutputField>,
+    min_score: Option<f32>,
+    limit_per_model: Option<u32>,
+    quiet: bool,
+    vat: f32,
+    open: Option<usize>,
+    count_only: bool,
+    postal_code: Option<String>,
+    radius: Option<u32>,
+    dealer: Option<String>,
+    brand: Brand,
+    stream: bool,
+    proxy: Option<Url>,
+    json_compact: bool,
+    db: Option<PathBuf>,
+    history: Option<Uuid>,
+    cache_dir: Option<PathBuf>,
+    cache_ttl: Duration,
+    page_size: u32,
+    in_stock_only: bool,
+    print_schema: bool,
+    top: Option<usize>,
+    compare: Option<(Uuid, Uuid)>,
+    display_currency: Option<DisplayCurrency>,
+    summary_only: bool,
+    user_agent: Option<String>,
+    equipment_similarity: Option<f64>,
+    max_results_total: u32,
+    max_owners: Option<u32>,
+    with_images: bool,
+    group_by: Option<GroupBy>,
+    color_mode: ColorMode,
+    start_index: u32,
+    excluded_vss_ids: Vec<Uuid>,
+    body_type: Option<String>,
 }
 
 impl Configuration {
@@ -53,101 +370,1859 @@ impl Configuration {
         self.output
     }
 
-    pub fn new(args: Args) -> Self {
-        Self {
+    pub fn locale(&self) -> &str {
+        &self.locale
+    }
+
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
+
+    pub fn concurrency(&self) -> usize {
+        self.concurrency
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    pub fn watch(&self) -> Option<Duration> {
+        self.watch
+    }
+
+    pub fn output_file(&self) -> Option<&Path> {
+        self.output_file.as_deref()
+    }
+
+    pub fn output_dir(&self) -> Option<&Path> {
+        self.output_dir.as_deref()
+    }
+
+    pub fn min_price(&self) -> Option<f32> {
+        self.min_price
+    }
+
+    pub fn max_price(&self) -> Option<f32> {
+        self.max_price
+    }
+
+    pub fn min_discount(&self) -> Option<f32> {
+        self.min_discount
+    }
+
+    pub fn max_mileage(&self) -> Option<u32> {
+        self.max_mileage
+    }
+
+    pub fn registered_after(&self) -> Option<NaiveDate> {
+        self.registered_after
+    }
+
+    pub fn fuel(&self) -> Option<FuelType> {
+        self.fuel
+    }
+
+    /// Sort keys, each one breaking the ties of the previous ones.
+    pub fn sort_keys(&self) -> &[SortKey] {
+        &self.sort_keys
+    }
+
+    /// Order of the given sort key, `--sort-order` overriding the default of the key.
+    pub fn sort_order(&self, sort_key: SortKey) -> SortOrder {
+        self.sort_order.unwrap_or_else(|| sort_key.default_order())
+    }
+
+    pub fn state_file(&self) -> Option<&Path> {
+        self.state_file.as_deref()
+    }
+
+    pub fn notify(&self) -> bool {
+        self.notify
+    }
+
+    pub fn webhook(&self) -> Option<&str> {
+        self.webhook.as_deref()
+    }
+
+    pub fn webhook_format(&self) -> WebhookFormat {
+        self.webhook_format
+    }
+
+    pub fn list_models(&self) -> bool {
+        self.list_models
+    }
+
+    pub fn min_power(&self) -> Option<u32> {
+        self.min_power
+    }
+
+    pub fn transmission(&self) -> Option<Transmission> {
+        self.transmission
+    }
+
+    pub fn color(&self) -> Option<&str> {
+        self.color.as_deref()
+    }
+
+    pub fn all_conditions(&self) -> bool {
+        self.all_conditions
+    }
+
+    /// Conditions to search, both new and used stock with --all.
+    pub fn conditions(&self) -> Vec<Condition> {
+        match self.all_conditions {
+            true => vec![Condition::New, Condition::Used],
+            false => vec![self.condition],
+        }
+    }
+
+    pub fn best_effort(&self) -> bool {
+        self.best_effort
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn min_savings(&self) -> Option<f32> {
+        self.min_savings
+    }
+
+    pub fn equipment_codes(&self) -> Option<&[String]> {
+        self.equipment_codes.as_deref()
+    }
+
+    pub fn equipment_match(&self) -> EquipmentMatch {
+        self.equipment_match
+    }
+
+    pub fn excluded_equipment_names(&self) -> Option<&[String]> {
+        self.excluded_equipment_names.as_deref()
+    }
+
+    pub fn equipment_regex(&self) -> Option<&Regex> {
+        self.equipment_regex.as_ref()
+    }
+
+    pub fn equipment_locale(&self) -> Option<&str> {
+        self.equipment_locale.as_deref()
+    }
+
+    pub fn discounted_only(&self) -> bool {
+        self.discounted_only
+    }
+
+    pub fn verbose(&self) -> u8 {
+        self.verbose
+    }
+
+    pub fn dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    pub fn fields(&self) -> &[OutputField] {
+        &self.fields
+    }
+
+    pub fn min_score(&self) -> Option<f32> {
+        self.min_score
+    }
+
+    pub fn limit_per_model(&self) -> Option<u32> {
+        self.limit_per_model
+    }
+
+    /// Quiet mode, implied by the machine-readable, HTML and RSS outputs.
+    pub fn quiet(&self) -> bool {
+        self.quiet
+            || matches!(
+                self.output,
+                OutputMode::Json
+                    | OutputMode::Ndjson
+                    | OutputMode::Csv
+                    | OutputMode::Html
+                    | OutputMode::Rss
+            )
+    }
+
+    pub fn vat(&self) -> f32 {
+        self.vat
+    }
+
+    pub fn open(&self) -> Option<usize> {
+        self.open
+    }
+
+    pub fn count_only(&self) -> bool {
+        self.count_only
+    }
+
+    /// Whether a filter is applied to the fetched vehicles, so the total count
+    /// of the search is not the number of matching vehicles.
+    pub fn has_local_filters(&self) -> bool {
+        self.equipment_names.is_some()
+            || self.equipment_codes.is_some()
+            || self.equipment_regex.is_some()
+            || self.excluded_equipment_names.is_some()
+            || self.min_price.is_some()
+            || self.max_price.is_some()
+            || self.min_discount.is_some()
+            || self.discounted_only
+            || self.min_savings.is_some()
+            || self.min_score.is_some()
+            || self.max_mileage.is_some()
+            || self.max_owners.is_some()
+            || !self.excluded_vss_ids.is_empty()
+            || self.registered_after.is_some()
+            || self.fuel.is_some()
+            || self.min_power.is_some()
+            || self.transmission.is_some()
+            || self.color.is_some()
+            || self.body_type.is_some()
+            || self.dealer.is_some()
+            || self.in_stock_only
+            || self.limit_per_model.is_some()
+            || self.top.is_some()
+    }
+
+    pub fn postal_code(&self) -> Option<&str> {
+        self.postal_code.as_deref()
+    }
+
+    pub fn radius(&self) -> Option<u32> {
+        self.radius
+    }
+
+    pub fn dealer(&self) -> Option<&str> {
+        self.dealer.as_deref()
+    }
+
+    pub fn brand(&self) -> Brand {
+        self.brand
+    }
+
+    pub fn stream(&self) -> bool {
+        self.stream
+    }
+
+    pub fn proxy(&self) -> Option<&Url> {
+        self.proxy.as_ref()
+    }
+
+    pub fn json_compact(&self) -> bool {
+        self.json_compact
+    }
+
+    pub fn db(&self) -> Option<&Path> {
+        self.db.as_deref()
+    }
+
+    pub fn history(&self) -> Option<&Uuid> {
+        self.history.as_ref()
+    }
+
+    pub fn cache_dir(&self) -> Option<&Path> {
+        self.cache_dir.as_deref()
+    }
+
+    pub fn cache_ttl(&self) -> Duration {
+        self.cache_ttl
+    }
+
+    pub fn page_size(&self) -> u32 {
+        self.page_size
+    }
+
+    pub fn in_stock_only(&self) -> bool {
+        self.in_stock_only
+    }
+
+    pub fn print_schema(&self) -> bool {
+        self.print_schema
+    }
+
+    pub fn top(&self) -> Option<usize> {
+        self.top
+    }
+
+    pub fn compare(&self) -> Option<(Uuid, Uuid)> {
+        self.compare
+    }
+
+    pub fn display_currency(&self) -> Option<&DisplayCurrency> {
+        self.display_currency.as_ref()
+    }
+
+    pub fn summary_only(&self) -> bool {
+        self.summary_only
+    }
+
+    pub fn user_agent(&self) -> Option<&str> {
+        self.user_agent.as_deref()
+    }
+
+    /// Similarity threshold of the equipment names, None to match them as substrings.
+    pub fn equipment_similarity(&self) -> Option<f64> {
+        self.equipment_similarity
+    }
+
+    pub fn max_results_total(&self) -> u32 {
+        self.max_results_total
+    }
+
+    pub fn max_owners(&self) -> Option<u32> {
+        self.max_owners
+    }
+
+    pub fn with_images(&self) -> bool {
+        self.with_images
+    }
+
+    pub fn group_by(&self) -> Option<GroupBy> {
+        self.group_by
+    }
+
+    pub fn color_mode(&self) -> ColorMode {
+        self.color_mode
+    }
+
+    pub fn start_index(&self) -> u32 {
+        self.start_index
+    }
+
+    pub fn excluded_vss_ids(&self) -> &[Uuid] {
+        &self.excluded_vss_ids
+    }
+
+    pub fn body_type(&self) -> Option<&str> {
+        self.body_type.as_deref()
+    }
+
+    /// Builds the configuration, failing when no model is left once blank entries are dropped.
+    pub fn new(args: Args) -> Result<Self> {
+        let mut models: ModelList = args
+            .model
+            .iter()
+            .map(|model| model.trim())
+            .filter(|model| !model.is_empty())
+            .map(String::from)
+            .collect();
+        if let Some(path) = &args.models_file {
+            let content = fs::read_to_string(path)
+                .with_context(|| format!("Failed to read models file {}", path.display()))?;
+            for model in parse_models_file(&content) {
+                if !models.contains(&model) {
+                    models.push(model);
+                }
+            }
+        }
+        if models.is_empty() {
+            bail!("At least one non-empty --model or --models-file entry is required");
+        }
+        let mut excluded_vss_ids = args.exclude_vss_id.clone();
+        if let Some(path) = &args.exclude_vss_file {
+            let content = fs::read_to_string(path).with_context(|| {
+                format!("Failed to read excluded VSS IDs file {}", path.display())
+            })?;
+            let vss_ids = parse_vss_ids_file(&content)
+                .with_context(|| format!("Invalid excluded VSS IDs file {}", path.display()))?;
+            for vss_id in vss_ids {
+                if !excluded_vss_ids.contains(&vss_id) {
+                    excluded_vss_ids.push(vss_id);
+                }
+            }
+        }
+        let model_count = models.len();
+        let output = match (args.json, args.text) {
+            (true, _) => OutputMode::Json,
+            (false, true) => OutputMode::Text,
+            // The output directory gets the text, json and csv files instead of the UI.
+            _ if args.output_dir.is_some() && args.output == OutputMode::Ui => OutputMode::Text,
+            _ => args.output,
+        };
+        if args.stream && !matches!(output, OutputMode::Text | OutputMode::Ndjson) {
+            bail!("--stream only works with the text and ndjson outputs");
+        }
+        if args.retry_base_delay_ms > args.retry_max_delay_ms {
+            bail!("--retry-base-delay-ms must not be greater than --retry-max-delay-ms");
+        }
+        if args.summary_only && output != OutputMode::Json {
+            bail!("--summary-only only works with the json output");
+        }
+        if args.compare.as_ref().is_some_and(|ids| ids.len() != 2) {
+            bail!("--compare needs exactly two VSS IDs");
+        }
+
+        Ok(Self {
             condition: match args.used {
                 true => Condition::Used,
                 false => Condition::New,
             },
-            models: args.model,
+            models,
             limit: args.limit,
             equipment_names: args.equipment_names,
-            output: match (args.json, args.text) {
-                (true, _) => OutputMode::Json,
-                (false, true) => OutputMode::Text,
-                _ => args.output,
+            min_price: args.min_price,
+            max_price: args.max_price,
+            min_discount: args.min_discount,
+            max_mileage: args.max_mileage,
+            registered_after: args.registered_after,
+            fuel: args.fuel,
+            sort_keys: args.sort,
+            sort_order: args.sort_order,
+            output_file: args.output_file,
+            output_dir: args.output_dir,
+            locale: args.locale,
+            retry_policy: RetryPolicy::new(
+                args.retries,
+                Duration::from_millis(args.retry_base_delay_ms),
+                Duration::from_millis(args.retry_max_delay_ms),
+            ),
+            concurrency: args.concurrency as usize,
+            timeout: Duration::from_secs(args.timeout),
+            watch: args.watch.map(Duration::from_secs),
+            state_file: args.state_file,
+            notify: args.notify,
+            webhook: args.webhook,
+            webhook_format: args.webhook_format,
+            list_models: args.list_models,
+            min_power: args.min_power,
+            transmission: args.transmission,
+            color: args.color,
+            all_conditions: args.all,
+            best_effort: args.best_effort,
+            base_url: args.base_url,
+            min_savings: args.min_savings,
+            equipment_codes: args.equipment_codes,
+            equipment_match: args.equipment_match,
+            excluded_equipment_names: args.excluded_equipment_names,
+            equipment_regex: args.equipment_regex,
+            equipment_locale: args.equipment_locale,
+            discounted_only: args.discounted_only,
+            verbose: args.verbose,
+            dry_run: args.dry_run,
+            fields: with_image_field(
+                with_net_field(
+                    args.fields.unwrap_or_else(|| default_fields(model_count)),
+                    args.show_net,
+                ),
+                args.with_images,
+            ),
+            min_score: args.min_score,
+            limit_per_model: args.limit_per_model,
+            quiet: args.quiet,
+            vat: args.vat,
+            open: args.open,
+            count_only: args.count_only,
+            postal_code: args.postal_code,
+            radius: args.radius,
+            dealer: args.dealer,
+            brand: args.brand,
+            stream: args.stream,
+            proxy: args.proxy,
+            json_compact: args.json_compact,
+            db: args.db,
+            history: args.history,
+            cache_dir: args.cache_dir,
+            cache_ttl: Duration::from_secs(args.cache_ttl),
+            page_size: args.page_size.min(MAX_PAGE_SIZE),
+            in_stock_only: args.in_stock_only,
+            print_schema: args.print_schema,
+            top: args.top.map(|top| top as usize),
+            compare: match args.compare.as_deref() {
+                Some(&[first, second]) => Some((first, second)),
+                _ => None,
             },
-        }
+            display_currency: args
+                .display_currency
+                .zip(args.rate)
+                .map(|(code, rate)| DisplayCurrency::new(code, rate)),
+            summary_only: args.summary_only,
+            user_agent: args.user_agent,
+            equipment_similarity: args.equipment_fuzzy.then_some(args.equipment_similarity),
+            max_results_total: args.max_results_total,
+            max_owners: args.max_owners,
+            with_images: args.with_images,
+            group_by: args.group_by,
+            color_mode: match args.no_color {
+                true => ColorMode::Never,
+                false => args.color_mode,
+            },
+            start_index: args.start_index,
+            excluded_vss_ids,
+            body_type: args.body_type,
+            output,
+        })
     }
 }
 
-pub fn load_config() -> Configuration {
-    Configuration::new(Args::parse())
+/// Default columns, with the model after the id when several models are searched.
+fn default_fields(model_count: usize) -> Vec<OutputField> {
+    let mut fields = OutputField::DEFAULT.to_vec();
+    if model_count > 1 {
+        fields.insert(1, OutputField::Model);
+    }
+    fields
 }
 
-#[derive(Parser, Debug)]
-#[command(version, about, long_about = None)]
-#[command(group(
-    clap::ArgGroup::new("output_mode")
+/// Adds the net price column after the price one, or last without a price column.
+fn with_net_field(mut fields: Vec<OutputField>, show_net: bool) -> Vec<OutputField> {
+    if show_net && !fields.contains(&OutputField::Net) {
+        let position = fields
+            .iter()
+            .position(|&field| field == OutputField::Price)
+            .map_or(fields.len(), |index| index + 1);
+        fields.insert(position, OutputField::Net);
+    }
+    fields
+}
+
+/// Adds the image column last.
+fn with_image_field(mut fields: Vec<OutputField>, with_images: bool) -> Vec<OutputField> {
+    if with_images && !fields.contains(&OutputField::Image) {
+        fields.push(OutputField::Image);
+    }
+    fields
+}
+
+/// Models of a models file, one per line. Blank lines and `#` comments are ignored.
+fn parse_models_file(content: &str) -> ModelList {
+    content
+        .lines()
+        .map(|line| line.split_once('#').map_or(line, |(model, _)| model).trim())
+        .filter(|model| !model.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// VSS IDs of a file, one per line. Blank lines and `#` comments are ignored.
+fn parse_vss_ids_file(content: &str) -> Result<Vec<Uuid>> {
+    content
+        .lines()
+        .enumerate()
+        .map(|(index, line)| {
+            (
+                index + 1,
+                line.split_once('#')
+                    .map_or(line, |(vss_id, _)| vss_id)
+                    .trim(),
+            )
+        })
+        .filter(|(_, vss_id)| !vss_id.is_empty())
+        .map(|(number, vss_id)| {
+            Uuid::parse_str(vss_id)
+                .with_context(|| format!("Invalid VSS ID on line {}: {}", number, vss_id))
+        })
+        .collect()
+}
+
+pub fn load_config() -> Result<Configuration> {
+    Configuration::new(Args::parse())
+}
+
+/// Compiles the equipment name pattern so an invalid regex fails at startup.
+fn parse_equipment_regex(s: &str) -> Result<Regex, String> {
+    Regex::new(s).map_err(|e| format!("Invalid equipment regex: {}", e))
+}
+
+/// Parses the search limit, which needs to be positive: a zero limit would fetch nothing.
+fn parse_limit(s: &str) -> Result<u32, String> {
+    match s.parse::<u32>() {
+        Ok(0) => Err(
+            "Invalid limit: 0 (a positive limit is required, omit --limit to fetch every result)"
+                .to_string(),
+        ),
+        Ok(limit) => Ok(limit),
+        Err(e) => Err(format!("Invalid limit: {} ({})", s, e)),
+    }
+}
+
+/// Parses a similarity threshold, between 0 and 1.
+fn parse_similarity(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(similarity) if (0.0..=1.0).contains(&similarity) => Ok(similarity),
+        _ => Err(format!("Invalid similarity: {} (expected 0.0 to 1.0)", s)),
+    }
+}
+
+/// Parses a proxy URL, which needs an http or https scheme and a host.
+fn parse_proxy(s: &str) -> Result<Url, String> {
+    let url = Url::parse(s).map_err(|e| format!("Invalid proxy URL: {}", e))?;
+    match (url.scheme(), url.host_str()) {
+        ("http" | "https", Some(_)) => Ok(url),
+        _ => Err(format!(
+            "Invalid proxy URL: {} (expected http or https with a host)",
+            s
+        )),
+    }
+}
+
+/// Parses a `LANG-REGION` locale such as `fr-fr` or `de-de`.
+fn parse_locale(s: &str) -> Result<String, String> {
+    let locale = s.to_ascii_lowercase();
+    match locale.split_once('-') {
+        Some((language, region))
+            if language.len() == 2
+                && region.len() == 2
+                && language.chars().all(|c| c.is_ascii_alphabetic())
+                && region.chars().all(|c| c.is_ascii_alphabetic()) =>
+        {
+            Ok(locale)
+        }
+        _ => Err(format!(
+            "Invalid locale: {} (expected LANG-REGION, e.g. fr-fr)",
+            s
+        )),
+    }
+}
+
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+#[command(group(
+    clap::ArgGroup::new("output_mode")
         .args(["output", "json", "text"])
         .required(false)
         .multiple(false)
 ))]
 pub struct Args {
-    /// Models to search for
-    #[arg(long, default_value = "iX2_U10E")]
+    /// Models to search for, repeatable or comma-separated
+    #[arg(
+        long,
+        env = "BMW_FINDER_MODEL",
+        value_delimiter = ',',
+        default_value = "iX2_U10E",
+        default_value_if("models_file", clap::builder::ArgPredicate::IsPresent, None)
+    )]
     model: Vec<String>,
 
+    /// File of models to search for, one per line, merged with --model
+    #[arg(long, env = "BMW_FINDER_MODELS_FILE", value_name = "PATH")]
+    models_file: Option<PathBuf>,
+
+    /// Vehicles to hide from the results, repeatable or comma-separated
+    #[arg(
+        long,
+        env = "BMW_FINDER_EXCLUDE_VSS_ID",
+        value_name = "VSS_ID",
+        value_delimiter = ','
+    )]
+    exclude_vss_id: Vec<Uuid>,
+
+    /// File of vehicles to hide, one VSS ID per line, merged with --exclude-vss-id
+    #[arg(long, env = "BMW_FINDER_EXCLUDE_VSS_FILE", value_name = "PATH")]
+    exclude_vss_file: Option<PathBuf>,
+
     /// Search for used cars
-    #[arg(long)]
+    #[arg(long, env = "BMW_FINDER_USED")]
     used: bool,
 
-    /// Maximum number of results to fetch
-    #[arg(short, long)]
-    limit: Option<u32>,
+    /// Search both new and used cars
+    #[arg(long, env = "BMW_FINDER_ALL", conflicts_with = "used")]
+    all: bool,
+
+    /// Maximum number of results to fetch
+    #[arg(short, long, env = "BMW_FINDER_LIMIT", value_parser = parse_limit)]
+    limit: Option<u32>,
+
+    /// Index of the first result to fetch, to skip the first N results of the search
+    #[arg(
+        long,
+        env = "BMW_FINDER_START_INDEX",
+        value_name = "N",
+        default_value_t = 0
+    )]
+    start_index: u32,
+
+    /// Maximum number of vehicles kept for each model range, after sorting
+    #[arg(
+        long,
+        env = "BMW_FINDER_LIMIT_PER_MODEL",
+        value_name = "N",
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    limit_per_model: Option<u32>,
+
+    /// Only show the first N vehicles, after filtering and sorting (unlike --limit, which caps the search)
+    #[arg(
+        long,
+        env = "BMW_FINDER_TOP",
+        value_name = "N",
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    top: Option<u32>,
+
+    /// Filter by equipment/pack name on all found cars
+    #[arg(
+        long = "equipment-name",
+        env = "BMW_FINDER_EQUIPMENT_NAME",
+        value_name = "NAME"
+    )]
+    equipment_names: Option<Vec<String>>,
+
+    /// Filter by equipment option code on all found cars
+    #[arg(
+        long = "equipment-code",
+        env = "BMW_FINDER_EQUIPMENT_CODE",
+        value_name = "CODE"
+    )]
+    equipment_codes: Option<Vec<String>>,
+
+    /// Equipment name matching: all (default) names or any of them
+    #[arg(
+        long,
+        env = "BMW_FINDER_EQUIPMENT_MATCH",
+        value_enum,
+        default_value = "all"
+    )]
+    equipment_match: EquipmentMatch,
+
+    /// Drop cars having this equipment/pack name
+    #[arg(
+        long = "exclude-equipment",
+        env = "BMW_FINDER_EXCLUDE_EQUIPMENT",
+        value_name = "NAME"
+    )]
+    excluded_equipment_names: Option<Vec<String>>,
+
+    /// Filter by a regular expression on the equipment/pack names
+    #[arg(
+        long,
+        env = "BMW_FINDER_EQUIPMENT_REGEX",
+        value_name = "PATTERN",
+        value_parser = parse_equipment_regex
+    )]
+    equipment_regex: Option<Regex>,
+
+    /// Only match equipment names of this locale key (e.g. fr_FR), falling back to the default name
+    #[arg(long, env = "BMW_FINDER_EQUIPMENT_LOCALE", value_name = "LOCALE")]
+    equipment_locale: Option<String>,
+
+    /// Match equipment names fuzzily instead of as substrings, e.g. "Pack M Sport" with "M Sport Package"
+    #[arg(long, env = "BMW_FINDER_EQUIPMENT_FUZZY")]
+    equipment_fuzzy: bool,
+
+    /// Similarity from 0.0 to 1.0 an equipment name needs to match with --equipment-fuzzy
+    #[arg(
+        long,
+        env = "BMW_FINDER_EQUIPMENT_SIMILARITY",
+        value_name = "0.0-1.0",
+        default_value_t = 0.9,
+        value_parser = parse_similarity,
+        requires = "equipment_fuzzy"
+    )]
+    equipment_similarity: f64,
+
+    /// Minimum price of the vehicles to keep
+    #[arg(long, env = "BMW_FINDER_MIN_PRICE", value_name = "PRICE")]
+    min_price: Option<f32>,
+
+    /// Maximum price of the vehicles to keep
+    #[arg(long, env = "BMW_FINDER_MAX_PRICE", value_name = "PRICE")]
+    max_price: Option<f32>,
+
+    /// Minimum discount percentage of the vehicles to keep
+    #[arg(long, env = "BMW_FINDER_MIN_DISCOUNT", value_name = "PERCENT")]
+    min_discount: Option<f32>,
+
+    /// Keep only the vehicles with a discount, shortcut for a minimum discount above 0 %
+    #[arg(long, env = "BMW_FINDER_DISCOUNTED_ONLY")]
+    discounted_only: bool,
+
+    /// Maximum mileage of the used vehicles to keep, in kilometers
+    #[arg(long, env = "BMW_FINDER_MAX_MILEAGE", value_name = "KM")]
+    max_mileage: Option<u32>,
+
+    /// Maximum number of previous owners of the used vehicles to keep
+    #[arg(long, env = "BMW_FINDER_MAX_OWNERS", value_name = "N")]
+    max_owners: Option<u32>,
+
+    /// Keep vehicles registered (or produced) on or after this date
+    #[arg(long, env = "BMW_FINDER_REGISTERED_AFTER", value_name = "YYYY-MM-DD")]
+    registered_after: Option<NaiveDate>,
+
+    /// Filter by fuel type: electric, hybrid, petrol or diesel
+    #[arg(long, env = "BMW_FINDER_FUEL", value_enum)]
+    fuel: Option<FuelType>,
+
+    /// Sort keys, comma-separated and applied in order: price (default), discount or mileage (used vehicles only)
+    #[arg(
+        long,
+        env = "BMW_FINDER_SORT",
+        alias = "sort-by",
+        value_enum,
+        value_delimiter = ',',
+        value_name = "KEYS",
+        default_value = "price"
+    )]
+    sort: Vec<SortKey>,
+
+    /// Sort order of every key: asc or desc. By default price and mileage are ascending and discount descending
+    #[arg(long, env = "BMW_FINDER_SORT_ORDER", value_enum)]
+    sort_order: Option<SortOrder>,
+
+    /// Group the text output under a heading per key: model (model range)
+    #[arg(
+        long,
+        env = "BMW_FINDER_GROUP_BY",
+        value_enum,
+        value_name = "KEY",
+        conflicts_with = "stream"
+    )]
+    group_by: Option<GroupBy>,
+
+    /// Market locale as LANG-REGION, e.g. fr-fr, de-de or es-es
+    #[arg(long, env = "BMW_FINDER_LOCALE", default_value = "fr-fr", value_parser = parse_locale)]
+    locale: String,
+
+    /// Number of retries for failed requests (connection errors, 5xx and 429)
+    #[arg(
+        long,
+        env = "BMW_FINDER_RETRIES",
+        value_name = "N",
+        default_value_t = 3
+    )]
+    retries: u32,
+
+    /// Delay before the first retry in milliseconds, doubled on every following retry
+    #[arg(
+        long,
+        env = "BMW_FINDER_RETRY_BASE_DELAY_MS",
+        value_name = "MS",
+        default_value_t = 250
+    )]
+    retry_base_delay_ms: u64,
+
+    /// Longest delay between two retries in milliseconds, before jitter
+    #[arg(
+        long,
+        env = "BMW_FINDER_RETRY_MAX_DELAY_MS",
+        value_name = "MS",
+        default_value_t = 30_000
+    )]
+    retry_max_delay_ms: u64,
+
+    /// Number of requests sent concurrently (at least 1)
+    #[arg(
+        long,
+        env = "BMW_FINDER_CONCURRENCY",
+        value_name = "N",
+        default_value_t = 5,
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    concurrency: u32,
+
+    /// Timeout of each request, in seconds
+    #[arg(
+        long,
+        env = "BMW_FINDER_TIMEOUT",
+        value_name = "SECONDS",
+        default_value_t = 30,
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    timeout: u64,
+
+    /// Cache the search responses in this directory
+    #[arg(long, env = "BMW_FINDER_CACHE_DIR", value_name = "PATH")]
+    cache_dir: Option<PathBuf>,
+
+    /// Reuse cached responses younger than this many seconds
+    #[arg(
+        long,
+        env = "BMW_FINDER_CACHE_TTL",
+        value_name = "SECONDS",
+        default_value_t = 3600
+    )]
+    cache_ttl: u64,
+
+    /// Repeat the search every SECONDS and report new and gone vehicles
+    #[arg(
+        long,
+        env = "BMW_FINDER_WATCH",
+        value_name = "SECONDS",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    watch: Option<u64>,
+
+    /// Compare with the results saved in this file, then save the current ones
+    #[arg(long, env = "BMW_FINDER_STATE_FILE", value_name = "PATH")]
+    state_file: Option<PathBuf>,
+
+    /// Record the price of the reported vehicles in this SQLite database
+    #[arg(long, env = "BMW_FINDER_DB", value_name = "PATH")]
+    db: Option<PathBuf>,
+
+    /// Print the prices recorded in the database for this vehicle and exit
+    #[arg(
+        long,
+        env = "BMW_FINDER_HISTORY",
+        value_name = "VSS_ID",
+        requires = "db"
+    )]
+    history: Option<Uuid>,
+
+    /// Send a desktop notification when watch mode finds new vehicles
+    #[arg(long, env = "BMW_FINDER_NOTIFY", requires = "watch")]
+    notify: bool,
+
+    /// Post the filtered vehicles to this URL
+    #[arg(long, env = "BMW_FINDER_WEBHOOK", value_name = "URL")]
+    webhook: Option<String>,
+
+    /// Webhook payload format: raw (default) or discord
+    #[arg(
+        long,
+        env = "BMW_FINDER_WEBHOOK_FORMAT",
+        value_enum,
+        default_value = "raw",
+        requires = "webhook"
+    )]
+    webhook_format: WebhookFormat,
+
+    /// List the known model range codes and exit
+    #[arg(long, env = "BMW_FINDER_LIST_MODELS")]
+    list_models: bool,
+
+    /// Print the JSON Schema of the json output and exit (needs the schema feature)
+    #[arg(long, env = "BMW_FINDER_PRINT_SCHEMA")]
+    print_schema: bool,
+
+    /// Keep vehicles with at least this power in horsepower
+    #[arg(long, env = "BMW_FINDER_MIN_POWER", value_name = "HP")]
+    min_power: Option<u32>,
+
+    /// Filter by transmission: auto or manual
+    #[arg(long, env = "BMW_FINDER_TRANSMISSION", value_enum)]
+    transmission: Option<Transmission>,
+
+    /// Filter by exterior color name (partial match, case insensitive)
+    #[arg(long, env = "BMW_FINDER_COLOR", value_name = "NAME")]
+    color: Option<String>,
+
+    /// Filter by body type, e.g. suv, sedan or touring (partial match, case insensitive)
+    #[arg(long, env = "BMW_FINDER_BODY_TYPE", value_name = "VALUE")]
+    body_type: Option<String>,
+
+    /// Filter by dealership name (partial match, case insensitive)
+    #[arg(long, env = "BMW_FINDER_DEALER", value_name = "NAME")]
+    dealer: Option<String>,
+
+    /// Keep the pages fetched successfully when some page requests fail
+    #[arg(long, env = "BMW_FINDER_BEST_EFFORT")]
+    best_effort: bool,
+
+    /// Print the planned page requests and exit, only the first page is fetched for the counts
+    #[arg(long, env = "BMW_FINDER_DRY_RUN")]
+    dry_run: bool,
+
+    /// Print the number of matching vehicles only, from the total count without local filters
+    #[arg(long, env = "BMW_FINDER_COUNT_ONLY")]
+    count_only: bool,
+
+    /// Print text or ndjson rows as their page arrives, without sorting the vehicles
+    #[arg(
+        long,
+        env = "BMW_FINDER_STREAM",
+        conflicts_with_all = ["watch", "limit_per_model", "top", "state_file", "webhook", "open"]
+    )]
+    stream: bool,
+
+    /// Write the json output on a single line instead of pretty-printing it
+    #[arg(long, env = "BMW_FINDER_JSON_COMPACT")]
+    json_compact: bool,
+
+    /// Color the text output: auto (when stdout is a terminal and NO_COLOR is unset), always or never
+    #[arg(
+        long,
+        env = "BMW_FINDER_COLOR_MODE",
+        value_enum,
+        value_name = "WHEN",
+        default_value = "auto"
+    )]
+    color_mode: ColorMode,
+
+    /// Never color the text output, same as --color-mode never
+    #[arg(long, env = "BMW_FINDER_NO_COLOR", conflicts_with = "color_mode")]
+    no_color: bool,
+
+    /// Log more details on stderr: -v info, -vv debug, -vvv trace
+    #[arg(short, long, env = "BMW_FINDER_VERBOSE", action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// Only print the results, without the search header and progress (implied by json, ndjson, csv, html and rss)
+    #[arg(short, long, env = "BMW_FINDER_QUIET")]
+    quiet: bool,
+
+    /// Base URL of the search API, e.g. a mock server or another regional endpoint
+    #[arg(
+        long,
+        value_name = "URL",
+        env = "BMW_FINDER_BASE_URL",
+        default_value = DEFAULT_BASE_URL
+    )]
+    base_url: String,
+
+    /// Proxy for every request, overriding the HTTPS_PROXY and HTTP_PROXY variables
+    #[arg(long, env = "BMW_FINDER_PROXY", value_name = "URL", value_parser = parse_proxy)]
+    proxy: Option<Url>,
+
+    /// Keep vehicles saving at least this amount on the catalog price
+    #[arg(long, env = "BMW_FINDER_MIN_SAVINGS", value_name = "EUR")]
+    min_savings: Option<f32>,
+
+    /// Open the links of the first N vehicles (1 by default) in the browser
+    #[arg(
+        long,
+        env = "BMW_FINDER_OPEN",
+        value_name = "N",
+        num_args = 0..=1,
+        default_missing_value = "1"
+    )]
+    open: Option<usize>,
+
+    /// Keep vehicles whose search relevance score is at least this value
+    #[arg(long, env = "BMW_FINDER_MIN_SCORE", value_name = "SCORE")]
+    min_score: Option<f32>,
+
+    /// Comma-separated columns of the text and CSV outputs:
+    /// id, price, net, discount, savings, link, mileage, power, fuel, transmission, color, interior, registration, score, dealer, model, status, image, body
+    #[arg(
+        long,
+        env = "BMW_FINDER_FIELDS",
+        value_enum,
+        value_delimiter = ',',
+        value_name = "FIELDS"
+    )]
+    fields: Option<Vec<OutputField>>,
+
+    /// Add a net (pre-tax) price column to the text and CSV outputs
+    #[arg(long, env = "BMW_FINDER_SHOW_NET")]
+    show_net: bool,
+
+    /// Add the URL of the first picture of the vehicles to the JSON and CSV outputs
+    #[arg(long, env = "BMW_FINDER_WITH_IMAGES")]
+    with_images: bool,
+
+    /// VAT rate used to compute the net price when the API gives none
+    #[arg(
+        long,
+        env = "BMW_FINDER_VAT",
+        value_name = "PERCENT",
+        default_value_t = 20.0
+    )]
+    vat: f32,
+
+    /// Search around this postal code instead of the whole country
+    #[arg(long, env = "BMW_FINDER_POSTAL_CODE", value_name = "CODE")]
+    postal_code: Option<String>,
+
+    /// Search within this distance of the postal code
+    #[arg(
+        long,
+        env = "BMW_FINDER_RADIUS",
+        value_name = "KM",
+        requires = "postal_code"
+    )]
+    radius: Option<u32>,
+
+    /// Brand to search: bmw (default) or mini
+    #[arg(long, env = "BMW_FINDER_BRAND", value_enum, default_value = "bmw")]
+    brand: Brand,
+
+    /// Number of vehicles requested per page, capped to 500
+    #[arg(
+        long,
+        env = "BMW_FINDER_PAGE_SIZE",
+        value_name = "N",
+        default_value_t = DEFAULT_PAGE_SIZE,
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    page_size: u32,
+
+    /// Keep only the available vehicles, dropping the reserved and sold ones
+    #[arg(long, env = "BMW_FINDER_IN_STOCK_ONLY", alias = "exclude-sold")]
+    in_stock_only: bool,
+
+    /// Print these two vehicles side by side and exit
+    #[arg(
+        long,
+        env = "BMW_FINDER_COMPARE",
+        value_name = "VSS_ID",
+        num_args = 1..=2,
+        value_delimiter = ','
+    )]
+    compare: Option<Vec<Uuid>>,
+
+    /// Show the prices in this currency (e.g. CHF), converted from euros with --rate
+    #[arg(
+        long,
+        env = "BMW_FINDER_DISPLAY_CURRENCY",
+        value_name = "CODE",
+        value_parser = parse_currency_code,
+        requires = "rate"
+    )]
+    display_currency: Option<String>,
+
+    /// Units of the display currency for one euro
+    #[arg(
+        long,
+        env = "BMW_FINDER_RATE",
+        value_parser = parse_rate,
+        requires = "display_currency"
+    )]
+    rate: Option<f32>,
+
+    /// Only write the summary of the vehicles as a single json object
+    #[arg(long, env = "BMW_FINDER_SUMMARY_ONLY", conflicts_with = "stream")]
+    summary_only: bool,
+
+    /// User agent of the requests, instead of bmw-finder/<version>
+    #[arg(long, env = "BMW_FINDER_USER_AGENT", value_name = "STRING")]
+    user_agent: Option<String>,
+
+    /// Hard cap on the vehicles fetched for each condition, even without --limit
+    #[arg(
+        long,
+        env = "BMW_FINDER_MAX_RESULTS_TOTAL",
+        value_name = "N",
+        default_value_t = DEFAULT_MAX_RESULTS_TOTAL,
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    max_results_total: u32,
+
+    /// Output mode: Ui (default), text, json, ndjson, csv, html or rss
+    #[arg(
+        long,
+        env = "BMW_FINDER_OUTPUT",
+        value_enum,
+        default_value = "ui",
+        group = "output_mode"
+    )]
+    output: OutputMode,
+
+    /// Write the output to this file instead of stdout
+    #[arg(long, env = "BMW_FINDER_OUTPUT_FILE", value_name = "PATH")]
+    output_file: Option<PathBuf>,
+
+    /// Write the text, json and csv outputs, and the selected one, as results.* files in this directory
+    #[arg(
+        long,
+        env = "BMW_FINDER_OUTPUT_DIR",
+        value_name = "DIR",
+        conflicts_with_all = ["output_file", "stream"]
+    )]
+    output_dir: Option<PathBuf>,
+
+    /// Shortcut for --output text
+    #[arg(long, env = "BMW_FINDER_TEXT", group = "output_mode")]
+    text: bool,
+
+    /// Shortcut for --output json
+    #[arg(long, env = "BMW_FINDER_JSON", group = "output_mode")]
+    json: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod configuration {
+        use super::*;
+
+        #[test]
+        fn should_use_args_to_create_configuration() {
+            let args = Args {
+                model: vec![String::from("My Model")],
+                used: true,
+                limit: Some(5),
+                equipment_names: Some(vec![String::from("Pack Innovation")]),
+                min_price: Some(10000.0),
+                max_price: Some(50000.0),
+                min_discount: Some(10.0),
+                max_mileage: Some(30000),
+                registered_after: NaiveDate::from_ymd_opt(2022, 1, 1),
+                fuel: Some(FuelType::Electric),
+                sort: vec![SortKey::Discount, SortKey::Price],
+                sort_order: Some(SortOrder::Desc),
+                output: OutputMode::Text,
+                output_file: Some(PathBuf::from("results.txt")),
+                output_dir: None,
+                locale: String::from("de-de"),
+                retries: 5,
+                retry_base_delay_ms: 100,
+                retry_max_delay_ms: 2000,
+                concurrency: 2,
+                timeout: 10,
+                watch: Some(300),
+                state_file: Some(PathBuf::from("state.json")),
+                notify: true,
+                webhook: Some(String::from("https://example.com/hook")),
+                webhook_format: WebhookFormat::Discord,
+                list_models: true,
+                min_power: Some(200),
+                transmission: Some(Transmission::Automatic),
+                color: Some(String::from("Blanc")),
+                all: false,
+                best_effort: true,
+                base_url: String::from("http://localhost:8080"),
+                min_savings: Some(5000.0),
+                equipment_codes: Some(vec![String::from("S402A")]),
+                equipment_match: EquipmentMatch::Any,
+                excluded_equipment_names: Some(vec![String::from("Toit panoramique")]),
+                equipment_regex: Some(Regex::new("^Pack M").unwrap()),
+                equipment_locale: Some(String::from("fr_FR")),
+                discounted_only: true,
+                verbose: 2,
+                dry_run: true,
+                fields: Some(vec![OutputField::Price, OutputField::Id]),
+                min_score: Some(0.5),
+                limit_per_model: Some(3),
+                quiet: true,
+                vat: 19.0,
+                show_net: true,
+                open: Some(3),
+                count_only: true,
+                postal_code: Some(String::from("75001")),
+                radius: Some(50),
+                dealer: Some(String::from("lyon")),
+                brand: Brand::Mini,
+                stream: true,
+                proxy: Some(Url::parse("http://proxy.local:3128").expect("Invalid proxy URL")),
+                json_compact: true,
+                db: Some(PathBuf::from("prices.db")),
+                history: Some(uuid::uuid!("67e55044-10b1-426f-9247-bb680e5fe0c8")),
+                cache_dir: Some(PathBuf::from(".cache")),
+                cache_ttl: 600,
+                page_size: 1000,
+                in_stock_only: true,
+                print_schema: true,
+                top: Some(3),
+                compare: Some(vec![
+                    uuid::uuid!("67e55044-10b1-426f-9247-bb680e5fe0c8"),
+                    uuid::uuid!("0b4f0b5e-6ad7-4f6c-9d1d-6a8a2c8b7f01"),
+                ]),
+                models_file: None,
+                display_currency: Some(String::from("CHF")),
+                rate: Some(0.94),
+                summary_only: false,
+                user_agent: Some(String::from("my-dashboard/2.0")),
+                equipment_fuzzy: true,
+                equipment_similarity: 0.8,
+                max_results_total: 1000,
+                max_owners: Some(1),
+                with_images: true,
+                group_by: Some(GroupBy::Model),
+                color_mode: ColorMode::Always,
+                no_color: false,
+                start_index: 100,
+                exclude_vss_id: vec![
+                    Uuid::parse_str("0b4f0b5e-6ad7-4f6c-9d1d-6a8a2c8b7f01")
+                        .expect("Invalid VSS ID"),
+                ],
+                exclude_vss_file: None,
+                body_type: Some(String::from("suv")),
+                text: false,
+                json: false,
+            };
+
+            let config = Configuration::new(args).expect("Invalid configuration");
+
+            assert_eq!(config.models, vec![String::from("My Model")]);
+            assert_eq!(config.condition, Condition::Used);
+            assert_eq!(config.limit, Some(5));
+            assert_eq!(
+                config.equipment_names,
+                Some(vec![String::from("Pack Innovation")])
+            );
+            assert_eq!(config.min_price, Some(10000.0));
+            assert_eq!(config.max_price, Some(50000.0));
+            assert_eq!(config.min_discount, Some(10.0));
+            assert_eq!(config.max_mileage, Some(30000));
+            assert_eq!(config.registered_after, NaiveDate::from_ymd_opt(2022, 1, 1));
+            assert_eq!(config.fuel, Some(FuelType::Electric));
+            assert_eq!(config.sort_keys(), &[SortKey::Discount, SortKey::Price]);
+            assert_eq!(config.sort_order(SortKey::Price), SortOrder::Desc);
+            assert_eq!(config.sort_order(SortKey::Discount), SortOrder::Desc);
+            assert_eq!(config.output, OutputMode::Text);
+            assert_eq!(config.output_file, Some(PathBuf::from("results.txt")));
+            assert_eq!(config.locale, "de-de");
+            assert_eq!(
+                config.retry_policy(),
+                &RetryPolicy::new(5, Duration::from_millis(100), Duration::from_millis(2000))
+            );
+            assert_eq!(config.concurrency(), 2);
+            assert_eq!(config.timeout(), Duration::from_secs(10));
+            assert_eq!(config.watch(), Some(Duration::from_secs(300)));
+            assert_eq!(config.state_file(), Some(Path::new("state.json")));
+            assert!(config.notify());
+            assert_eq!(config.webhook(), Some("https://example.com/hook"));
+            assert_eq!(config.webhook_format(), WebhookFormat::Discord);
+            assert!(config.list_models());
+            assert_eq!(config.min_power(), Some(200));
+            assert_eq!(config.transmission(), Some(Transmission::Automatic));
+            assert_eq!(config.color(), Some("Blanc"));
+            assert!(!config.all_conditions());
+            assert_eq!(config.conditions(), vec![Condition::Used]);
+            assert!(config.best_effort());
+            assert_eq!(config.base_url(), "http://localhost:8080");
+            assert_eq!(config.min_savings(), Some(5000.0));
+            assert_eq!(config.equipment_codes(), Some(&[String::from("S402A")][..]));
+            assert_eq!(config.equipment_match(), EquipmentMatch::Any);
+            assert_eq!(
+                config.excluded_equipment_names(),
+                Some(&[String::from("Toit panoramique")][..])
+            );
+            assert_eq!(config.equipment_regex().map(Regex::as_str), Some("^Pack M"));
+            assert_eq!(config.equipment_locale(), Some("fr_FR"));
+            assert!(config.discounted_only());
+            assert_eq!(config.verbose(), 2);
+            assert!(config.dry_run());
+            assert_eq!(
+                config.fields(),
+                &[
+                    OutputField::Price,
+                    OutputField::Net,
+                    OutputField::Id,
+                    OutputField::Image
+                ]
+            );
+            assert_eq!(config.min_score(), Some(0.5));
+            assert_eq!(config.limit_per_model(), Some(3));
+            assert!(config.quiet());
+            assert_eq!(config.vat(), 19.0);
+            assert_eq!(config.open(), Some(3));
+            assert!(config.count_only());
+            assert!(config.has_local_filters());
+            assert_eq!(config.postal_code(), Some("75001"));
+            assert_eq!(config.radius(), Some(50));
+            assert_eq!(config.dealer(), Some("lyon"));
+            assert_eq!(config.brand(), Brand::Mini);
+            assert!(config.stream());
+            assert_eq!(
+                config.proxy().map(Url::as_str),
+                Some("http://proxy.local:3128/")
+            );
+            assert!(config.json_compact());
+            assert_eq!(config.db(), Some(Path::new("prices.db")));
+            assert_eq!(
+                config.history(),
+                Some(&uuid::uuid!("67e55044-10b1-426f-9247-bb680e5fe0c8"))
+            );
+            assert_eq!(config.cache_dir(), Some(Path::new(".cache")));
+            assert_eq!(config.cache_ttl(), Duration::from_secs(600));
+            assert_eq!(config.page_size(), MAX_PAGE_SIZE);
+            assert!(config.in_stock_only());
+            assert!(config.print_schema());
+            assert_eq!(config.top(), Some(3));
+            assert_eq!(
+                config.compare(),
+                Some((
+                    uuid::uuid!("67e55044-10b1-426f-9247-bb680e5fe0c8"),
+                    uuid::uuid!("0b4f0b5e-6ad7-4f6c-9d1d-6a8a2c8b7f01")
+                ))
+            );
+            assert_eq!(
+                config.display_currency(),
+                Some(&DisplayCurrency::new(String::from("CHF"), 0.94))
+            );
+            assert!(!config.summary_only());
+            assert_eq!(config.user_agent(), Some("my-dashboard/2.0"));
+            assert_eq!(config.equipment_similarity(), Some(0.8));
+            assert_eq!(config.max_results_total(), 1000);
+            assert_eq!(config.max_owners(), Some(1));
+            assert!(config.with_images());
+            assert_eq!(config.group_by(), Some(GroupBy::Model));
+            assert_eq!(config.color_mode(), ColorMode::Always);
+            assert_eq!(config.start_index(), 100);
+            assert_eq!(
+                config.excluded_vss_ids(),
+                &[
+                    Uuid::parse_str("0b4f0b5e-6ad7-4f6c-9d1d-6a8a2c8b7f01")
+                        .expect("Invalid VSS ID")
+                ]
+            );
+            assert_eq!(config.body_type(), Some("suv"));
+        }
+
+        #[test]
+        fn should_sort_discount_descending_by_default() {
+            let config = Configuration::new(Args::parse_from(["test"]))
+                .expect("Failed to create configuration");
+
+            assert_eq!(config.sort_order(SortKey::Price), SortOrder::Asc);
+            assert_eq!(config.sort_order(SortKey::Discount), SortOrder::Desc);
+            assert_eq!(config.sort_order(SortKey::Mileage), SortOrder::Asc);
+        }
+    }
+
+    mod args {
+        use super::*;
+        use clap::error::ErrorKind;
+
+        #[test]
+        fn should_error_without_models() {
+            let mut args = Args::parse_from(["test"]);
+            args.model = vec![];
+
+            assert!(Configuration::new(args).is_err());
+        }
+
+        #[test]
+        fn should_error_with_whitespace_only_models() {
+            let args = Args::parse_from(["test", "--model", "  ", "--model", ""]);
+
+            let error = Configuration::new(args).expect_err("Blank models should be rejected");
+
+            assert!(error.to_string().contains("--model"));
+        }
+
+        #[test]
+        fn should_parse_models_file() {
+            let content =
+                "# Watchlist\niX1_U11E\n\n  i4_G26E  \n# iX2_U10E\nX1_U11 # the old one\n";
+
+            assert_eq!(
+                parse_models_file(content),
+                vec![
+                    String::from("iX1_U11E"),
+                    String::from("i4_G26E"),
+                    String::from("X1_U11")
+                ]
+            );
+        }
+
+        #[test]
+        fn should_merge_models_file_with_model_flags() {
+            let path =
+                std::env::temp_dir().join(format!("bmw_finder_models_{}.txt", Uuid::new_v4()));
+            fs::write(&path, "i4_G26E\n# comment\niX1_U11E\n")
+                .expect("Failed to write models file");
+            let args = Args::parse_from([
+                "test",
+                "--model",
+                "iX1_U11E",
+                "--models-file",
+                path.to_str().expect("Invalid path"),
+            ]);
+
+            let config = Configuration::new(args).expect("Invalid configuration");
+            fs::remove_file(&path).expect("Failed to remove models file");
+
+            assert_eq!(
+                config.models(),
+                &[String::from("iX1_U11E"), String::from("i4_G26E")]
+            );
+        }
+
+        #[test]
+        fn should_not_add_default_model_to_models_file() {
+            let args = Args::parse_from(["test", "--models-file", "models.txt"]);
+
+            assert!(args.model.is_empty());
+        }
+
+        #[test]
+        fn should_error_on_missing_models_file() {
+            let args = Args::parse_from(["test", "--models-file", "/nonexistent/models.txt"]);
+
+            let error = Configuration::new(args).expect_err("Missing file should be rejected");
+
+            assert!(error.to_string().contains("/nonexistent/models.txt"));
+        }
+
+        #[test]
+        fn should_parse_vss_ids_file() {
+            let content = "# Dismissed\n67e55044-10b1-426f-9247-bb680e5fe0c8\n\n  0b4f0b5e-6ad7-4f6c-9d1d-6a8a2c8b7f01 # too far\n";
+
+            assert_eq!(
+                parse_vss_ids_file(content).expect("Valid file"),
+                vec![
+                    Uuid::parse_str("67e55044-10b1-426f-9247-bb680e5fe0c8")
+                        .expect("Invalid VSS ID"),
+                    Uuid::parse_str("0b4f0b5e-6ad7-4f6c-9d1d-6a8a2c8b7f01")
+                        .expect("Invalid VSS ID"),
+                ]
+            );
+        }
+
+        #[test]
+        fn should_error_on_invalid_vss_id_in_file() {
+            let error = parse_vss_ids_file("67e55044-10b1-426f-9247-bb680e5fe0c8\nnot-a-uuid\n")
+                .expect_err("Invalid VSS ID should be rejected");
+
+            assert_eq!(error.to_string(), "Invalid VSS ID on line 2: not-a-uuid");
+        }
+
+        #[test]
+        fn should_error_on_invalid_excluded_vss_id() {
+            let res = Args::try_parse_from(["test", "--exclude-vss-id", "not-a-uuid"]);
+            assert_eq!(
+                res.expect_err("Invalid VSS ID should be rejected").kind(),
+                ErrorKind::ValueValidation
+            );
+        }
+
+        #[test]
+        fn should_merge_excluded_vss_ids_file_with_flags() {
+            let path =
+                std::env::temp_dir().join(format!("bmw_finder_excluded_{}.txt", Uuid::new_v4()));
+            fs::write(
+                &path,
+                "67e55044-10b1-426f-9247-bb680e5fe0c8\n0b4f0b5e-6ad7-4f6c-9d1d-6a8a2c8b7f01\n",
+            )
+            .expect("Failed to write excluded VSS IDs file");
+            let args = Args::parse_from([
+                "test",
+                "--exclude-vss-id",
+                "0b4f0b5e-6ad7-4f6c-9d1d-6a8a2c8b7f01",
+                "--exclude-vss-file",
+                path.to_str().expect("Invalid path"),
+            ]);
+
+            let config = Configuration::new(args).expect("Invalid configuration");
+            fs::remove_file(&path).expect("Failed to remove excluded VSS IDs file");
+
+            assert_eq!(
+                config.excluded_vss_ids(),
+                &[
+                    Uuid::parse_str("0b4f0b5e-6ad7-4f6c-9d1d-6a8a2c8b7f01")
+                        .expect("Invalid VSS ID"),
+                    Uuid::parse_str("67e55044-10b1-426f-9247-bb680e5fe0c8")
+                        .expect("Invalid VSS ID"),
+                ]
+            );
+            assert!(config.has_local_filters());
+        }
+
+        #[test]
+        fn should_trim_models() {
+            let args = Args::parse_from(["test", "--model", " iX1_U11E ", "--model", " "]);
+
+            let config = Configuration::new(args).expect("Invalid configuration");
+
+            assert_eq!(config.models(), &[String::from("iX1_U11E")]);
+        }
+
+        #[test]
+        fn should_stream_text_and_ndjson_only() {
+            for output in ["text", "ndjson"] {
+                let args = Args::parse_from(["test", "--output", output, "--stream"]);
+                assert!(Configuration::new(args).is_ok());
+            }
+            for output in ["ui", "json", "csv", "html", "rss"] {
+                let args = Args::parse_from(["test", "--output", output, "--stream"]);
+                assert!(Configuration::new(args).is_err());
+            }
+        }
+
+        #[test]
+        fn should_write_text_instead_of_ui_to_output_dir() {
+            let config = Configuration::new(Args::parse_from(["test", "--output-dir", "results"]))
+                .expect("Invalid configuration");
+            assert_eq!(config.output(), OutputMode::Text);
+            assert_eq!(config.output_dir(), Some(Path::new("results")));
+
+            let config = Configuration::new(Args::parse_from([
+                "test",
+                "--output",
+                "html",
+                "--output-dir",
+                "results",
+            ]))
+            .expect("Invalid configuration");
+            assert_eq!(config.output(), OutputMode::Html);
+        }
+
+        #[test]
+        fn should_not_have_local_filters_by_default() {
+            let config = Configuration::new(Args::parse_from(["test", "--used", "--limit", "5"]))
+                .expect("Invalid configuration");
+
+            assert!(!config.has_local_filters());
+        }
+
+        #[test]
+        fn should_search_both_conditions_with_all() {
+            let config = Configuration::new(Args::parse_from(["test", "--all"]))
+                .expect("Invalid configuration");
+
+            assert!(config.all_conditions());
+            assert_eq!(config.conditions(), vec![Condition::New, Condition::Used]);
+        }
+
+        #[test]
+        fn should_add_model_field_when_searching_several_models() {
+            let config = Configuration::new(Args::parse_from([
+                "test", "--model", "iX1_U11E", "--model", "i4_G26E",
+            ]))
+            .expect("Invalid configuration");
+
+            assert_eq!(
+                config.fields(),
+                &[
+                    OutputField::Id,
+                    OutputField::Model,
+                    OutputField::Price,
+                    OutputField::Discount,
+                    OutputField::Savings,
+                    OutputField::Link,
+                ]
+            );
+        }
+
+        #[test]
+        fn should_keep_given_fields_when_searching_several_models() {
+            let config = Configuration::new(Args::parse_from([
+                "test", "--model", "iX1_U11E", "--model", "i4_G26E", "--fields", "id,price",
+            ]))
+            .expect("Invalid configuration");
+
+            assert_eq!(config.fields(), &[OutputField::Id, OutputField::Price]);
+        }
+
+        #[test]
+        fn should_never_color_with_no_color() {
+            let config = Configuration::new(Args::parse_from(["test", "--no-color"]))
+                .expect("Invalid configuration");
+
+            assert_eq!(config.color_mode(), ColorMode::Never);
+        }
+
+        #[test]
+        fn should_error_on_color_mode_and_no_color() {
+            let res = Args::try_parse_from(["test", "--color-mode", "always", "--no-color"]);
+            assert_eq!(
+                res.expect_err("--color-mode and --no-color should conflict")
+                    .kind(),
+                ErrorKind::ArgumentConflict
+            );
+        }
+
+        #[test]
+        fn should_accept_sort_by_as_alias_of_sort() {
+            let args = Args::parse_from(["test", "--sort-by", "mileage"]);
+
+            assert_eq!(args.sort, vec![SortKey::Mileage]);
+        }
+
+        #[test]
+        fn should_error_on_invalid_sort_key() {
+            let res = Args::try_parse_from(["test", "--sort", "price,power"]);
+            assert!(res.is_err());
+        }
+
+        #[test]
+        fn should_add_image_field_last_with_images() {
+            let config = Configuration::new(Args::parse_from([
+                "test",
+                "--fields",
+                "id,price",
+                "--with-images",
+            ]))
+            .expect("Invalid configuration");
+
+            assert_eq!(
+                config.fields(),
+                &[OutputField::Id, OutputField::Price, OutputField::Image]
+            );
+        }
+
+        #[test]
+        fn should_error_on_all_and_used() {
+            let res = Args::try_parse_from(["test", "--all", "--used"]);
+            assert_eq!(
+                res.expect_err("--all and --used should conflict").kind(),
+                ErrorKind::ArgumentConflict
+            );
+        }
+
+        #[test]
+        fn should_parse_open_count() {
+            let args = Args::parse_from(["test", "--open", "3", "--text"]);
+            assert_eq!(args.open, Some(3));
+        }
+
+        #[test]
+        fn should_error_on_radius_without_postal_code() {
+            let res = Args::try_parse_from(["test", "--radius", "50"]);
+            assert_eq!(
+                res.expect_err("--radius should require --postal-code")
+                    .kind(),
+                ErrorKind::MissingRequiredArgument
+            );
+        }
+
+        #[test]
+        fn should_parse_stream() {
+            let args = Args::parse_from(["test", "--text", "--stream"]);
+            assert!(args.stream);
+        }
+
+        #[test]
+        fn should_error_on_stream_and_state_file() {
+            let res = Args::try_parse_from(["test", "--stream", "--state-file", "state.json"]);
+            assert_eq!(
+                res.expect_err("--stream should conflict with --state-file")
+                    .kind(),
+                ErrorKind::ArgumentConflict
+            );
+        }
+
+        #[test]
+        fn should_error_on_output_dir_and_output_file() {
+            let res = Args::try_parse_from([
+                "test",
+                "--output-dir",
+                "results",
+                "--output-file",
+                "results.txt",
+            ]);
+            assert_eq!(
+                res.expect_err("--output-dir should conflict with --output-file")
+                    .kind(),
+                ErrorKind::ArgumentConflict
+            );
+        }
+
+        #[test]
+        fn should_error_on_zero_limit() {
+            let err = Args::try_parse_from(["test", "--limit", "0"])
+                .expect_err("--limit 0 should be rejected");
+            assert_eq!(err.kind(), ErrorKind::ValueValidation);
+            assert!(err.to_string().contains("a positive limit is required"));
+        }
+
+        #[test]
+        fn should_accept_limit_of_one() {
+            let args = Args::try_parse_from(["test", "--limit", "1"]).unwrap();
+            assert_eq!(args.limit, Some(1));
+        }
+
+        #[test]
+        fn should_error_on_zero_top() {
+            let res = Args::try_parse_from(["test", "--top", "0"]);
+            assert_eq!(
+                res.expect_err("--top 0 should be rejected").kind(),
+                ErrorKind::ValueValidation
+            );
+        }
+
+        #[test]
+        fn should_error_on_retry_base_delay_above_max() {
+            let args = Args::parse_from([
+                "test",
+                "--retry-base-delay-ms",
+                "2000",
+                "--retry-max-delay-ms",
+                "1000",
+            ]);
+
+            let error = Configuration::new(args).expect_err("Base delay above max should fail");
+
+            assert!(error.to_string().contains("--retry-max-delay-ms"));
+        }
+
+        #[test]
+        fn should_error_on_similarity_out_of_range() {
+            let res = Args::try_parse_from([
+                "test",
+                "--equipment-fuzzy",
+                "--equipment-similarity",
+                "1.5",
+            ]);
+            assert_eq!(
+                res.expect_err("--equipment-similarity 1.5 should be rejected")
+                    .kind(),
+                ErrorKind::ValueValidation
+            );
+        }
+
+        #[test]
+        fn should_summarize_json_output_only() {
+            let args = Args::parse_from(["test", "--text", "--summary-only"]);
 
-    /// Filter by equipment/pack name on all found cars
-    #[arg(long = "equipment-name", value_name = "NAME")]
-    equipment_names: Option<Vec<String>>,
+            let error = Configuration::new(args).expect_err("--summary-only needs json");
 
-    /// Output mode: Ui (default), text, or json
-    #[arg(long, value_enum, default_value = "ui", group = "output_mode")]
-    output: OutputMode,
+            assert!(error.to_string().contains("--summary-only"));
+        }
 
-    /// Shortcut for --output text
-    #[arg(long, group = "output_mode")]
-    text: bool,
+        #[test]
+        fn should_error_on_display_currency_without_rate() {
+            let res = Args::try_parse_from(["test", "--display-currency", "CHF"]);
+            assert_eq!(
+                res.expect_err("--display-currency should require --rate")
+                    .kind(),
+                ErrorKind::MissingRequiredArgument
+            );
+        }
 
-    /// Shortcut for --output json
-    #[arg(long, group = "output_mode")]
-    json: bool,
-}
+        #[test]
+        fn should_parse_comma_separated_compared_vehicles() {
+            let args = Args::parse_from([
+                "test",
+                "--compare",
+                "67e55044-10b1-426f-9247-bb680e5fe0c8,0b4f0b5e-6ad7-4f6c-9d1d-6a8a2c8b7f01",
+            ]);
+            assert_eq!(args.compare.map(|ids| ids.len()), Some(2));
+        }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        #[test]
+        fn should_error_without_two_compared_vehicles() {
+            for compare in [
+                "67e55044-10b1-426f-9247-bb680e5fe0c8",
+                "67e55044-10b1-426f-9247-bb680e5fe0c8,0b4f0b5e-6ad7-4f6c-9d1d-6a8a2c8b7f01,3f2b8c1e-9a4d-4e7b-8c6f-1d2e3f4a5b6c",
+            ] {
+                let args = Args::parse_from(["test", "--compare", compare]);
+                assert!(Configuration::new(args).is_err());
+            }
+        }
 
-    mod configuration {
-        use super::*;
+        #[test]
+        fn should_error_on_invalid_proxy() {
+            for proxy in ["not a url", "ftp://proxy.local", "http://"] {
+                let res = Args::try_parse_from(["test", "--proxy", proxy]);
+                assert_eq!(
+                    res.expect_err("invalid proxies should be rejected").kind(),
+                    ErrorKind::ValueValidation
+                );
+            }
+        }
 
         #[test]
-        fn should_use_args_to_create_configuration() {
-            let args = Args {
-                model: vec![String::from("My Model")],
-                used: true,
-                limit: Some(5),
-                equipment_names: Some(vec![String::from("Pack Innovation")]),
-                output: OutputMode::Text,
-                text: false,
-                json: false,
-            };
+        fn should_error_on_history_without_db() {
+            let res =
+                Args::try_parse_from(["test", "--history", "67e55044-10b1-426f-9247-bb680e5fe0c8"]);
+            assert_eq!(
+                res.expect_err("--history should require --db").kind(),
+                ErrorKind::MissingRequiredArgument
+            );
+        }
 
-            let config = Configuration::new(args);
+        #[test]
+        fn should_error_on_unknown_brand() {
+            let res = Args::try_parse_from(["test", "--brand", "audi"]);
+            assert_eq!(
+                res.expect_err("unknown brands should be rejected").kind(),
+                ErrorKind::ValueValidation
+            );
+        }
 
-            assert_eq!(config.models, vec![String::from("My Model")]);
-            assert_eq!(config.condition, Condition::Used);
-            assert_eq!(config.limit, Some(5));
+        #[test]
+        fn should_error_on_unknown_field() {
+            let res = Args::try_parse_from(["test", "--fields", "id,owner"]);
             assert_eq!(
-                config.equipment_names,
-                Some(vec![String::from("Pack Innovation")])
+                res.expect_err("unknown fields should be rejected").kind(),
+                ErrorKind::ValueValidation
             );
-            assert_eq!(config.output, OutputMode::Text);
         }
-    }
 
-    mod args {
-        use super::*;
-        use clap::error::ErrorKind;
+        #[test]
+        fn should_error_on_invalid_equipment_regex() {
+            let res = Args::try_parse_from(["test", "--equipment-regex", "(toit"]);
+            assert_eq!(
+                res.expect_err("an unclosed group should be rejected")
+                    .kind(),
+                ErrorKind::ValueValidation
+            );
+        }
 
         #[test]
         fn should_error_on_output_and_text() {
@@ -173,6 +2248,38 @@ mod tests {
             assert_eq!(err.kind(), ErrorKind::ArgumentConflict);
         }
 
+        #[test]
+        fn should_error_on_invalid_registration_date() {
+            let res = Args::try_parse_from(["test", "--registered-after", "15/06/2023"]);
+            assert!(res.is_err());
+            let err = res.unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::ValueValidation);
+        }
+
+        #[test]
+        fn should_error_on_zero_page_size() {
+            let res = Args::try_parse_from(["test", "--page-size", "0"]);
+            assert!(res.is_err());
+            let err = res.unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::ValueValidation);
+        }
+
+        #[test]
+        fn should_error_on_zero_concurrency() {
+            let res = Args::try_parse_from(["test", "--concurrency", "0"]);
+            assert!(res.is_err());
+            let err = res.unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::ValueValidation);
+        }
+
+        #[test]
+        fn should_error_on_invalid_locale() {
+            let res = Args::try_parse_from(["test", "--locale", "france"]);
+            assert!(res.is_err());
+            let err = res.unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::ValueValidation);
+        }
+
         #[test]
         fn should_be_parsed() {
             let args = Args::parse_from(vec![
@@ -188,8 +2295,143 @@ mod tests {
                 "Pack M Sport",
                 "--model",
                 "My second Model",
+                "--min-price",
+                "10000",
+                "--max-price",
+                "50000.5",
+                "--min-discount",
+                "12.5",
+                "--max-mileage",
+                "50000",
+                "--registered-after",
+                "2023-06-15",
+                "--fuel",
+                "Diesel",
+                "--sort",
+                "discount,mileage",
+                "--sort-order",
+                "desc",
                 "--output",
                 "json",
+                "--output-file",
+                "results.json",
+                "--locale",
+                "ES-es",
+                "--retries",
+                "0",
+                "--retry-base-delay-ms",
+                "500",
+                "--retry-max-delay-ms",
+                "5000",
+                "--concurrency",
+                "10",
+                "--timeout",
+                "60",
+                "--watch",
+                "600",
+                "--state-file",
+                "last.json",
+                "--notify",
+                "--webhook",
+                "https://example.com/hook",
+                "--webhook-format",
+                "Discord",
+                "--list-models",
+                "--min-power",
+                "250",
+                "--transmission",
+                "manual",
+                "--color",
+                "Noir",
+                "--best-effort",
+                "--base-url",
+                "http://localhost:9090/search",
+                "--min-savings",
+                "2500.5",
+                "--equipment-code",
+                "S402A",
+                "--equipment-code",
+                "S4NB",
+                "--equipment-match",
+                "any",
+                "--exclude-equipment",
+                "Toit panoramique",
+                "--equipment-regex",
+                "^Pack (M|Innovation)$",
+                "--equipment-locale",
+                "de_DE",
+                "--discounted-only",
+                "-vv",
+                "--dry-run",
+                "--fields",
+                "link,mileage",
+                "--min-score",
+                "0.75",
+                "--limit-per-model",
+                "2",
+                "-q",
+                "--vat",
+                "21",
+                "--show-net",
+                "--open",
+                "--count-only",
+                "--postal-code",
+                "69002",
+                "--radius",
+                "30",
+                "--dealer",
+                "BMW Lyon",
+                "--brand",
+                "mini",
+                "--proxy",
+                "https://127.0.0.1:8443",
+                "--json-compact",
+                "--db",
+                "history.db",
+                "--history",
+                "67e55044-10b1-426f-9247-bb680e5fe0c8",
+                "--cache-dir",
+                "/tmp/bmw",
+                "--cache-ttl",
+                "120",
+                "--page-size",
+                "100",
+                "--exclude-sold",
+                "--print-schema",
+                "--top",
+                "5",
+                "--compare",
+                "67e55044-10b1-426f-9247-bb680e5fe0c8",
+                "0b4f0b5e-6ad7-4f6c-9d1d-6a8a2c8b7f01",
+                "--models-file",
+                "models.txt",
+                "--display-currency",
+                "gbp",
+                "--rate",
+                "0.85",
+                "--summary-only",
+                "--user-agent",
+                "curl/8.0",
+                "--equipment-fuzzy",
+                "--equipment-similarity",
+                "0.75",
+                "--max-results-total",
+                "2000",
+                "--max-owners",
+                "2",
+                "--with-images",
+                "--group-by",
+                "model",
+                "--color-mode",
+                "never",
+                "--start-index",
+                "50",
+                "--exclude-vss-id",
+                "67e55044-10b1-426f-9247-bb680e5fe0c8",
+                "--exclude-vss-file",
+                "dismissed.txt",
+                "--body-type",
+                "touring",
             ]);
 
             assert_eq!(
@@ -205,7 +2447,105 @@ mod tests {
                     String::from("Pack M Sport")
                 ])
             );
+            assert_eq!(args.min_price, Some(10000.0));
+            assert_eq!(args.max_price, Some(50000.5));
+            assert_eq!(args.min_discount, Some(12.5));
+            assert_eq!(args.max_mileage, Some(50000));
+            assert_eq!(args.registered_after, NaiveDate::from_ymd_opt(2023, 6, 15));
+            assert_eq!(args.fuel, Some(FuelType::Diesel));
+            assert_eq!(args.sort, vec![SortKey::Discount, SortKey::Mileage]);
+            assert_eq!(args.sort_order, Some(SortOrder::Desc));
             assert_eq!(args.output, OutputMode::Json);
+            assert_eq!(args.output_file, Some(PathBuf::from("results.json")));
+            assert_eq!(args.locale, "es-es");
+            assert_eq!(args.retries, 0);
+            assert_eq!(args.retry_base_delay_ms, 500);
+            assert_eq!(args.retry_max_delay_ms, 5000);
+            assert_eq!(args.concurrency, 10);
+            assert_eq!(args.timeout, 60);
+            assert_eq!(args.watch, Some(600));
+            assert_eq!(args.state_file, Some(PathBuf::from("last.json")));
+            assert!(args.notify);
+            assert_eq!(args.webhook, Some(String::from("https://example.com/hook")));
+            assert_eq!(args.webhook_format, WebhookFormat::Discord);
+            assert!(args.list_models);
+            assert_eq!(args.min_power, Some(250));
+            assert_eq!(args.transmission, Some(Transmission::Manual));
+            assert_eq!(args.color, Some(String::from("Noir")));
+            assert!(args.best_effort);
+            assert_eq!(args.base_url, "http://localhost:9090/search");
+            assert_eq!(args.min_savings, Some(2500.5));
+            assert_eq!(
+                args.equipment_codes,
+                Some(vec![String::from("S402A"), String::from("S4NB")])
+            );
+            assert_eq!(args.equipment_match, EquipmentMatch::Any);
+            assert_eq!(
+                args.excluded_equipment_names,
+                Some(vec![String::from("Toit panoramique")])
+            );
+            assert_eq!(
+                args.equipment_regex.as_ref().map(Regex::as_str),
+                Some("^Pack (M|Innovation)$")
+            );
+            assert_eq!(args.equipment_locale, Some(String::from("de_DE")));
+            assert!(args.discounted_only);
+            assert_eq!(args.verbose, 2);
+            assert!(args.dry_run);
+            assert_eq!(
+                args.fields,
+                Some(vec![OutputField::Link, OutputField::Mileage])
+            );
+            assert_eq!(args.min_score, Some(0.75));
+            assert_eq!(args.limit_per_model, Some(2));
+            assert!(args.quiet);
+            assert_eq!(args.vat, 21.0);
+            assert!(args.show_net);
+            assert_eq!(args.open, Some(1));
+            assert!(args.count_only);
+            assert_eq!(args.postal_code, Some(String::from("69002")));
+            assert_eq!(args.radius, Some(30));
+            assert_eq!(args.dealer, Some(String::from("BMW Lyon")));
+            assert_eq!(args.brand, Brand::Mini);
+            assert_eq!(
+                args.proxy.as_ref().map(Url::as_str),
+                Some("https://127.0.0.1:8443/")
+            );
+            assert!(args.json_compact);
+            assert_eq!(args.db, Some(PathBuf::from("history.db")));
+            assert_eq!(
+                args.history,
+                Some(uuid::uuid!("67e55044-10b1-426f-9247-bb680e5fe0c8"))
+            );
+            assert_eq!(args.cache_dir, Some(PathBuf::from("/tmp/bmw")));
+            assert_eq!(args.cache_ttl, 120);
+            assert_eq!(args.page_size, 100);
+            assert!(args.in_stock_only);
+            assert!(args.print_schema);
+            assert_eq!(args.top, Some(5));
+            assert_eq!(
+                args.compare,
+                Some(vec![
+                    uuid::uuid!("67e55044-10b1-426f-9247-bb680e5fe0c8"),
+                    uuid::uuid!("0b4f0b5e-6ad7-4f6c-9d1d-6a8a2c8b7f01")
+                ])
+            );
+            assert_eq!(args.models_file, Some(PathBuf::from("models.txt")));
+            assert_eq!(args.display_currency, Some(String::from("GBP")));
+            assert_eq!(args.rate, Some(0.85));
+            assert!(args.summary_only);
+            assert_eq!(args.user_agent, Some(String::from("curl/8.0")));
+            assert!(args.equipment_fuzzy);
+            assert_eq!(args.equipment_similarity, 0.75);
+            assert_eq!(args.max_results_total, 2000);
+            assert_eq!(args.max_owners, Some(2));
+            assert!(args.with_images);
+            assert_eq!(args.group_by, Some(GroupBy::Model));
+            assert_eq!(args.color_mode, ColorMode::Never);
+            assert_eq!(args.start_index, 50);
+            assert_eq!(args.exclude_vss_id.len(), 1);
+            assert_eq!(args.exclude_vss_file, Some(PathBuf::from("dismissed.txt")));
+            assert_eq!(args.body_type, Some(String::from("touring")));
         }
 
         #[test]
@@ -216,7 +2556,188 @@ mod tests {
             assert_eq!(args.used, false);
             assert_eq!(args.limit, None);
             assert_eq!(args.equipment_names, None);
+            assert_eq!(args.min_price, None);
+            assert_eq!(args.max_price, None);
+            assert_eq!(args.min_discount, None);
+            assert_eq!(args.max_mileage, None);
+            assert_eq!(args.registered_after, None);
+            assert_eq!(args.fuel, None);
+            assert_eq!(args.sort, vec![SortKey::Price]);
+            assert_eq!(args.sort_order, None);
             assert_eq!(args.output, OutputMode::Ui);
+            assert_eq!(args.output_file, None);
+            assert_eq!(args.locale, "fr-fr");
+            assert_eq!(args.retries, 3);
+            assert_eq!(args.retry_base_delay_ms, 250);
+            assert_eq!(args.retry_max_delay_ms, 30_000);
+            assert_eq!(args.concurrency, 5);
+            assert_eq!(args.timeout, 30);
+            assert_eq!(args.watch, None);
+            assert_eq!(args.state_file, None);
+            assert!(!args.notify);
+            assert_eq!(args.webhook, None);
+            assert_eq!(args.webhook_format, WebhookFormat::Raw);
+            assert!(!args.list_models);
+            assert_eq!(args.min_power, None);
+            assert_eq!(args.transmission, None);
+            assert_eq!(args.color, None);
+            assert!(!args.all);
+            assert!(!args.best_effort);
+            assert_eq!(args.base_url, DEFAULT_BASE_URL);
+            assert_eq!(args.min_savings, None);
+            assert_eq!(args.equipment_codes, None);
+            assert_eq!(args.equipment_match, EquipmentMatch::All);
+            assert_eq!(args.excluded_equipment_names, None);
+            assert!(args.equipment_regex.is_none());
+            assert_eq!(args.equipment_locale, None);
+            assert!(!args.discounted_only);
+            assert_eq!(args.verbose, 0);
+            assert!(!args.dry_run);
+            assert_eq!(args.fields, None);
+            assert_eq!(args.min_score, None);
+            assert_eq!(args.limit_per_model, None);
+            assert!(!args.quiet);
+            assert_eq!(args.vat, 20.0);
+            assert!(!args.show_net);
+            assert_eq!(args.open, None);
+            assert!(!args.count_only);
+            assert_eq!(args.postal_code, None);
+            assert_eq!(args.radius, None);
+            assert_eq!(args.dealer, None);
+            assert_eq!(args.brand, Brand::Bmw);
+            assert!(!args.stream);
+            assert_eq!(args.proxy, None);
+            assert!(!args.json_compact);
+            assert_eq!(args.db, None);
+            assert_eq!(args.history, None);
+            assert_eq!(args.cache_dir, None);
+            assert_eq!(args.cache_ttl, 3600);
+            assert_eq!(args.page_size, DEFAULT_PAGE_SIZE);
+            assert!(!args.in_stock_only);
+            assert!(!args.print_schema);
+            assert_eq!(args.top, None);
+            assert_eq!(args.compare, None);
+            assert_eq!(args.models_file, None);
+            assert_eq!(args.display_currency, None);
+            assert_eq!(args.rate, None);
+            assert!(!args.summary_only);
+            assert_eq!(args.user_agent, None);
+            assert!(!args.equipment_fuzzy);
+            assert_eq!(args.equipment_similarity, 0.9);
+            assert_eq!(args.max_results_total, DEFAULT_MAX_RESULTS_TOTAL);
+            assert_eq!(args.max_owners, None);
+            assert!(!args.with_images);
+            assert_eq!(args.group_by, None);
+            assert_eq!(args.color_mode, ColorMode::Auto);
+            assert!(!args.no_color);
+            assert_eq!(args.start_index, 0);
+            assert!(args.exclude_vss_id.is_empty());
+            assert_eq!(args.exclude_vss_file, None);
+            assert_eq!(args.body_type, None);
+        }
+    }
+
+    mod env_vars {
+        use super::*;
+        use clap::{CommandFactory, FromArgMatches};
+
+        /// Parses the arguments with `arg` read from `var` instead of its own variable.
+        /// Each test sets a variable nobody else reads, as tests run in parallel.
+        fn try_parse_with_env(
+            arg: &str,
+            var: &'static str,
+            value: &str,
+            args: &[&str],
+        ) -> Result<Args, clap::Error> {
+            // SAFETY: the variable is only read by the test setting it.
+            unsafe { std::env::set_var(var, value) };
+            let matches = Args::command()
+                .mut_arg(arg, |arg| arg.env(var))
+                .try_get_matches_from(std::iter::once("test").chain(args.iter().copied()));
+            // SAFETY: as above.
+            unsafe { std::env::remove_var(var) };
+            Args::from_arg_matches(&matches?)
+        }
+
+        #[test]
+        fn should_name_every_variable_after_its_flag() {
+            for arg in Args::command().get_arguments() {
+                let Some(long) = arg.get_long() else {
+                    continue;
+                };
+                if long == "help" || long == "version" {
+                    continue;
+                }
+                let expected = format!("BMW_FINDER_{}", long.to_uppercase().replace('-', "_"));
+
+                assert_eq!(
+                    arg.get_env().and_then(|env| env.to_str()),
+                    Some(expected.as_str()),
+                    "--{} has no {} variable",
+                    long,
+                    expected
+                );
+            }
+        }
+
+        #[test]
+        fn should_read_value_from_env() {
+            let args = try_parse_with_env("limit", "BMW_FINDER_TEST_LIMIT", "7", &[])
+                .expect("Invalid arguments");
+
+            assert_eq!(args.limit, Some(7));
+        }
+
+        #[test]
+        fn should_prefer_flag_over_env() {
+            let args = try_parse_with_env(
+                "output",
+                "BMW_FINDER_TEST_OUTPUT",
+                "csv",
+                &["--output", "ndjson"],
+            )
+            .expect("Invalid arguments");
+
+            assert_eq!(args.output, OutputMode::Ndjson);
+        }
+
+        #[test]
+        fn should_prefer_env_over_default() {
+            let args = try_parse_with_env("output", "BMW_FINDER_TEST_OUTPUT_DEFAULT", "csv", &[])
+                .expect("Invalid arguments");
+
+            assert_eq!(args.output, OutputMode::Csv);
+        }
+
+        #[test]
+        fn should_read_switch_from_env() {
+            let args = try_parse_with_env("used", "BMW_FINDER_TEST_USED", "true", &[])
+                .expect("Invalid arguments");
+
+            assert!(args.used);
+        }
+
+        #[test]
+        fn should_read_comma_separated_models_from_env() {
+            let args =
+                try_parse_with_env("model", "BMW_FINDER_TEST_MODEL", "iX1_U11E,i4_G26E", &[])
+                    .expect("Invalid arguments");
+
+            assert_eq!(
+                args.model,
+                vec![String::from("iX1_U11E"), String::from("i4_G26E")]
+            );
+        }
+
+        #[test]
+        fn should_validate_env_values() {
+            let res = try_parse_with_env("limit", "BMW_FINDER_TEST_LIMIT_INVALID", "many", &[]);
+
+            assert_eq!(
+                res.expect_err("invalid env values should be rejected")
+                    .kind(),
+                clap::error::ErrorKind::ValueValidation
+            );
         }
     }
 
@@ -245,6 +2766,31 @@ mod tests {
             assert_eq!(OutputMode::from_str("Json"), Ok(OutputMode::Json));
         }
 
+        #[test]
+        fn parses_ndjson_case_insensitive() {
+            assert_eq!(OutputMode::from_str("ndjson"), Ok(OutputMode::Ndjson));
+            assert_eq!(OutputMode::from_str("NDJSON"), Ok(OutputMode::Ndjson));
+        }
+
+        #[test]
+        fn parses_csv_case_insensitive() {
+            assert_eq!(OutputMode::from_str("csv"), Ok(OutputMode::Csv));
+            assert_eq!(OutputMode::from_str("CSV"), Ok(OutputMode::Csv));
+            assert_eq!(OutputMode::from_str("Csv"), Ok(OutputMode::Csv));
+        }
+
+        #[test]
+        fn parses_html_case_insensitive() {
+            assert_eq!(OutputMode::from_str("html"), Ok(OutputMode::Html));
+            assert_eq!(OutputMode::from_str("HTML"), Ok(OutputMode::Html));
+        }
+
+        #[test]
+        fn parses_rss_case_insensitive() {
+            assert_eq!(OutputMode::from_str("rss"), Ok(OutputMode::Rss));
+            assert_eq!(OutputMode::from_str("RSS"), Ok(OutputMode::Rss));
+        }
+
         #[test]
         fn returns_err_on_invalid_value() {
             assert!(OutputMode::from_str("foo").is_err());
@@ -252,4 +2798,175 @@ mod tests {
             assert!(OutputMode::from_str("123").is_err());
         }
     }
+
+    mod color_mode_fromstr {
+        use super::*;
+        use std::str::FromStr;
+
+        #[test]
+        fn parses_modes_case_insensitive() {
+            assert_eq!(ColorMode::from_str("auto"), Ok(ColorMode::Auto));
+            assert_eq!(ColorMode::from_str("ALWAYS"), Ok(ColorMode::Always));
+            assert_eq!(ColorMode::from_str("Never"), Ok(ColorMode::Never));
+        }
+
+        #[test]
+        fn returns_err_on_invalid_value() {
+            assert!(ColorMode::from_str("sometimes").is_err());
+            assert!(ColorMode::from_str("").is_err());
+        }
+    }
+
+    mod group_by_fromstr {
+        use super::*;
+        use std::str::FromStr;
+
+        #[test]
+        fn parses_model_case_insensitive() {
+            assert_eq!(GroupBy::from_str("model"), Ok(GroupBy::Model));
+            assert_eq!(GroupBy::from_str("MODEL"), Ok(GroupBy::Model));
+        }
+
+        #[test]
+        fn returns_err_on_invalid_value() {
+            assert!(GroupBy::from_str("dealer").is_err());
+            assert!(GroupBy::from_str("").is_err());
+        }
+    }
+
+    mod sort_order_fromstr {
+        use super::*;
+        use std::str::FromStr;
+
+        #[test]
+        fn parses_asc_case_insensitive() {
+            assert_eq!(SortOrder::from_str("asc"), Ok(SortOrder::Asc));
+            assert_eq!(SortOrder::from_str("ASC"), Ok(SortOrder::Asc));
+        }
+
+        #[test]
+        fn parses_desc_case_insensitive() {
+            assert_eq!(SortOrder::from_str("desc"), Ok(SortOrder::Desc));
+            assert_eq!(SortOrder::from_str("DESC"), Ok(SortOrder::Desc));
+        }
+
+        #[test]
+        fn returns_err_on_invalid_value() {
+            assert!(SortOrder::from_str("up").is_err());
+            assert!(SortOrder::from_str("").is_err());
+        }
+    }
+
+    mod sort_key_fromstr {
+        use super::*;
+        use std::str::FromStr;
+
+        #[test]
+        fn parses_price_case_insensitive() {
+            assert_eq!(SortKey::from_str("price"), Ok(SortKey::Price));
+            assert_eq!(SortKey::from_str("PRICE"), Ok(SortKey::Price));
+        }
+
+        #[test]
+        fn parses_discount_case_insensitive() {
+            assert_eq!(SortKey::from_str("discount"), Ok(SortKey::Discount));
+            assert_eq!(SortKey::from_str("Discount"), Ok(SortKey::Discount));
+        }
+
+        #[test]
+        fn parses_mileage_case_insensitive() {
+            assert_eq!(SortKey::from_str("mileage"), Ok(SortKey::Mileage));
+            assert_eq!(SortKey::from_str("MILEAGE"), Ok(SortKey::Mileage));
+        }
+
+        #[test]
+        fn returns_err_on_invalid_value() {
+            assert!(SortKey::from_str("power").is_err());
+            assert!(SortKey::from_str("").is_err());
+        }
+    }
+
+    mod output_field_fromstr {
+        use super::*;
+        use std::str::FromStr;
+
+        #[test]
+        fn parses_case_insensitive() {
+            assert_eq!(OutputField::from_str("id"), Ok(OutputField::Id));
+            assert_eq!(OutputField::from_str("Mileage"), Ok(OutputField::Mileage));
+            assert_eq!(
+                OutputField::from_str("REGISTRATION"),
+                Ok(OutputField::Registration)
+            );
+        }
+
+        #[test]
+        fn returns_err_on_invalid_value() {
+            assert!(OutputField::from_str("owner").is_err());
+            assert!(OutputField::from_str("").is_err());
+        }
+    }
+
+    mod brand_fromstr {
+        use super::*;
+        use std::str::FromStr;
+
+        #[test]
+        fn parses_case_insensitive() {
+            assert_eq!(Brand::from_str("bmw"), Ok(Brand::Bmw));
+            assert_eq!(Brand::from_str("MINI"), Ok(Brand::Mini));
+        }
+
+        #[test]
+        fn returns_err_on_invalid_value() {
+            assert!(Brand::from_str("audi").is_err());
+            assert!(Brand::from_str("").is_err());
+        }
+    }
+
+    mod equipment_match_fromstr {
+        use super::*;
+        use std::str::FromStr;
+
+        #[test]
+        fn parses_case_insensitive() {
+            assert_eq!(EquipmentMatch::from_str("all"), Ok(EquipmentMatch::All));
+            assert_eq!(EquipmentMatch::from_str("ANY"), Ok(EquipmentMatch::Any));
+        }
+
+        #[test]
+        fn returns_err_on_invalid_value() {
+            assert!(EquipmentMatch::from_str("none").is_err());
+            assert!(EquipmentMatch::from_str("").is_err());
+        }
+    }
+
+    mod webhook_format_fromstr {
+        use super::*;
+        use std::str::FromStr;
+
+        #[test]
+        fn parses_raw_case_insensitive() {
+            assert_eq!(WebhookFormat::from_str("raw"), Ok(WebhookFormat::Raw));
+            assert_eq!(WebhookFormat::from_str("RAW"), Ok(WebhookFormat::Raw));
+        }
+
+        #[test]
+        fn parses_discord_case_insensitive() {
+            assert_eq!(
+                WebhookFormat::from_str("discord"),
+                Ok(WebhookFormat::Discord)
+            );
+            assert_eq!(
+                WebhookFormat::from_str("Discord"),
+                Ok(WebhookFormat::Discord)
+            );
+        }
+
+        #[test]
+        fn returns_err_on_invalid_value() {
+            assert!(WebhookFormat::from_str("slack").is_err());
+            assert!(WebhookFormat::from_str("").is_err());
+        }
+    }
 }
@@ -0,0 +1,95 @@
+//! Currency module for the BMW Finder application.
+//! Converts the euro prices of the search into another currency for display.
+
+/// Currency the prices are displayed in, with its exchange rate from the euro.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DisplayCurrency {
+    code: String,
+    rate: f32,
+}
+
+impl DisplayCurrency {
+    pub fn new(code: String, rate: f32) -> Self {
+        Self { code, rate }
+    }
+
+    /// ISO 4217 code, e.g. `CHF`.
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    /// Converts a euro amount into the display currency.
+    pub fn convert(&self, amount: f32) -> f32 {
+        amount * self.rate
+    }
+
+    /// Sign written after the amounts, the code for currencies without a well-known sign.
+    pub fn symbol(&self) -> &str {
+        match self.code.as_str() {
+            "EUR" => "€",
+            "GBP" => "£",
+            "USD" => "$",
+            "JPY" => "¥",
+            code => code,
+        }
+    }
+}
+
+/// Parses a three-letter currency code, upper-cased.
+pub fn parse_currency_code(s: &str) -> Result<String, String> {
+    match s.len() == 3 && s.chars().all(|c| c.is_ascii_alphabetic()) {
+        true => Ok(s.to_ascii_uppercase()),
+        false => Err(format!(
+            "Invalid currency code: {} (expected three letters, e.g. CHF)",
+            s
+        )),
+    }
+}
+
+/// Parses an exchange rate, which must be a positive number.
+pub fn parse_rate(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(rate) if rate.is_finite() && rate > 0.0 => Ok(rate),
+        _ => Err(format!("Invalid rate: {} (expected a positive number)", s)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_convert_amount_with_rate() {
+        let currency = DisplayCurrency::new(String::from("CHF"), 0.94);
+
+        assert!((currency.convert(42000.0) - 39480.0).abs() < 0.01);
+        assert_eq!(currency.convert(0.0), 0.0);
+    }
+
+    #[test]
+    fn should_use_sign_or_code_as_symbol() {
+        assert_eq!(
+            DisplayCurrency::new(String::from("GBP"), 0.85).symbol(),
+            "£"
+        );
+        assert_eq!(
+            DisplayCurrency::new(String::from("CHF"), 0.94).symbol(),
+            "CHF"
+        );
+    }
+
+    #[test]
+    fn should_parse_currency_code() {
+        assert_eq!(parse_currency_code("chf"), Ok(String::from("CHF")));
+        assert!(parse_currency_code("CH").is_err());
+        assert!(parse_currency_code("C1F").is_err());
+    }
+
+    #[test]
+    fn should_parse_positive_rate() {
+        assert_eq!(parse_rate("0.94"), Ok(0.94));
+        assert!(parse_rate("0").is_err());
+        assert!(parse_rate("-1").is_err());
+        assert!(parse_rate("abc").is_err());
+    }
+}
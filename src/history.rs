@@ -0,0 +1,212 @@
+//! History module for the BMW Finder application.
+//! Records the price of the reported vehicles in a SQLite database to follow them over time.
+
+use std::path::Path;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::legacy::format_price_cell;
+use crate::vehicle::Vehicle;
+
+/// Price of a vehicle at the time of a run.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(not(feature = "history"), allow(dead_code))]
+pub struct PricePoint {
+    pub recorded_at: DateTime<Utc>,
+    pub price: f32,
+}
+
+/// Records the current price of the vehicles in the database.
+pub fn record_prices(path: &Path, vehicles: &[&Vehicle]) -> Result<()> {
+    record_prices_at(path, vehicles, Utc::now())
+}
+
+/// Prints the recorded price points of a vehicle, oldest first.
+pub fn print_history(path: &Path, vss_id: &Uuid, locale: &str) -> Result<()> {
+    let price_points = load_price_points(path, vss_id)?;
+    if price_points.is_empty() {
+        println!("No price recorded for {}", vss_id);
+    }
+    for point in price_points {
+        println!(
+            "{} | {}",
+            point.recorded_at.format("%Y-%m-%d %H:%M"),
+            format_price_cell(point.price, locale)
+        );
+    }
+    Ok(())
+}
+
+#[cfg(feature = "history")]
+fn record_prices_at(path: &Path, vehicles: &[&Vehicle], recorded_at: DateTime<Utc>) -> Result<()> {
+    PriceHistory::open(path)?.record(vehicles, recorded_at)
+}
+
+#[cfg(not(feature = "history"))]
+fn record_prices_at(
+    _path: &Path,
+    _vehicles: &[&Vehicle],
+    _recorded_at: DateTime<Utc>,
+) -> Result<()> {
+    anyhow::bail!("built without the history feature")
+}
+
+#[cfg(feature = "history")]
+fn load_price_points(path: &Path, vss_id: &Uuid) -> Result<Vec<PricePoint>> {
+    PriceHistory::open(path)?.price_points(vss_id)
+}
+
+#[cfg(not(feature = "history"))]
+fn load_price_points(_path: &Path, _vss_id: &Uuid) -> Result<Vec<PricePoint>> {
+    anyhow::bail!("built without the history feature")
+}
+
+/// Price history table of a SQLite database.
+#[cfg(feature = "history")]
+pub struct PriceHistory {
+    connection: rusqlite::Connection,
+}
+
+#[cfg(feature = "history")]
+impl PriceHistory {
+    /// Opens the database, creating the price history table when missing.
+    pub fn open(path: &Path) -> Result<Self> {
+        use anyhow::Context;
+
+        let connection = rusqlite::Connection::open(path)
+            .with_context(|| format!("Failed to open database {}", path.display()))?;
+        connection.execute(
+            "CREATE TABLE IF NOT EXISTS price_history (
+                vss_id TEXT NOT NULL,
+                recorded_at TEXT NOT NULL,
+                price REAL NOT NULL,
+                PRIMARY KEY (vss_id, recorded_at)
+            )",
+            (),
+        )?;
+        Ok(Self { connection })
+    }
+
+    /// Upserts the price of every vehicle with a price at the given time, in a single transaction.
+    pub fn record(&mut self, vehicles: &[&Vehicle], recorded_at: DateTime<Utc>) -> Result<()> {
+        let recorded_at = recorded_at.to_rfc3339();
+        let transaction = self.connection.transaction()?;
+        {
+            let mut statement = transaction.prepare(
+                "INSERT INTO price_history (vss_id, recorded_at, price) VALUES (?1, ?2, ?3)
+                 ON CONFLICT (vss_id, recorded_at) DO UPDATE SET price = excluded.price",
+            )?;
+            for (vehicle, price) in vehicles
+                .iter()
+                .filter_map(|vehicle| Some((vehicle, vehicle.get_price()?)))
+            {
+                statement.execute((vehicle.vss_id.to_string(), &recorded_at, price))?;
+            }
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+
+    /// Price points of a vehicle, oldest first.
+    pub fn price_points(&self, vss_id: &Uuid) -> Result<Vec<PricePoint>> {
+        let mut statement = self.connection.prepare(
+            "SELECT recorded_at, price FROM price_history WHERE vss_id = ?1 ORDER BY recorded_at",
+        )?;
+        let rows = statement.query_map([vss_id.to_string()], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, f32>(1)?))
+        })?;
+        rows.map(|row| -> Result<PricePoint> {
+            let (recorded_at, price) = row?;
+            Ok(PricePoint {
+                recorded_at: DateTime::parse_from_rfc3339(&recorded_at)?.with_timezone(&Utc),
+                price,
+            })
+        })
+        .collect()
+    }
+}
+
+#[cfg(all(test, feature = "history"))]
+mod tests {
+    use super::*;
+    use crate::vehicle::test_support::{from_json, vehicle_json};
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    fn vehicle(vss_id: &str, price: f32) -> Vehicle {
+        let mut value = vehicle_json(price, None);
+        value["vssId"] = json!(vss_id);
+        from_json(value)
+    }
+
+    fn at(day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 6, day, 8, 0, 0)
+            .single()
+            .expect("Invalid date")
+    }
+
+    #[test]
+    fn should_round_trip_price_points() {
+        let mut history = PriceHistory::open(Path::new(":memory:")).expect("Failed to open");
+        let tracked = vehicle("67e55044-10b1-426f-9247-bb680e5fe0c8", 42000.0);
+        let other = vehicle("0b4f0b5e-6ad7-4f6c-9d1d-6a8a2c8b7f01", 30000.0);
+        let cheaper = vehicle("67e55044-10b1-426f-9247-bb680e5fe0c8", 39500.0);
+
+        history
+            .record(&[&tracked, &other], at(1))
+            .expect("Failed to record");
+        history
+            .record(&[&cheaper], at(2))
+            .expect("Failed to record");
+
+        assert_eq!(
+            history
+                .price_points(&tracked.vss_id)
+                .expect("Failed to query"),
+            vec![
+                PricePoint {
+                    recorded_at: at(1),
+                    price: 42000.0
+                },
+                PricePoint {
+                    recorded_at: at(2),
+                    price: 39500.0
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn should_update_the_price_recorded_at_the_same_time() {
+        let mut history = PriceHistory::open(Path::new(":memory:")).expect("Failed to open");
+        let vehicle_before = vehicle("67e55044-10b1-426f-9247-bb680e5fe0c8", 42000.0);
+        let vehicle_after = vehicle("67e55044-10b1-426f-9247-bb680e5fe0c8", 41000.0);
+
+        history
+            .record(&[&vehicle_before], at(1))
+            .expect("Failed to record");
+        history
+            .record(&[&vehicle_after], at(1))
+            .expect("Failed to record");
+
+        let points = history
+            .price_points(&vehicle_before.vss_id)
+            .expect("Failed to query");
+        assert_eq!(points.len(), 1);
+        assert_eq!(points[0].price, 41000.0);
+    }
+
+    #[test]
+    fn should_have_no_price_points_for_unknown_vehicle() {
+        let history = PriceHistory::open(Path::new(":memory:")).expect("Failed to open");
+
+        assert!(
+            history
+                .price_points(&Uuid::new_v4())
+                .expect("Failed to query")
+                .is_empty()
+        );
+    }
+}
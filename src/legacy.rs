@@ -1,100 +1,3488 @@
-//! Legacy module for text and JSON output in the BMW Finder application.
+//! Legacy module for text, JSON, NDJSON, CSV, HTML and RSS output in the BMW Finder application.
 //! Contains the legacy mode execution logic and associated display functions.
 
+use anyhow::{Context, Result};
+use futures::TryStreamExt;
 use itertools::Itertools;
+use owo_colors::{OwoColorize, Style};
+use reqwest::Client;
 use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::pin::pin;
 
-use crate::bmw::search::search;
-use crate::config::{Condition, Configuration, OutputMode};
-use crate::vehicle::Vehicle;
+use crate::browser::open_vehicles;
+use crate::color::color_stream;
+use crate::config::{
+    Condition, Configuration, EquipmentMatch, GroupBy, OutputField, OutputMode, SortKey, SortOrder,
+};
+use crate::history::record_prices;
+use crate::output::{output_dir_file_name, output_dir_modes, output_writer, writer_for};
+use crate::report::{VehicleReport, compute_summary, compute_summary_report};
+use crate::source::VehicleSource;
+use crate::state::{Change, State};
+use crate::vehicle::{Dealer, Vehicle, stocklocator_url};
+use crate::webhook::send_webhook;
 
-/// Runs the legacy (text/json) mode of the application.
-pub async fn run(configuration: &Configuration) {
-    print_header(configuration);
-    let found_vehicles = fetch_and_report_vehicles(configuration).await;
+/// Runs the legacy (text/json/ndjson/csv) mode of the application.
+/// The client is used to deliver the webhook.
+pub async fn run(
+    source: &impl VehicleSource,
+    client: &Client,
+    configuration: &Configuration,
+) -> Result<()> {
+    print_header(configuration, &mut io::stdout().lock())?;
+    let found_vehicles = fetch_and_report_vehicles(source, configuration).await?;
     let filtered_vehicles = filter_and_sort_vehicles(&found_vehicles, configuration);
-    match configuration.output() {
-        OutputMode::Text => print_text_output(&filtered_vehicles),
-        OutputMode::Json => print_json_output(&filtered_vehicles),
-        _ => unreachable!(),
+    let previous_state = match configuration.state_file() {
+        Some(path) => State::load(path)?,
+        None => None,
+    };
+    write_output(configuration, &filtered_vehicles, previous_state.as_ref())?;
+    if source.interrupted() {
+        // Partial results are printed but neither recorded nor delivered.
+        return Ok(());
+    }
+    if let Some(path) = configuration.db() {
+        record_prices(path, &filtered_vehicles)?;
+    }
+    if let Some(count) = configuration.open() {
+        open_vehicles(&filtered_vehicles, count, configuration.locale());
+    }
+    if let Some(url) = configuration.webhook()
+        && let Err(e) = send_webhook(client, url, configuration, &filtered_vehicles).await
+    {
+        eprintln!("Error delivering webhook: {:#}", e);
+    }
+    if let Some(path) = configuration.state_file() {
+        State::from_vehicles(&filtered_vehicles).save(path)?;
+    }
+    Ok(())
+}
+
+/// Prints the matching vehicles as their page arrives, in text or ndjson.
+/// The vehicles are not sorted across pages and the text output has no summary.
+pub async fn run_stream(source: &impl VehicleSource, configuration: &Configuration) -> Result<()> {
+    print_header(configuration, &mut io::stdout().lock())?;
+    let count = stream_output(
+        source,
+        configuration,
+        &mut color_stream(io::stdout(), configuration.color_mode()),
+    )
+    .await?;
+    if !configuration.quiet() {
+        println!("\n{} vehicles", count);
+    }
+    Ok(())
+}
+
+/// Writes each matching vehicle as soon as the source yields it, returning how many were written.
+async fn stream_output(
+    source: &impl VehicleSource,
+    configuration: &Configuration,
+    writer: &mut impl Write,
+) -> Result<usize> {
+    let fields = configuration.fields();
+    if configuration.output() == OutputMode::Text {
+        writeln!(
+            writer,
+            "{}",
+            format_text_row(
+                fields,
+                |field| text_header(field).to_string(),
+                |_| Style::new()
+            )
+        )?;
+    }
+    let mut vehicles = pin!(source.search_stream(configuration));
+    let mut count = 0;
+    while let Some(vehicle) = vehicles.try_next().await.context("Error during search")? {
+        if !vehicle_matches_filters(&vehicle, configuration) {
+            continue;
+        }
+        match configuration.output() {
+            OutputMode::Text => writeln!(
+                writer,
+                "{}",
+                format_vehicle_text_row(&vehicle, configuration, false)
+            )?,
+            _ => {
+                serde_json::to_writer(
+                    &mut *writer,
+                    &VehicleReport::new(&vehicle, configuration.locale())
+                        .with_display_currency(configuration.display_currency())
+                        .with_image(configuration.with_images()),
+                )?;
+                writeln!(writer)?;
+            }
+        }
+        writer.flush()?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Prints the number of matching vehicles only.
+pub async fn run_count(source: &impl VehicleSource, configuration: &Configuration) -> Result<()> {
+    println!("{}", count_vehicles(source, configuration).await?);
+    Ok(())
+}
+
+/// Counts the matching vehicles. The total count of the search is enough without
+/// local filters, otherwise every vehicle is fetched to count the filtered ones.
+async fn count_vehicles(
+    source: &impl VehicleSource,
+    configuration: &Configuration,
+) -> Result<usize> {
+    if !configuration.has_local_filters() {
+        return Ok(source
+            .count(configuration)
+            .await
+            .context("Error during search")? as usize);
+    }
+    let found_vehicles = source
+        .search(configuration)
+        .await
+        .context("Error during search")?;
+    Ok(filter_and_sort_vehicles(&found_vehicles, configuration).len())
+}
+
+/// Writes the vehicles to the configured output directory or file, or to stdout when none is set.
+/// Vehicles are annotated with their changes when a previous state is given.
+fn write_output(
+    configuration: &Configuration,
+    vehicles: &[&Vehicle],
+    previous_state: Option<&State>,
+) -> Result<()> {
+    if let Some(dir) = configuration.output_dir() {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create output directory {}", dir.display()))?;
+        for output in output_dir_modes(configuration) {
+            let path = dir.join(output_dir_file_name(output));
+            let file = File::create(&path)
+                .with_context(|| format!("Failed to create output file {}", path.display()))?;
+            let mut writer = BufWriter::new(color_stream(file, configuration.color_mode()));
+            writer_for(output, configuration, previous_state).write(vehicles, &mut writer)?;
+            writer.flush()?;
+        }
+        return Ok(());
+    }
+    let output_writer = output_writer(configuration, previous_state);
+    match configuration.output_file() {
+        Some(path) => {
+            let file = File::create(path)
+                .with_context(|| format!("Failed to create output file {}", path.display()))?;
+            let mut writer = BufWriter::new(color_stream(file, configuration.color_mode()));
+            output_writer.write(vehicles, &mut writer)?;
+            writer.flush()?;
+        }
+        None => output_writer.write(
+            vehicles,
+            &mut color_stream(io::stdout().lock(), configuration.color_mode()),
+        )?,
     }
+    Ok(())
 }
 
-/// Prints the search header for output.
-fn print_header(configuration: &Configuration) {
-    if configuration.limit.is_some() {
-        println!("Limiting results to {}", configuration.limit.unwrap());
+/// Prints the search header for output, nothing in quiet mode.
+fn print_header(configuration: &Configuration, writer: &mut impl Write) -> io::Result<()> {
+    if configuration.quiet() {
+        return Ok(());
     }
-    println!(
+    if let Some(limit) = configuration.limit {
+        writeln!(writer, "Limiting results to {}", limit)?;
+    }
+    writeln!(
+        writer,
         "Searching for {} vehicles ({}) ...\n",
-        match configuration.condition {
-            Condition::New => "new",
-            Condition::Used => "used",
+        match (configuration.all_conditions(), configuration.condition) {
+            (true, _) => "new and used",
+            (false, Condition::New) => "new",
+            (false, Condition::Used) => "used",
         },
         configuration.models().join(", ")
-    );
+    )
 }
 
 /// Fetches vehicles and prints the number found.
-async fn fetch_and_report_vehicles(configuration: &Configuration) -> HashMap<uuid::Uuid, Vehicle> {
-    let found_vehicles = search(configuration).await.unwrap();
-    println!("Found {} vehicles:", found_vehicles.len());
-    found_vehicles
+async fn fetch_and_report_vehicles(
+    source: &impl VehicleSource,
+    configuration: &Configuration,
+) -> Result<HashMap<uuid::Uuid, Vehicle>> {
+    let found_vehicles = source
+        .search(configuration)
+        .await
+        .context("Error during search")?;
+    if !configuration.quiet() {
+        println!("Found {} vehicles:", found_vehicles.len());
+    }
+    Ok(found_vehicles)
 }
 
 /// Filters and sorts vehicles according to configuration.
-fn filter_and_sort_vehicles<'a>(
+pub fn filter_and_sort_vehicles<'a>(
     found_vehicles: &'a HashMap<uuid::Uuid, Vehicle>,
     configuration: &Configuration,
 ) -> Vec<&'a Vehicle> {
-    found_vehicles
+    let vehicles: Vec<&Vehicle> = found_vehicles
         .values()
-        .filter(|vehicle| vehicle_matches_equipment(vehicle, configuration))
-        .sorted_by(|a, b| sort_by_price(a, b))
+        .filter(|vehicle| vehicle_matches_filters(vehicle, configuration))
+        .sorted_by(|a, b| sort_vehicles(a, b, configuration))
+        .collect();
+    let mut vehicles = match configuration.limit_per_model() {
+        Some(limit) => limit_per_model(vehicles, limit),
+        None => vehicles,
+    };
+    if let Some(top) = configuration.top() {
+        vehicles.truncate(top);
+    }
+    vehicles
+}
+
+/// Checks if the vehicle passes every configured filter.
+pub fn vehicle_matches_filters(vehicle: &Vehicle, configuration: &Configuration) -> bool {
+    vehicle_matches_vss_id(vehicle, configuration)
+        && vehicle_matches_equipment(vehicle, configuration)
+        && vehicle_matches_price(vehicle, configuration)
+        && vehicle_matches_discount(vehicle, configuration)
+        && vehicle_matches_savings(vehicle, configuration)
+        && vehicle_matches_score(vehicle, configuration)
+        && vehicle_matches_mileage(vehicle, configuration)
+        && vehicle_matches_owners(vehicle, configuration)
+        && vehicle_matches_registration_date(vehicle, configuration)
+        && vehicle_matches_fuel(vehicle, configuration)
+        && vehicle_matches_power(vehicle, configuration)
+        && vehicle_matches_transmission(vehicle, configuration)
+        && vehicle_matches_color(vehicle, configuration)
+        && vehicle_matches_body_type(vehicle, configuration)
+        && vehicle_matches_dealer(vehicle, configuration)
+        && vehicle_matches_availability(vehicle, configuration)
+}
+
+/// Keeps at most `limit` vehicles of each model range, in their sorted order.
+/// Vehicles without a model range count as one group.
+fn limit_per_model(vehicles: Vec<&Vehicle>, limit: u32) -> Vec<&Vehicle> {
+    let mut counts: HashMap<Option<&str>, u32> = HashMap::new();
+    vehicles
+        .into_iter()
+        .filter(|vehicle| {
+            let count = counts.entry(vehicle.get_model_range()).or_default();
+            *count += 1;
+            *count <= limit
+        })
         .collect()
 }
 
+/// Checks that the vehicle is not one of the excluded vehicles.
+pub fn vehicle_matches_vss_id(vehicle: &Vehicle, configuration: &Configuration) -> bool {
+    !configuration.excluded_vss_ids().contains(&vehicle.vss_id)
+}
+
 /// Checks if a vehicle matches the expected equipment configuration.
+/// Equipment names are matched as substrings, or fuzzily with a similarity threshold.
 pub fn vehicle_matches_equipment(vehicle: &Vehicle, configuration: &Configuration) -> bool {
+    let locale = configuration.equipment_locale();
+    let has_name = |name: &String| match configuration.equipment_similarity() {
+        Some(threshold) => vehicle.has_equipment_name_similar(name, locale, threshold),
+        None => vehicle.has_equipment_name_like(name, locale),
+    };
     configuration
         .equipment_names()
-        .map(|equipment_names| vehicle.has_equipment_names(equipment_names))
-        .unwrap_or(true)
+        .is_none_or(|equipment_names| match configuration.equipment_match() {
+            EquipmentMatch::All => equipment_names.iter().all(has_name),
+            EquipmentMatch::Any => equipment_names.iter().any(has_name),
+        })
+        && configuration
+            .equipment_codes()
+            .is_none_or(|equipment_codes| vehicle.has_equipment_codes(equipment_codes))
+        && configuration
+            .equipment_regex()
+            .is_none_or(|pattern| vehicle.has_equipment_name_matching(pattern, locale))
+        && configuration
+            .excluded_equipment_names()
+            .is_none_or(|excluded_names| !excluded_names.iter().any(has_name))
+}
+
+/// Checks if the vehicle price is within the configured price range.
+/// A vehicle without a price only matches when no range is configured.
+pub fn vehicle_matches_price(vehicle: &Vehicle, configuration: &Configuration) -> bool {
+    let price = vehicle.get_price();
+    configuration
+        .min_price()
+        .is_none_or(|min| price.is_some_and(|price| price >= min))
+        && configuration
+            .max_price()
+            .is_none_or(|max| price.is_some_and(|price| price <= max))
+}
+
+/// Checks if the vehicle discount reaches the configured minimum discount,
+/// and is positive when only discounted vehicles are wanted.
+pub fn vehicle_matches_discount(vehicle: &Vehicle, configuration: &Configuration) -> bool {
+    let discount = vehicle.get_discount_percentage();
+    configuration
+        .min_discount()
+        .is_none_or(|min_discount| discount.is_some_and(|discount| discount >= min_discount))
+        && (!configuration.discounted_only() || discount.is_some_and(|discount| discount > 0.0))
+}
+
+/// Checks if the relevance score of the vehicle reaches the configured minimum score.
+/// Vehicles without a score never match when the filter is set.
+pub fn vehicle_matches_score(vehicle: &Vehicle, configuration: &Configuration) -> bool {
+    configuration
+        .min_score()
+        .is_none_or(|min_score| vehicle.get_score().is_some_and(|score| score >= min_score))
+}
+
+/// Checks if the amount saved on the vehicle reaches the configured minimum savings.
+/// Vehicles without an offer price never match when the filter is set.
+pub fn vehicle_matches_savings(vehicle: &Vehicle, configuration: &Configuration) -> bool {
+    configuration.min_savings().is_none_or(|min_savings| {
+        vehicle
+            .get_discount_amount()
+            .is_some_and(|savings| savings >= min_savings)
+    })
+}
+
+/// Checks if the vehicle mileage is below the configured maximum mileage.
+/// New vehicles without mileage always match.
+pub fn vehicle_matches_mileage(vehicle: &Vehicle, configuration: &Configuration) -> bool {
+    configuration
+        .max_mileage()
+        .is_none_or(|max_mileage| match vehicle.get_mileage() {
+            Some(mileage) => mileage <= max_mileage,
+            None => vehicle.is_new(),
+        })
+}
+
+/// Checks if the vehicle had at most the configured number of previous owners.
+/// New vehicles always match, used vehicles without the data never match when the filter is set.
+pub fn vehicle_matches_owners(vehicle: &Vehicle, configuration: &Configuration) -> bool {
+    configuration
+        .max_owners()
+        .is_none_or(|max_owners| match vehicle.get_previous_owners() {
+            Some(owners) => vehicle.is_new() || owners <= max_owners,
+            None => vehicle.is_new(),
+        })
+}
+
+/// Checks if the vehicle was registered on or after the configured date.
+/// Vehicles without a known date never match when the filter is set.
+pub fn vehicle_matches_registration_date(vehicle: &Vehicle, configuration: &Configuration) -> bool {
+    configuration
+        .registered_after()
+        .is_none_or(|registered_after| {
+            vehicle
+                .get_registration_date()
+                .is_some_and(|date| date >= registered_after)
+        })
+}
+
+/// Checks if the vehicle fuel type is the configured one.
+pub fn vehicle_matches_fuel(vehicle: &Vehicle, configuration: &Configuration) -> bool {
+    configuration
+        .fuel()
+        .is_none_or(|fuel| vehicle.get_fuel_type() == Some(fuel))
+}
+
+/// Checks if the vehicle power reaches the configured minimum power.
+/// Vehicles without a known power never match when the filter is set.
+pub fn vehicle_matches_power(vehicle: &Vehicle, configuration: &Configuration) -> bool {
+    configuration.min_power().is_none_or(|min_power| {
+        vehicle
+            .get_power_hp()
+            .is_some_and(|power| power >= min_power)
+    })
+}
+
+/// Checks if the vehicle transmission is the configured one.
+/// Vehicles with an unknown or missing transmission never match when the filter is set.
+pub fn vehicle_matches_transmission(vehicle: &Vehicle, configuration: &Configuration) -> bool {
+    configuration
+        .transmission()
+        .is_none_or(|transmission| vehicle.get_transmission() == Some(transmission))
+}
+
+/// Checks if the vehicle exterior color contains the configured color name.
+pub fn vehicle_matches_color(vehicle: &Vehicle, configuration: &Configuration) -> bool {
+    configuration
+        .color()
+        .is_none_or(|color| vehicle.has_exterior_color_like(color))
+}
+
+/// Checks if the vehicle body type contains the configured body type.
+/// Vehicles without a known body type never match.
+pub fn vehicle_matches_body_type(vehicle: &Vehicle, configuration: &Configuration) -> bool {
+    configuration
+        .body_type()
+        .is_none_or(|body_type| vehicle.has_body_type_like(body_type))
+}
+
+/// Checks if the vehicle dealer name contains the configured dealer name.
+/// Vehicles without dealer information never match.
+pub fn vehicle_matches_dealer(vehicle: &Vehicle, configuration: &Configuration) -> bool {
+    configuration
+        .dealer()
+        .is_none_or(|dealer| vehicle.has_dealer_name_like(dealer))
+}
+
+/// Checks if the vehicle is available when only in stock vehicles are wanted.
+pub fn vehicle_matches_availability(vehicle: &Vehicle, configuration: &Configuration) -> bool {
+    !configuration.in_stock_only() || vehicle.is_available()
+}
+
+/// Sorts two vehicles according to the configured sort keys and order, each key
+/// breaking the ties of the previous ones. Remaining ties are broken by VSS ID,
+/// so the output does not depend on the search order.
+pub fn sort_vehicles(
+    vehicle_a: &Vehicle,
+    vehicle_b: &Vehicle,
+    configuration: &Configuration,
+) -> Ordering {
+    configuration
+        .sort_keys()
+        .iter()
+        .fold(Ordering::Equal, |ordering, &sort_key| {
+            ordering.then_with(|| {
+                compare_by_key(
+                    sort_key,
+                    vehicle_a,
+                    vehicle_b,
+                    configuration.sort_order(sort_key),
+                )
+            })
+        })
+        .then_with(|| vehicle_a.vss_id.cmp(&vehicle_b.vss_id))
+}
+
+/// Compares two vehicles on a single sort key in the given order, None last.
+pub fn compare_by_key(
+    sort_key: SortKey,
+    vehicle_a: &Vehicle,
+    vehicle_b: &Vehicle,
+    sort_order: SortOrder,
+) -> Ordering {
+    match sort_key {
+        SortKey::Price => sort_by_price(vehicle_a, vehicle_b, sort_order),
+        SortKey::Discount => sort_by_discount(vehicle_a, vehicle_b, sort_order),
+        SortKey::Mileage => sort_by_mileage(vehicle_a, vehicle_b, sort_order),
+    }
+}
+
+/// Sorts two vehicles by discount percentage in the given order, None last.
+pub fn sort_by_discount(
+    vehicle_a: &Vehicle,
+    vehicle_b: &Vehicle,
+    sort_order: SortOrder,
+) -> Ordering {
+    match (
+        vehicle_a.get_discount_percentage(),
+        vehicle_b.get_discount_percentage(),
+    ) {
+        (Some(discount_a), Some(discount_b)) => {
+            let ordering = discount_a
+                .partial_cmp(&discount_b)
+                .unwrap_or(Ordering::Equal);
+            match sort_order {
+                SortOrder::Asc => ordering,
+                SortOrder::Desc => ordering.reverse(),
+            }
+        }
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+/// Sorts two vehicles by mileage in the given order, None last.
+pub fn sort_by_mileage(
+    vehicle_a: &Vehicle,
+    vehicle_b: &Vehicle,
+    sort_order: SortOrder,
+) -> Ordering {
+    match (vehicle_a.get_mileage(), vehicle_b.get_mileage()) {
+        (Some(mileage_a), Some(mileage_b)) => match sort_order {
+            SortOrder::Asc => mileage_a.cmp(&mileage_b),
+            SortOrder::Desc => mileage_b.cmp(&mileage_a),
+        },
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+/// Sorts two vehicles by price in the given order, None last.
+pub fn sort_by_price(vehicle_a: &Vehicle, vehicle_b: &Vehicle, sort_order: SortOrder) -> Ordering {
+    match (vehicle_a.get_price(), vehicle_b.get_price()) {
+        (Some(price_a), Some(price_b)) => {
+            let ordering = price_a.partial_cmp(&price_b).unwrap_or(Ordering::Equal);
+            match sort_order {
+                SortOrder::Asc => ordering,
+                SortOrder::Desc => ordering.reverse(),
+            }
+        }
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+/// Writes the list of vehicles in text format.
+pub fn print_text_output(
+    vehicles: &[&Vehicle],
+    configuration: &Configuration,
+    previous_state: Option<&State>,
+    writer: &mut impl Write,
+) -> io::Result<()> {
+    let fields = configuration.fields();
+    writeln!(
+        writer,
+        "{}",
+        format_text_row(
+            fields,
+            |field| text_header(field).to_string(),
+            |_| Style::new()
+        )
+    )?;
+    let top_discount = top_discount_vehicle(vehicles);
+    match configuration.group_by() {
+        Some(GroupBy::Model) => {
+            for (model, group) in group_by_model(vehicles) {
+                writeln!(writer, "\n{} ({} vehicles)", model, group.len())?;
+                write_text_rows(&group, top_discount, configuration, previous_state, writer)?;
+            }
+        }
+        None => write_text_rows(
+            vehicles,
+            top_discount,
+            configuration,
+            previous_state,
+            writer,
+        )?,
+    }
+    let summary = compute_summary(vehicles);
+    writeln!(
+        writer,
+        "\n{} vehicles | min {} | max {} | avg {} | avg discount {} %",
+        summary.count,
+        format_display_price(summary.min_price, configuration),
+        format_display_price(summary.max_price, configuration),
+        format_display_price(summary.average_price, configuration),
+        format_number(summary.average_discount, configuration.locale())
+    )
+}
+
+/// Writes a text row per vehicle, annotated with its change since the previous run.
+/// The row of the top discount vehicle is bold.
+fn write_text_rows(
+    vehicles: &[&Vehicle],
+    top_discount: Option<uuid::Uuid>,
+    configuration: &Configuration,
+    previous_state: Option<&State>,
+    writer: &mut impl Write,
+) -> io::Result<()> {
+    for vehicle in vehicles {
+        let change = previous_state
+            .and_then(|state| state.change(vehicle))
+            .map(|change| format!(" | {}", format_change(&change, configuration)))
+            .unwrap_or_default();
+        writeln!(
+            writer,
+            "{}{}",
+            format_vehicle_text_row(vehicle, configuration, top_discount == Some(vehicle.vss_id)),
+            change
+        )?;
+    }
+    Ok(())
+}
+
+/// Discount percentage above which the discount cell of the text output is green.
+const HIGHLIGHT_DISCOUNT_PERCENTAGE: f32 = 10.0;
+
+/// Style of the discount cell of the text output, green above the highlight threshold.
+fn discount_style(percentage: f32) -> Style {
+    match percentage > HIGHLIGHT_DISCOUNT_PERCENTAGE {
+        true => Style::new().green(),
+        false => Style::new(),
+    }
+}
+
+/// The vehicle with the highest discount, none when no vehicle is discounted.
+fn top_discount_vehicle(vehicles: &[&Vehicle]) -> Option<uuid::Uuid> {
+    vehicles
+        .iter()
+        .filter_map(|vehicle| Some((vehicle.vss_id, vehicle.get_discount_percentage()?)))
+        .filter(|&(_, discount)| discount > 0.0)
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(vss_id, _)| vss_id)
+}
+
+/// Formats the text row of a vehicle, with the discount cell styled and the whole row
+/// bold when highlighted. The colors are stripped by the output stream when disabled.
+fn format_vehicle_text_row(
+    vehicle: &Vehicle,
+    configuration: &Configuration,
+    highlighted: bool,
+) -> String {
+    let row_style = match highlighted {
+        true => Style::new().bold(),
+        false => Style::new(),
+    };
+    let discount_style = vehicle
+        .get_discount_percentage()
+        .map_or(row_style, |discount| match highlighted {
+            true => discount_style(discount).bold(),
+            false => discount_style(discount),
+        });
+    format_text_row(
+        configuration.fields(),
+        |field| text_cell(field, vehicle, configuration),
+        |field| match field {
+            OutputField::Discount => discount_style,
+            _ => row_style,
+        },
+    )
+}
+
+/// Heading of the vehicles without a model range when grouping by model.
+const UNKNOWN_MODEL: &str = "Unknown";
+
+/// Splits the vehicles by model range, in alphabetical order with the unknown ones last.
+/// Each group keeps the sorted order of the vehicles.
+fn group_by_model<'a>(vehicles: &[&'a Vehicle]) -> Vec<(&'a str, Vec<&'a Vehicle>)> {
+    let mut groups: BTreeMap<Option<&str>, Vec<&Vehicle>> = BTreeMap::new();
+    for &vehicle in vehicles {
+        groups
+            .entry(vehicle.get_model_range())
+            .or_default()
+            .push(vehicle);
+    }
+    let unknown = groups.remove(&None);
+    groups
+        .into_iter()
+        .filter_map(|(model, group)| Some((model?, group)))
+        .chain(unknown.map(|group| (UNKNOWN_MODEL, group)))
+        .collect()
+}
+
+/// Describes the change of a vehicle, with the previous price formatted for display.
+fn format_change(change: &Change, configuration: &Configuration) -> String {
+    match change {
+        Change::New => change.to_string(),
+        Change::PriceDrop { previous_price } => format!(
+            "PRICE DROP (was {})",
+            format_display_price(*previous_price, configuration)
+        ),
+    }
+}
+
+/// Joins the cells of the fields, padded to the column width except the last one,
+/// then styled so the escape sequences do not count in the width.
+fn format_text_row(
+    fields: &[OutputField],
+    cell: impl Fn(OutputField) -> String,
+    style: impl Fn(OutputField) -> Style,
+) -> String {
+    fields
+        .iter()
+        .enumerate()
+        .map(|(index, &field)| {
+            let text = match index + 1 == fields.len() {
+                true => cell(field),
+                false => format!("{: <width$}", cell(field), width = text_width(field)),
+            };
+            text.style(style(field)).to_string()
+        })
+        .join(" | ")
+}
+
+pub fn text_header(field: OutputField) -> &'static str {
+    match field {
+        OutputField::Id => "Id",
+        OutputField::Price => "Price",
+        OutputField::Net => "Net",
+        OutputField::Discount => "Discount",
+        OutputField::Savings => "Savings",
+        OutputField::Link => "Link",
+        OutputField::Mileage => "Mileage",
+        OutputField::Power => "Power",
+        OutputField::Fuel => "Fuel",
+        OutputField::Transmission => "Transmission",
+        OutputField::Color => "Color",
+        OutputField::Interior => "Interior",
+        OutputField::Registration => "Registration",
+        OutputField::Score => "Score",
+        OutputField::Dealer => "Dealer",
+        OutputField::Model => "Model",
+        OutputField::Status => "Status",
+        OutputField::Image => "Image",
+        OutputField::Body => "Body",
+    }
+}
+
+fn text_width(field: OutputField) -> usize {
+    match field {
+        OutputField::Id => 36,
+        OutputField::Discount | OutputField::Power | OutputField::Fuel | OutputField::Score => 8,
+        OutputField::Mileage | OutputField::Status => 10,
+        OutputField::Color | OutputField::Interior => 20,
+        OutputField::Model => 26,
+        OutputField::Dealer => 32,
+        OutputField::Price
+        | OutputField::Net
+        | OutputField::Savings
+        | OutputField::Link
+        | OutputField::Image
+        | OutputField::Body
+        | OutputField::Transmission
+        | OutputField::Registration => 12,
+    }
+}
+
+/// Formats a field of the vehicle for the text table, a dash when the value is unknown.
+pub fn text_cell(field: OutputField, vehicle: &Vehicle, configuration: &Configuration) -> String {
+    let dash = || String::from("-");
+    match field {
+        OutputField::Id => vehicle.vss_id.to_string(),
+        OutputField::Price => vehicle
+            .get_price()
+            .map_or_else(dash, |price| format_display_price(price, configuration)),
+        OutputField::Discount => {
+            format_discount_cell(vehicle.get_discount_percentage(), configuration.locale())
+        }
+        OutputField::Savings => format_savings_cell(vehicle.get_discount_amount(), configuration),
+        OutputField::Net => vehicle
+            .get_net_price(configuration.vat())
+            .map_or_else(dash, |price| format_display_price(price, configuration)),
+        OutputField::Link => vehicle.get_link(configuration.locale()),
+        OutputField::Mileage => vehicle
+            .get_mileage()
+            .map_or_else(dash, |mileage| format!("{} km", mileage)),
+        OutputField::Power => vehicle
+            .get_power_hp()
+            .map_or_else(dash, |power| format!("{} hp", power)),
+        OutputField::Fuel => vehicle
+            .get_fuel_type()
+            .map_or_else(dash, |fuel| format!("{:?}", fuel)),
+        OutputField::Transmission => vehicle
+            .get_transmission()
+            .map_or_else(dash, |transmission| format!("{:?}", transmission)),
+        OutputField::Color => vehicle
+            .get_exterior_color()
+            .map_or_else(dash, str::to_string),
+        OutputField::Interior => vehicle
+            .get_interior_color()
+            .map_or_else(dash, str::to_string),
+        OutputField::Registration => vehicle
+            .get_registration_date()
+            .map_or_else(dash, |date| date.to_string()),
+        OutputField::Score => vehicle
+            .get_score()
+            .map_or_else(dash, |score| format!("{:.2}", score)),
+        OutputField::Dealer => vehicle
+            .get_dealer()
+            .and_then(Dealer::label)
+            .unwrap_or_else(dash),
+        OutputField::Model => vehicle
+            .get_model_name()
+            .or(vehicle.get_model_range())
+            .map_or_else(dash, str::to_string),
+        OutputField::Status => vehicle
+            .get_availability()
+            .map_or_else(dash, |availability| format!("{:?}", availability)),
+        OutputField::Image => vehicle.get_first_image().map_or_else(dash, str::to_string),
+        OutputField::Body => vehicle.get_body_type().map_or_else(dash, str::to_string),
+    }
 }
 
-/// Sorts two vehicles by ascending price, None last.
-pub fn sort_by_price(vehicle_a: &Vehicle, vehicle_b: &Vehicle) -> Ordering {
-    vehicle_a
-        .get_price()
-        .partial_cmp(&vehicle_b.get_price())
-        .unwrap_or(Ordering::Equal)
+/// Formats a number with two decimals and the thousands and decimal separators
+/// of the locale language, e.g. `45 990,00` for `fr-fr` or `45,990.00` for `en-gb`.
+pub fn format_number(value: f32, locale: &str) -> String {
+    let (thousands_separator, decimal_separator) = number_separators(locale);
+    let formatted = format!("{:.2}", value);
+    let (sign, digits) = match formatted.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("", formatted.as_str()),
+    };
+    let (integer, fraction) = digits.split_once('.').unwrap_or((digits, "00"));
+    let grouped = integer
+        .as_bytes()
+        .rchunks(3)
+        .rev()
+        .map(|group| std::str::from_utf8(group).expect("Digits are ASCII"))
+        .join(thousands_separator);
+    format!("{}{}{}{}", sign, grouped, decimal_separator, fraction)
 }
 
-/// Displays the list of vehicles in text format.
-pub fn print_text_output(vehicles: &[&Vehicle]) {
-    println!(
-        "{0: <36} | {1: <12} | {2: <8} | {3}",
-        "Id", "Price", "Discount", "Link"
-    );
+/// Thousands and decimal separators of the language of a `lang-region` locale.
+fn number_separators(locale: &str) -> (&'static str, &'static str) {
+    match locale.split('-').next().unwrap_or_default() {
+        "fr" | "pl" | "cs" | "sk" | "sv" | "fi" | "no" | "nb" => (" ", ","),
+        "de" | "es" | "it" | "nl" | "pt" | "da" | "tr" | "el" => (".", ","),
+        _ => (",", "."),
+    }
+}
+
+/// Formats a price for the text table.
+pub fn format_price_cell(price: f32, locale: &str) -> String {
+    format!("{} €", format_number(price, locale))
+}
+
+/// Converts a euro amount into the display currency, unchanged without one.
+pub fn display_amount(amount: f32, configuration: &Configuration) -> f32 {
+    configuration
+        .display_currency()
+        .map_or(amount, |currency| currency.convert(amount))
+}
+
+/// Formats a price for display, converted into the display currency when one is configured.
+pub fn format_display_price(price: f32, configuration: &Configuration) -> String {
+    match configuration.display_currency() {
+        Some(currency) => format!(
+            "{} {}",
+            format_number(currency.convert(price), configuration.locale()),
+            currency.symbol()
+        ),
+        None => format_price_cell(price, configuration.locale()),
+    }
+}
+
+/// Formats a price in euros with two decimals for messages, a dash when it is unknown.
+pub fn format_euros(price: Option<f32>) -> String {
+    price.map_or_else(|| String::from("-"), |price| format!("{:.2} €", price))
+}
+
+/// Formats a discount for the text table, a dash when the vehicle has no offer price.
+pub fn format_discount_cell(discount: Option<f32>, locale: &str) -> String {
+    discount
+        .map(|discount| format!("{} %", format_number(discount, locale)))
+        .unwrap_or_else(|| String::from("-"))
+}
+
+/// Formats the amount saved for the text table, a dash when the vehicle has no offer price.
+pub fn format_savings_cell(savings: Option<f32>, configuration: &Configuration) -> String {
+    savings
+        .map(|savings| format_display_price(savings, configuration))
+        .unwrap_or_else(|| String::from("-"))
+}
+
+/// Writes the list of vehicle reports in JSON format, pretty-printed unless
+/// compact JSON is configured.
+pub fn print_json_output(
+    vehicles: &[&Vehicle],
+    configuration: &Configuration,
+    previous_state: Option<&State>,
+    writer: &mut impl Write,
+) -> io::Result<()> {
+    let reports: Vec<VehicleReport> = vehicles
+        .iter()
+        .map(|vehicle| {
+            VehicleReport::new(vehicle, configuration.locale())
+                .with_change(previous_state.and_then(|state| state.change(vehicle)))
+                .with_display_currency(configuration.display_currency())
+                .with_image(configuration.with_images())
+        })
+        .collect();
+    match configuration.json_compact() {
+        true => serde_json::to_writer(&mut *writer, &reports)?,
+        false => serde_json::to_writer_pretty(&mut *writer, &reports)?,
+    }
+    writeln!(writer)
+}
+
+/// Writes the summary of the vehicles as a single JSON object, without the vehicles,
+/// pretty-printed unless compact JSON is configured.
+pub fn print_summary_json_output(
+    vehicles: &[&Vehicle],
+    configuration: &Configuration,
+    writer: &mut impl Write,
+) -> io::Result<()> {
+    let report = compute_summary_report(vehicles);
+    match configuration.json_compact() {
+        true => serde_json::to_writer(&mut *writer, &report)?,
+        false => serde_json::to_writer_pretty(&mut *writer, &report)?,
+    }
+    writeln!(writer)
+}
+
+/// Writes one compact JSON vehicle report per line, as each vehicle is serialized.
+pub fn print_ndjson_output(
+    vehicles: &[&Vehicle],
+    configuration: &Configuration,
+    previous_state: Option<&State>,
+    writer: &mut impl Write,
+) -> io::Result<()> {
+    for vehicle in vehicles {
+        let report = VehicleReport::new(vehicle, configuration.locale())
+            .with_change(previous_state.and_then(|state| state.change(vehicle)))
+            .with_display_currency(configuration.display_currency())
+            .with_image(configuration.with_images());
+        serde_json::to_writer(&mut *writer, &report)?;
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+/// Writes the list of vehicles in CSV format.
+pub fn print_csv_output(
+    vehicles: &[&Vehicle],
+    configuration: &Configuration,
+    writer: &mut impl Write,
+) -> io::Result<()> {
+    let fields = configuration.fields();
+    writeln!(
+        writer,
+        "{}",
+        fields.iter().map(|&field| csv_header(field)).join(",")
+    )?;
+    for vehicle in vehicles {
+        writeln!(writer, "{}", format_csv_row(vehicle, fields, configuration))?;
+    }
+    Ok(())
+}
+
+fn csv_header(field: OutputField) -> &'static str {
+    match field {
+        OutputField::Id => "vss_id",
+        OutputField::Price => "price",
+        OutputField::Net => "net_price",
+        OutputField::Discount => "discount",
+        OutputField::Savings => "savings",
+        OutputField::Link => "link",
+        OutputField::Mileage => "mileage",
+        OutputField::Power => "power_hp",
+        OutputField::Fuel => "fuel",
+        OutputField::Transmission => "transmission",
+        OutputField::Color => "color",
+        OutputField::Interior => "interior_color",
+        OutputField::Registration => "registration_date",
+        OutputField::Score => "score",
+        OutputField::Dealer => "dealer",
+        OutputField::Model => "model",
+        OutputField::Status => "status",
+        OutputField::Image => "image",
+        OutputField::Body => "body_type",
+    }
+}
+
+/// Formats a vehicle as a CSV row, prices as plain numbers without currency
+/// and unknown values left empty.
+fn format_csv_row(
+    vehicle: &Vehicle,
+    fields: &[OutputField],
+    configuration: &Configuration,
+) -> String {
+    fields
+        .iter()
+        .map(|&field| match field {
+            OutputField::Id => vehicle.vss_id.to_string(),
+            OutputField::Price => vehicle
+                .get_price()
+                .map(|price| format!("{:.2}", display_amount(price, configuration)))
+                .unwrap_or_default(),
+            OutputField::Discount => vehicle
+                .get_discount_percentage()
+                .map(|discount| format!("{:.2}", discount))
+                .unwrap_or_default(),
+            OutputField::Savings => vehicle
+                .get_discount_amount()
+                .map(|savings| format!("{:.2}", display_amount(savings, configuration)))
+                .unwrap_or_default(),
+            OutputField::Net => vehicle
+                .get_net_price(configuration.vat())
+                .map(|price| format!("{:.2}", display_amount(price, configuration)))
+                .unwrap_or_default(),
+            OutputField::Link => vehicle.get_link(configuration.locale()),
+            OutputField::Mileage => vehicle
+                .get_mileage()
+                .map(|mileage| mileage.to_string())
+                .unwrap_or_default(),
+            OutputField::Power => vehicle
+                .get_power_hp()
+                .map(|power| power.to_string())
+                .unwrap_or_default(),
+            OutputField::Fuel => vehicle
+                .get_fuel_type()
+                .map(|fuel| format!("{:?}", fuel).to_lowercase())
+                .unwrap_or_default(),
+            OutputField::Transmission => vehicle
+                .get_transmission()
+                .map(|transmission| format!("{:?}", transmission).to_lowercase())
+                .unwrap_or_default(),
+            OutputField::Color => vehicle
+                .get_exterior_color()
+                .map(str::to_string)
+                .unwrap_or_default(),
+            OutputField::Interior => vehicle
+                .get_interior_color()
+                .map(str::to_string)
+                .unwrap_or_default(),
+            OutputField::Registration => vehicle
+                .get_registration_date()
+                .map(|date| date.to_string())
+                .unwrap_or_default(),
+            OutputField::Score => vehicle
+                .get_score()
+                .map(|score| score.to_string())
+                .unwrap_or_default(),
+            OutputField::Dealer => vehicle
+                .get_dealer()
+                .and_then(Dealer::label)
+                .unwrap_or_default(),
+            OutputField::Model => vehicle
+                .get_model_name()
+                .or(vehicle.get_model_range())
+                .map(str::to_string)
+                .unwrap_or_default(),
+            OutputField::Status => vehicle
+                .get_availability()
+                .map(|availability| format!("{:?}", availability).to_lowercase())
+                .unwrap_or_default(),
+            OutputField::Image => vehicle
+                .get_first_image()
+                .map(str::to_string)
+                .unwrap_or_default(),
+            OutputField::Body => vehicle
+                .get_body_type()
+                .map(str::to_string)
+                .unwrap_or_default(),
+        })
+        .map(|field| escape_csv_field(&field))
+        .join(",")
+}
+
+/// Writes a self-contained HTML document with a table of the vehicles.
+pub fn print_html_output(
+    vehicles: &[&Vehicle],
+    configuration: &Configuration,
+    writer: &mut impl Write,
+) -> io::Result<()> {
+    let locale = configuration.locale();
+    writeln!(writer, "<!DOCTYPE html>")?;
+    writeln!(writer, "<html lang=\"{}\">", escape_html(locale))?;
+    writeln!(writer, "<head>")?;
+    writeln!(writer, "<meta charset=\"utf-8\">")?;
+    writeln!(
+        writer,
+        "<title>BMW Finder - {}</title>",
+        escape_html(&configuration.models().join(", "))
+    )?;
+    writeln!(writer, "<style>{}</style>", HTML_STYLE)?;
+    writeln!(writer, "</head>")?;
+    writeln!(writer, "<body>")?;
+    writeln!(writer, "<table>")?;
+    writeln!(
+        writer,
+        "<thead><tr><th>Id</th><th>Price</th><th>Discount</th><th>Link</th></tr></thead>"
+    )?;
+    writeln!(writer, "<tbody>")?;
     for vehicle in vehicles {
-        println!(
-            "{0: <36} | {1: <12} | {2: <8} | {3}",
+        let link = escape_html(&vehicle.get_link(locale));
+        writeln!(
+            writer,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td><a href=\"{}\">{}</a></td></tr>",
             vehicle.vss_id,
-            format!("{:.2} €", vehicle.get_price()),
-            format!(
-                "{:.2} %",
-                vehicle.get_discount_percentage().unwrap_or_default()
-            ),
-            vehicle.get_link()
-        );
+            escape_html(&text_cell(OutputField::Price, vehicle, configuration)),
+            escape_html(&format_discount_cell(
+                vehicle.get_discount_percentage(),
+                locale
+            )),
+            link,
+            link
+        )?;
+    }
+    writeln!(writer, "</tbody>")?;
+    writeln!(writer, "</table>")?;
+    writeln!(writer, "</body>")?;
+    writeln!(writer, "</html>")
+}
+
+/// Writes an RSS 2.0 feed with an item per vehicle, titled with its model and price.
+pub fn print_rss_output(
+    vehicles: &[&Vehicle],
+    configuration: &Configuration,
+    writer: &mut impl Write,
+) -> io::Result<()> {
+    let locale = configuration.locale();
+    writeln!(writer, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+    writeln!(writer, "<rss version=\"2.0\">")?;
+    writeln!(writer, "<channel>")?;
+    writeln!(
+        writer,
+        "<title>BMW Finder - {}</title>",
+        escape_html(&configuration.models().join(", "))
+    )?;
+    writeln!(
+        writer,
+        "<link>{}</link>",
+        escape_html(&stocklocator_url(
+            locale,
+            configuration.condition == Condition::New
+        ))
+    )?;
+    writeln!(
+        writer,
+        "<description>{} vehicles</description>",
+        vehicles.len()
+    )?;
+    writeln!(writer, "<language>{}</language>", escape_html(locale))?;
+    for vehicle in vehicles {
+        let model = vehicle
+            .get_model_name()
+            .or(vehicle.get_model_range())
+            .unwrap_or(configuration.brand().as_param());
+        writeln!(writer, "<item>")?;
+        writeln!(
+            writer,
+            "<title>{} - {}</title>",
+            escape_html(model),
+            escape_html(&text_cell(OutputField::Price, vehicle, configuration))
+        )?;
+        writeln!(
+            writer,
+            "<link>{}</link>",
+            escape_html(&vehicle.get_link(locale))
+        )?;
+        writeln!(
+            writer,
+            "<guid isPermaLink=\"false\">{}</guid>",
+            vehicle.vss_id
+        )?;
+        writeln!(writer, "</item>")?;
+    }
+    writeln!(writer, "</channel>")?;
+    writeln!(writer, "</rss>")
+}
+
+const HTML_STYLE: &str = "body{font-family:sans-serif;margin:2em}\
+table{border-collapse:collapse}\
+th,td{border:1px solid #ccc;padding:.4em .8em;text-align:left}\
+th{background:#1c69d4;color:#fff}\
+tbody tr:nth-child(even){background:#f4f4f4}";
+
+/// Escapes the characters with a meaning in HTML text and attribute values.
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// Quotes a CSV field when it contains a separator, a quote or a line break.
+fn escape_csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
     }
 }
 
-/// Displays the list of vehicles in JSON format.
-pub fn print_json_output(vehicles: &[&Vehicle]) {
-    match serde_json::to_string_pretty(vehicles) {
-        Ok(json) => println!("{}", json),
-        Err(e) => eprintln!("Failed to serialize vehicles to JSON: {}", e),
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Args;
+    use crate::vehicle::test_support::{from_json, vehicle_json};
+    use clap::Parser;
+    use serde_json::json;
+
+    fn vehicle(gross_price: f32, offer_price: Option<f32>) -> Vehicle {
+        from_json(vehicle_json(gross_price, offer_price))
+    }
+
+    fn used_vehicle(mileage: Option<u32>) -> Vehicle {
+        let mut value = vehicle_json(30000.0, None);
+        value["ordering"]["orderData"]["usageState"] = json!("USED");
+        value["vehicleSpecification"]["mileage"] = json!(mileage);
+        from_json(value)
+    }
+
+    fn model_vehicle(model_range: &str, price: f32) -> Vehicle {
+        let mut value = vehicle_json(price, None);
+        value["vehicleSpecification"]["marketingModelRange"] = json!(model_range);
+        from_json(value)
+    }
+
+    fn owned_vehicle(previous_owners: Option<u32>) -> Vehicle {
+        let mut value = vehicle_json(30000.0, None);
+        value["ordering"]["orderData"]["usageState"] = json!("USED");
+        value["vehicleSpecification"]["numberOfPreviousOwners"] = json!(previous_owners);
+        from_json(value)
+    }
+
+    fn registered_vehicle(date: Option<&str>) -> Vehicle {
+        let mut value = vehicle_json(30000.0, None);
+        value["vehicleSpecification"]["firstRegistrationDate"] = json!(date);
+        from_json(value)
+    }
+
+    fn fueled_vehicle(fuel_type: Option<&str>) -> Vehicle {
+        let mut value = vehicle_json(30000.0, None);
+        value["vehicleSpecification"]["fuelType"] = json!(fuel_type);
+        from_json(value)
+    }
+
+    fn powered_vehicle(power_kw: Option<f32>) -> Vehicle {
+        let mut value = vehicle_json(30000.0, None);
+        value["vehicleSpecification"]["powerKw"] = json!(power_kw);
+        from_json(value)
+    }
+
+    fn transmission_vehicle(transmission: Option<&str>) -> Vehicle {
+        let mut value = vehicle_json(30000.0, None);
+        value["vehicleSpecification"]["transmission"] = json!(transmission);
+        from_json(value)
+    }
+
+    fn colored_vehicle(exterior_color: Option<&str>) -> Vehicle {
+        let mut value = vehicle_json(30000.0, None);
+        value["vehicleSpecification"]["exteriorColor"] = json!(exterior_color);
+        from_json(value)
+    }
+
+    fn body_vehicle(body_type: Option<&str>) -> Vehicle {
+        let mut value = vehicle_json(30000.0, None);
+        value["vehicleSpecification"]["bodyType"] = json!(body_type);
+        from_json(value)
+    }
+
+    /// Vehicle with the given equipment codes and French names.
+    fn equipped_vehicle(equipments: &[(&str, &str)]) -> Vehicle {
+        let mut value = vehicle_json(30000.0, None);
+        for (code, name) in equipments {
+            value["vehicleSpecification"]["modelAndOption"]["equipments"][*code] =
+                json!({ "name": { "fr_FR": name } });
+        }
+        from_json(value)
+    }
+
+    fn configuration(args: &[&str]) -> Configuration {
+        Configuration::new(Args::parse_from(
+            std::iter::once("test").chain(args.iter().copied()),
+        ))
+        .expect("Invalid configuration")
+    }
+
+    mod vehicle_matches_equipment {
+        use super::*;
+
+        #[test]
+        fn should_keep_vehicle_with_every_code() {
+            let configuration =
+                configuration(&["--equipment-code", "S402A", "--equipment-code", "S4NB"]);
+
+            assert!(vehicle_matches_equipment(
+                &equipped_vehicle(&[("S402A", "Toit ouvrant"), ("S4NB", "Climatisation")]),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_drop_vehicle_missing_a_code() {
+            let configuration =
+                configuration(&["--equipment-code", "S402A", "--equipment-code", "S4NB"]);
+
+            assert!(!vehicle_matches_equipment(
+                &equipped_vehicle(&[("S402A", "Toit ouvrant")]),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_match_near_miss_equipment_name_only_when_fuzzy() {
+            let vehicle = equipped_vehicle(&[("S337A", "M Sport Package")]);
+
+            assert!(!vehicle_matches_equipment(
+                &vehicle,
+                &configuration(&["--equipment-name", "Pack M Sport"])
+            ));
+            assert!(vehicle_matches_equipment(
+                &vehicle,
+                &configuration(&["--equipment-name", "Pack M Sport", "--equipment-fuzzy"])
+            ));
+        }
+
+        #[test]
+        fn should_drop_vehicle_with_excluded_equipment() {
+            let configuration = configuration(&[
+                "--equipment-name",
+                "attelage",
+                "--exclude-equipment",
+                "toit panoramique",
+            ]);
+
+            assert!(!vehicle_matches_equipment(
+                &equipped_vehicle(&[("S3AC", "Attelage"), ("S402A", "Toit panoramique")]),
+                &configuration
+            ));
+            assert!(vehicle_matches_equipment(
+                &equipped_vehicle(&[("S3AC", "Attelage")]),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_keep_vehicle_matching_equipment_regex() {
+            let configuration =
+                configuration(&["--equipment-regex", "^Pack (M Sport|Innovation)$"]);
+
+            assert!(vehicle_matches_equipment(
+                &equipped_vehicle(&[("S7A2A", "Pack Innovation")]),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_drop_vehicle_not_matching_equipment_regex() {
+            let configuration =
+                configuration(&["--equipment-regex", "^Pack (M Sport|Innovation)$"]);
+
+            assert!(!vehicle_matches_equipment(
+                &equipped_vehicle(&[("S7A2A", "Pack Innovation Plus")]),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_match_names_in_the_equipment_locale() {
+            let mut value = vehicle_json(30000.0, None);
+            value["vehicleSpecification"]["modelAndOption"]["equipments"]["S3AC"] =
+                json!({ "name": { "fr_FR": "Attelage", "de_DE": "Anhängerkupplung" } });
+            let vehicle = from_json(value);
+
+            assert!(vehicle_matches_equipment(
+                &vehicle,
+                &configuration(&["--equipment-name", "anhänger"])
+            ));
+            assert!(!vehicle_matches_equipment(
+                &vehicle,
+                &configuration(&[
+                    "--equipment-name",
+                    "anhänger",
+                    "--equipment-locale",
+                    "fr_FR"
+                ])
+            ));
+        }
+
+        #[test]
+        fn should_require_every_name_by_default() {
+            let configuration = configuration(&[
+                "--equipment-name",
+                "attelage",
+                "--equipment-name",
+                "barres de toit",
+            ]);
+
+            assert!(!vehicle_matches_equipment(
+                &equipped_vehicle(&[("S3AC", "Attelage")]),
+                &configuration
+            ));
+            assert!(vehicle_matches_equipment(
+                &equipped_vehicle(&[("S3AC", "Attelage"), ("S3AT", "Barres de toit")]),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_keep_vehicle_with_any_name() {
+            let configuration = configuration(&[
+                "--equipment-name",
+                "attelage",
+                "--equipment-name",
+                "barres de toit",
+                "--equipment-match",
+                "any",
+            ]);
+
+            assert!(vehicle_matches_equipment(
+                &equipped_vehicle(&[("S3AC", "Attelage")]),
+                &configuration
+            ));
+            assert!(!vehicle_matches_equipment(
+                &equipped_vehicle(&[("S402A", "Toit ouvrant")]),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_combine_codes_and_names() {
+            let configuration =
+                configuration(&["--equipment-code", "S402A", "--equipment-name", "attelage"]);
+
+            assert!(!vehicle_matches_equipment(
+                &equipped_vehicle(&[("S402A", "Toit ouvrant")]),
+                &configuration
+            ));
+            assert!(vehicle_matches_equipment(
+                &equipped_vehicle(&[("S402A", "Toit ouvrant"), ("S3AC", "Attelage")]),
+                &configuration
+            ));
+        }
+    }
+
+    mod vehicle_matches_price {
+        use super::*;
+
+        fn priceless_vehicle() -> Vehicle {
+            let mut value = vehicle_json(0.0, None);
+            value["price"] = serde_json::json!({});
+            from_json(value)
+        }
+
+        #[test]
+        fn should_drop_vehicle_without_price_when_range_is_configured() {
+            assert!(!vehicle_matches_price(
+                &priceless_vehicle(),
+                &configuration(&["--min-price", "30000"])
+            ));
+            assert!(!vehicle_matches_price(
+                &priceless_vehicle(),
+                &configuration(&["--max-price", "30000"])
+            ));
+        }
+
+        #[test]
+        fn should_keep_vehicle_without_price_without_range() {
+            assert!(vehicle_matches_price(
+                &priceless_vehicle(),
+                &configuration(&[])
+            ));
+        }
+
+        #[test]
+        fn should_drop_vehicle_below_min_price() {
+            let configuration = configuration(&["--min-price", "30000"]);
+
+            assert!(!vehicle_matches_price(
+                &vehicle(40000.0, Some(29999.0)),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_drop_vehicle_above_max_price() {
+            let configuration = configuration(&["--max-price", "30000"]);
+
+            assert!(!vehicle_matches_price(
+                &vehicle(40000.0, Some(30001.0)),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_keep_vehicle_inside_range() {
+            let configuration = configuration(&["--min-price", "30000", "--max-price", "40000"]);
+
+            assert!(vehicle_matches_price(
+                &vehicle(45000.0, Some(30000.0)),
+                &configuration
+            ));
+            assert!(vehicle_matches_price(
+                &vehicle(45000.0, Some(40000.0)),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_use_catalog_price_when_no_offer_price() {
+            let configuration = configuration(&["--min-price", "30000", "--max-price", "40000"]);
+
+            assert!(vehicle_matches_price(
+                &vehicle(35000.0, None),
+                &configuration
+            ));
+            assert!(!vehicle_matches_price(
+                &vehicle(45000.0, None),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_keep_every_vehicle_without_bounds() {
+            let configuration = configuration(&[]);
+
+            assert!(vehicle_matches_price(
+                &vehicle(45000.0, Some(1.0)),
+                &configuration
+            ));
+        }
+    }
+
+    mod vehicle_matches_discount {
+        use super::*;
+
+        #[test]
+        fn should_keep_vehicle_with_discount_equal_to_threshold() {
+            let configuration = configuration(&["--min-discount", "25"]);
+
+            assert!(vehicle_matches_discount(
+                &vehicle(100.0, Some(75.0)),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_keep_vehicle_with_discount_above_threshold() {
+            let configuration = configuration(&["--min-discount", "25"]);
+
+            assert!(vehicle_matches_discount(
+                &vehicle(100.0, Some(50.0)),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_drop_vehicle_with_discount_below_threshold() {
+            let configuration = configuration(&["--min-discount", "25"]);
+
+            assert!(!vehicle_matches_discount(
+                &vehicle(100.0, Some(80.0)),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_drop_vehicle_without_discount() {
+            let configuration = configuration(&["--min-discount", "25"]);
+
+            assert!(!vehicle_matches_discount(
+                &vehicle(100.0, None),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_keep_vehicle_without_discount_when_no_threshold() {
+            let configuration = configuration(&[]);
+
+            assert!(vehicle_matches_discount(
+                &vehicle(100.0, None),
+                &configuration
+            ));
+        }
+    }
+
+    mod limit_per_model {
+        use super::*;
+
+        #[test]
+        fn should_cap_each_model_and_keep_the_order() {
+            let found_vehicles: HashMap<uuid::Uuid, Vehicle> = [
+                model_vehicle("iX1_U11E", 40000.0),
+                model_vehicle("iX1_U11E", 41000.0),
+                model_vehicle("iX1_U11E", 42000.0),
+                model_vehicle("i4_G26E", 50000.0),
+                model_vehicle("i4_G26E", 45000.0),
+                model_vehicle("iX2_U10E", 60000.0),
+            ]
+            .into_iter()
+            .map(|vehicle| (vehicle.vss_id, vehicle))
+            .collect();
+
+            let vehicles = filter_and_sort_vehicles(
+                &found_vehicles,
+                &configuration(&["--limit-per-model", "2"]),
+            );
+
+            assert_eq!(
+                vehicles
+                    .iter()
+                    .filter_map(|vehicle| Some((vehicle.get_model_range()?, vehicle.get_price()?)))
+                    .collect::<Vec<_>>(),
+                vec![
+                    ("iX1_U11E", 40000.0),
+                    ("iX1_U11E", 41000.0),
+                    ("i4_G26E", 45000.0),
+                    ("i4_G26E", 50000.0),
+                    ("iX2_U10E", 60000.0),
+                ]
+            );
+        }
+    }
+
+    mod discounted_only {
+        use super::*;
+
+        #[test]
+        fn should_keep_only_vehicles_with_a_positive_discount() {
+            let discounted = vehicle(50000.0, Some(45000.0));
+            let found_vehicles = HashMap::from([
+                (discounted.vss_id, discounted.clone()),
+                {
+                    let vehicle = vehicle(50000.0, Some(50000.0));
+                    (vehicle.vss_id, vehicle)
+                },
+                {
+                    let vehicle = vehicle(50000.0, None);
+                    (vehicle.vss_id, vehicle)
+                },
+            ]);
+
+            let vehicles =
+                filter_and_sort_vehicles(&found_vehicles, &configuration(&["--discounted-only"]));
+
+            assert_eq!(
+                vehicles.iter().map(|v| v.vss_id).collect::<Vec<_>>(),
+                vec![discounted.vss_id]
+            );
+        }
+    }
+
+    mod vehicle_matches_score {
+        use super::*;
+
+        fn scored_vehicle(score: Option<f32>) -> Vehicle {
+            let mut vehicle = vehicle(30000.0, None);
+            vehicle.set_score(score);
+            vehicle
+        }
+
+        #[test]
+        fn should_keep_vehicle_with_score_above_minimum() {
+            let configuration = configuration(&["--min-score", "0.5"]);
+
+            assert!(vehicle_matches_score(
+                &scored_vehicle(Some(0.5)),
+                &configuration
+            ));
+            assert!(vehicle_matches_score(
+                &scored_vehicle(Some(0.9)),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_drop_vehicle_with_score_below_minimum() {
+            let configuration = configuration(&["--min-score", "0.5"]);
+
+            assert!(!vehicle_matches_score(
+                &scored_vehicle(Some(0.2)),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_drop_vehicle_without_score() {
+            let configuration = configuration(&["--min-score", "0.5"]);
+
+            assert!(!vehicle_matches_score(
+                &scored_vehicle(None),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_keep_every_vehicle_without_minimum() {
+            assert!(vehicle_matches_score(
+                &scored_vehicle(None),
+                &configuration(&[])
+            ));
+        }
+    }
+
+    mod vehicle_matches_savings {
+        use super::*;
+
+        #[test]
+        fn should_keep_vehicle_with_savings_equal_to_threshold() {
+            let configuration = configuration(&["--min-savings", "5000"]);
+
+            assert!(vehicle_matches_savings(
+                &vehicle(50000.0, Some(45000.0)),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_drop_vehicle_with_savings_below_threshold() {
+            let configuration = configuration(&["--min-savings", "5000"]);
+
+            assert!(!vehicle_matches_savings(
+                &vehicle(50000.0, Some(45001.0)),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_drop_vehicle_without_offer_price() {
+            let configuration = configuration(&["--min-savings", "5000"]);
+
+            assert!(!vehicle_matches_savings(
+                &vehicle(50000.0, None),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_keep_vehicle_without_offer_price_when_no_threshold() {
+            let configuration = configuration(&[]);
+
+            assert!(vehicle_matches_savings(
+                &vehicle(50000.0, None),
+                &configuration
+            ));
+        }
+    }
+
+    mod vehicle_matches_vss_id {
+        use super::*;
+
+        #[test]
+        fn should_remove_excluded_vehicles() {
+            let (dismissed, kept) = (vehicle(30000.0, None), vehicle(35000.0, None));
+            let found_vehicles: HashMap<uuid::Uuid, Vehicle> = [dismissed, kept]
+                .into_iter()
+                .map(|vehicle| (vehicle.vss_id, vehicle))
+                .collect();
+            let dismissed_id = found_vehicles
+                .values()
+                .find(|vehicle| vehicle.get_price() == Some(30000.0))
+                .map(|vehicle| vehicle.vss_id.to_string())
+                .expect("Missing dismissed vehicle");
+
+            let vehicles = filter_and_sort_vehicles(
+                &found_vehicles,
+                &configuration(&["--exclude-vss-id", &dismissed_id]),
+            );
+
+            assert_eq!(
+                vehicles
+                    .iter()
+                    .filter_map(|vehicle| vehicle.get_price())
+                    .collect::<Vec<_>>(),
+                vec![35000.0]
+            );
+        }
+
+        #[test]
+        fn should_ignore_absent_excluded_vss_id() {
+            let vehicle = vehicle(30000.0, None);
+            let configuration =
+                configuration(&["--exclude-vss-id", "0b4f0b5e-6ad7-4f6c-9d1d-6a8a2c8b7f01"]);
+
+            assert!(vehicle_matches_vss_id(&vehicle, &configuration));
+        }
+    }
+
+    mod vehicle_matches_owners {
+        use super::*;
+
+        #[test]
+        fn should_keep_used_vehicle_with_max_owners() {
+            let configuration = configuration(&["--max-owners", "2"]);
+
+            assert!(vehicle_matches_owners(
+                &owned_vehicle(Some(2)),
+                &configuration
+            ));
+            assert!(vehicle_matches_owners(
+                &owned_vehicle(Some(0)),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_drop_used_vehicle_above_max_owners() {
+            let configuration = configuration(&["--max-owners", "2"]);
+
+            assert!(!vehicle_matches_owners(
+                &owned_vehicle(Some(3)),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_drop_used_vehicle_without_owners() {
+            let configuration = configuration(&["--max-owners", "2"]);
+
+            assert!(!vehicle_matches_owners(
+                &owned_vehicle(None),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_keep_new_vehicle() {
+            let configuration = configuration(&["--max-owners", "0"]);
+
+            assert!(vehicle_matches_owners(
+                &vehicle(30000.0, None),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_keep_every_vehicle_without_max_owners() {
+            let configuration = configuration(&[]);
+
+            assert!(vehicle_matches_owners(&owned_vehicle(None), &configuration));
+            assert!(vehicle_matches_owners(
+                &owned_vehicle(Some(5)),
+                &configuration
+            ));
+        }
+    }
+
+    mod vehicle_matches_mileage {
+        use super::*;
+
+        #[test]
+        fn should_keep_used_vehicle_below_max_mileage() {
+            let configuration = configuration(&["--max-mileage", "50000"]);
+
+            assert!(vehicle_matches_mileage(
+                &used_vehicle(Some(50000)),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_drop_used_vehicle_above_max_mileage() {
+            let configuration = configuration(&["--max-mileage", "50000"]);
+
+            assert!(!vehicle_matches_mileage(
+                &used_vehicle(Some(50001)),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_keep_new_vehicle_without_mileage() {
+            let configuration = configuration(&["--max-mileage", "50000"]);
+
+            assert!(vehicle_matches_mileage(
+                &vehicle(30000.0, None),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_drop_used_vehicle_without_mileage() {
+            let configuration = configuration(&["--max-mileage", "50000"]);
+
+            assert!(!vehicle_matches_mileage(
+                &used_vehicle(None),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_keep_every_vehicle_without_max_mileage() {
+            let configuration = configuration(&[]);
+
+            assert!(vehicle_matches_mileage(
+                &used_vehicle(Some(250000)),
+                &configuration
+            ));
+        }
+    }
+
+    mod vehicle_matches_registration_date {
+        use super::*;
+
+        #[test]
+        fn should_keep_vehicle_registered_on_the_date() {
+            let configuration = configuration(&["--registered-after", "2023-06-15"]);
+
+            assert!(vehicle_matches_registration_date(
+                &registered_vehicle(Some("2023-06-15")),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_drop_vehicle_registered_the_day_before() {
+            let configuration = configuration(&["--registered-after", "2023-06-15"]);
+
+            assert!(!vehicle_matches_registration_date(
+                &registered_vehicle(Some("2023-06-14")),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_drop_vehicle_without_date() {
+            let configuration = configuration(&["--registered-after", "2023-06-15"]);
+
+            assert!(!vehicle_matches_registration_date(
+                &registered_vehicle(None),
+                &configuration
+            ));
+            assert!(!vehicle_matches_registration_date(
+                &registered_vehicle(Some("unknown")),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_keep_vehicle_without_date_when_no_filter() {
+            let configuration = configuration(&[]);
+
+            assert!(vehicle_matches_registration_date(
+                &registered_vehicle(None),
+                &configuration
+            ));
+        }
+    }
+
+    mod vehicle_matches_fuel {
+        use super::*;
+
+        #[test]
+        fn should_keep_vehicle_with_matching_fuel() {
+            let configuration = configuration(&["--fuel", "electric"]);
+
+            assert!(vehicle_matches_fuel(
+                &fueled_vehicle(Some("ELECTRIC")),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_drop_vehicle_with_other_fuel() {
+            let configuration = configuration(&["--fuel", "electric"]);
+
+            assert!(!vehicle_matches_fuel(
+                &fueled_vehicle(Some("DIESEL")),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_drop_vehicle_without_fuel() {
+            let configuration = configuration(&["--fuel", "electric"]);
+
+            assert!(!vehicle_matches_fuel(&fueled_vehicle(None), &configuration));
+        }
+
+        #[test]
+        fn should_keep_vehicle_without_fuel_when_no_filter() {
+            let configuration = configuration(&[]);
+
+            assert!(vehicle_matches_fuel(&fueled_vehicle(None), &configuration));
+        }
+    }
+
+    mod vehicle_matches_power {
+        use super::*;
+
+        #[test]
+        fn should_keep_vehicle_with_enough_power() {
+            let configuration = configuration(&["--min-power", "204"]);
+
+            assert!(vehicle_matches_power(
+                &powered_vehicle(Some(150.0)),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_drop_vehicle_below_min_power() {
+            let configuration = configuration(&["--min-power", "205"]);
+
+            assert!(!vehicle_matches_power(
+                &powered_vehicle(Some(150.0)),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_drop_vehicle_without_power() {
+            let configuration = configuration(&["--min-power", "100"]);
+
+            assert!(!vehicle_matches_power(
+                &powered_vehicle(None),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_keep_vehicle_without_power_when_no_filter() {
+            let configuration = configuration(&[]);
+
+            assert!(vehicle_matches_power(
+                &powered_vehicle(None),
+                &configuration
+            ));
+        }
+    }
+
+    mod vehicle_matches_transmission {
+        use super::*;
+
+        #[test]
+        fn should_keep_vehicle_with_matching_transmission() {
+            let configuration = configuration(&["--transmission", "auto"]);
+
+            assert!(vehicle_matches_transmission(
+                &transmission_vehicle(Some("STEPTRONIC")),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_drop_vehicle_with_other_transmission() {
+            let configuration = configuration(&["--transmission", "auto"]);
+
+            assert!(!vehicle_matches_transmission(
+                &transmission_vehicle(Some("MANUAL")),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_drop_vehicle_with_unknown_or_missing_transmission() {
+            let configuration = configuration(&["--transmission", "manual"]);
+
+            assert!(!vehicle_matches_transmission(
+                &transmission_vehicle(Some("CVT")),
+                &configuration
+            ));
+            assert!(!vehicle_matches_transmission(
+                &transmission_vehicle(None),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_keep_vehicle_without_transmission_when_no_filter() {
+            let configuration = configuration(&[]);
+
+            assert!(vehicle_matches_transmission(
+                &transmission_vehicle(None),
+                &configuration
+            ));
+        }
+    }
+
+    mod vehicle_matches_body_type {
+        use super::*;
+
+        #[test]
+        fn should_keep_vehicle_with_matching_body_type() {
+            let configuration = configuration(&["--body-type", "SUV"]);
+
+            assert!(vehicle_matches_body_type(
+                &body_vehicle(Some("SAV")),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_drop_vehicle_with_other_body_type() {
+            let configuration = configuration(&["--body-type", "suv"]);
+
+            assert!(!vehicle_matches_body_type(
+                &body_vehicle(Some("TOURING")),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_drop_vehicle_without_body_type() {
+            let configuration = configuration(&["--body-type", "suv"]);
+
+            assert!(!vehicle_matches_body_type(
+                &body_vehicle(None),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_keep_vehicle_without_body_type_when_no_filter() {
+            assert!(vehicle_matches_body_type(
+                &body_vehicle(None),
+                &configuration(&[])
+            ));
+        }
+
+        #[test]
+        fn should_print_normalized_body_type() {
+            let vehicle = body_vehicle(Some("LIMOUSINE"));
+            let configuration = configuration(&["--fields", "body"]);
+
+            assert_eq!(
+                text_cell(OutputField::Body, &vehicle, &configuration),
+                "Sedan"
+            );
+            assert_eq!(
+                format_csv_row(&vehicle, configuration.fields(), &configuration),
+                "Sedan"
+            );
+        }
+    }
+
+    mod vehicle_matches_color {
+        use super::*;
+
+        #[test]
+        fn should_keep_vehicle_with_matching_color() {
+            let configuration = configuration(&["--color", "blanc"]);
+
+            assert!(vehicle_matches_color(
+                &colored_vehicle(Some("Blanc Alpin")),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_drop_vehicle_with_other_color() {
+            let configuration = configuration(&["--color", "blanc"]);
+
+            assert!(!vehicle_matches_color(
+                &colored_vehicle(Some("Noir Saphir")),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_drop_vehicle_without_color() {
+            let configuration = configuration(&["--color", "blanc"]);
+
+            assert!(!vehicle_matches_color(
+                &colored_vehicle(None),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_keep_vehicle_without_color_when_no_filter() {
+            let configuration = configuration(&[]);
+
+            assert!(vehicle_matches_color(
+                &colored_vehicle(None),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_print_interior_color_column() {
+            let mut value = vehicle_json(30000.0, None);
+            value["vehicleSpecification"]["interiorColor"] = json!("Veganza Noir");
+            let vehicle = from_json(value);
+            let configuration = configuration(&["--fields", "color,interior"]);
+
+            assert_eq!(
+                text_cell(OutputField::Interior, &vehicle, &configuration),
+                "Veganza Noir"
+            );
+            assert_eq!(
+                format_csv_row(&vehicle, configuration.fields(), &configuration),
+                ",Veganza Noir"
+            );
+        }
+    }
+
+    mod vehicle_matches_dealer {
+        use super::*;
+
+        fn dealer_vehicle(name: Option<&str>) -> Vehicle {
+            let mut value = vehicle_json(30000.0, None);
+            if let Some(name) = name {
+                value["dealer"] = json!({ "name": name, "city": "Lyon" });
+            }
+            from_json(value)
+        }
+
+        #[test]
+        fn should_keep_vehicle_with_matching_dealer() {
+            let configuration = configuration(&["--dealer", "lyon"]);
+
+            assert!(vehicle_matches_dealer(
+                &dealer_vehicle(Some("BMW Lyon Nord")),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_drop_vehicle_with_other_dealer() {
+            let configuration = configuration(&["--dealer", "lyon"]);
+
+            assert!(!vehicle_matches_dealer(
+                &dealer_vehicle(Some("BMW Paris Est")),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_drop_vehicle_without_dealer() {
+            let configuration = configuration(&["--dealer", "lyon"]);
+
+            assert!(!vehicle_matches_dealer(
+                &dealer_vehicle(None),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_keep_vehicle_without_dealer_when_no_filter() {
+            assert!(vehicle_matches_dealer(
+                &dealer_vehicle(None),
+                &configuration(&[])
+            ));
+        }
+
+        #[test]
+        fn should_print_dealer_column() {
+            let vehicle = dealer_vehicle(Some("BMW Lyon Nord"));
+            let configuration = configuration(&["--fields", "dealer"]);
+
+            assert_eq!(
+                text_cell(OutputField::Dealer, &vehicle, &configuration),
+                "BMW Lyon Nord (Lyon)"
+            );
+            assert_eq!(
+                format_csv_row(&vehicle, configuration.fields(), &configuration),
+                "BMW Lyon Nord (Lyon)"
+            );
+        }
+    }
+
+    mod vehicle_matches_availability {
+        use super::*;
+
+        fn vehicle_with_availability(availability: &str) -> Vehicle {
+            let mut value = vehicle_json(30000.0, None);
+            value["ordering"]["orderData"]["availability"] = json!(availability);
+            from_json(value)
+        }
+
+        #[test]
+        fn should_keep_available_vehicle() {
+            let configuration = configuration(&["--in-stock-only"]);
+
+            assert!(vehicle_matches_availability(
+                &vehicle_with_availability("AVAILABLE"),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_drop_reserved_vehicle() {
+            let configuration = configuration(&["--in-stock-only"]);
+
+            assert!(!vehicle_matches_availability(
+                &vehicle_with_availability("RESERVED"),
+                &configuration
+            ));
+        }
+
+        #[test]
+        fn should_keep_reserved_vehicle_without_filter() {
+            assert!(vehicle_matches_availability(
+                &vehicle_with_availability("RESERVED"),
+                &configuration(&[])
+            ));
+        }
+
+        #[test]
+        fn should_print_status_column() {
+            let vehicle = vehicle_with_availability("RESERVED");
+            let configuration = configuration(&["--fields", "status"]);
+
+            assert_eq!(
+                text_cell(OutputField::Status, &vehicle, &configuration),
+                "Reserved"
+            );
+            assert_eq!(
+                format_csv_row(&vehicle, configuration.fields(), &configuration),
+                "reserved"
+            );
+        }
+    }
+
+    mod model_column {
+        use super::*;
+
+        fn model_vehicle(model_range: Option<&str>) -> Vehicle {
+            let mut value = vehicle_json(30000.0, None);
+            value["vehicleSpecification"]["marketingModelRange"] = json!(model_range);
+            from_json(value)
+        }
+
+        #[test]
+        fn should_print_model_name() {
+            let vehicle = model_vehicle(Some("iX1_U11E"));
+            let configuration = configuration(&["--fields", "model"]);
+
+            assert_eq!(
+                text_cell(OutputField::Model, &vehicle, &configuration),
+                "BMW iX1"
+            );
+            assert_eq!(
+                format_csv_row(&vehicle, configuration.fields(), &configuration),
+                "BMW iX1"
+            );
+        }
+
+        #[test]
+        fn should_print_model_range_when_not_bundled() {
+            let vehicle = model_vehicle(Some("M2_G87"));
+            let configuration = configuration(&["--fields", "model"]);
+
+            assert_eq!(
+                text_cell(OutputField::Model, &vehicle, &configuration),
+                "M2_G87"
+            );
+        }
+
+        #[test]
+        fn should_print_dash_without_model_range() {
+            let vehicle = model_vehicle(None);
+
+            assert_eq!(
+                text_cell(OutputField::Model, &vehicle, &configuration(&[])),
+                "-"
+            );
+        }
+    }
+
+    mod filter_and_sort_vehicles {
+        use super::*;
+
+        fn ten_vehicles() -> HashMap<uuid::Uuid, Vehicle> {
+            (1..=10)
+                .map(|index| vehicle(50000.0, Some(50000.0 - index as f32 * 1000.0)))
+                .map(|vehicle| (vehicle.vss_id, vehicle))
+                .collect()
+        }
+
+        #[test]
+        fn should_only_keep_top_vehicles_by_price() {
+            let vehicles = ten_vehicles();
+
+            let prices: Vec<f32> =
+                filter_and_sort_vehicles(&vehicles, &configuration(&["--top", "3"]))
+                    .iter()
+                    .filter_map(|vehicle| vehicle.get_price())
+                    .collect();
+
+            assert_eq!(prices, vec![40000.0, 41000.0, 42000.0]);
+        }
+
+        #[test]
+        fn should_only_keep_top_vehicles_in_sort_order() {
+            let vehicles = ten_vehicles();
+            let configuration = configuration(&["--top", "3", "--sort-order", "desc"]);
+
+            let prices: Vec<f32> = filter_and_sort_vehicles(&vehicles, &configuration)
+                .iter()
+                .filter_map(|vehicle| vehicle.get_price())
+                .collect();
+
+            assert_eq!(prices, vec![49000.0, 48000.0, 47000.0]);
+        }
+
+        #[test]
+        fn should_keep_every_vehicle_when_fewer_than_top() {
+            let vehicles = ten_vehicles();
+
+            assert_eq!(
+                filter_and_sort_vehicles(&vehicles, &configuration(&["--top", "20"])).len(),
+                10
+            );
+        }
+
+        #[test]
+        fn should_only_keep_vehicles_in_price_range() {
+            let configuration = configuration(&["--min-price", "30000", "--max-price", "40000"]);
+            let vehicles: HashMap<uuid::Uuid, Vehicle> = [
+                vehicle(50000.0, Some(45000.0)),
+                vehicle(38000.0, None),
+                vehicle(50000.0, Some(32000.0)),
+                vehicle(25000.0, None),
+            ]
+            .into_iter()
+            .map(|vehicle| (vehicle.vss_id, vehicle))
+            .collect();
+
+            let prices: Vec<f32> = filter_and_sort_vehicles(&vehicles, &configuration)
+                .iter()
+                .filter_map(|vehicle| vehicle.get_price())
+                .collect();
+
+            assert_eq!(prices, vec![32000.0, 38000.0]);
+        }
+
+        #[test]
+        fn should_sort_by_descending_price() {
+            let configuration = configuration(&["--sort-order", "desc"]);
+            let vehicles: HashMap<uuid::Uuid, Vehicle> = [
+                vehicle(38000.0, None),
+                vehicle(50000.0, Some(45000.0)),
+                vehicle(25000.0, None),
+            ]
+            .into_iter()
+            .map(|vehicle| (vehicle.vss_id, vehicle))
+            .collect();
+
+            let prices: Vec<f32> = filter_and_sort_vehicles(&vehicles, &configuration)
+                .iter()
+                .filter_map(|vehicle| vehicle.get_price())
+                .collect();
+
+            assert_eq!(prices, vec![45000.0, 38000.0, 25000.0]);
+        }
+
+        #[test]
+        fn should_order_equally_priced_vehicles_by_vss_id() {
+            let vehicles: HashMap<uuid::Uuid, Vehicle> = (0..5)
+                .map(|_| vehicle(30000.0, None))
+                .map(|vehicle| (vehicle.vss_id, vehicle))
+                .collect();
+            let mut expected: Vec<uuid::Uuid> = vehicles.keys().copied().collect();
+            expected.sort();
+
+            let cases: [&[&str]; 3] = [&[], &["--sort-order", "desc"], &["--sort-by", "discount"]];
+            for args in cases {
+                let vss_ids: Vec<uuid::Uuid> =
+                    filter_and_sort_vehicles(&vehicles, &configuration(args))
+                        .iter()
+                        .map(|vehicle| vehicle.vss_id)
+                        .collect();
+
+                assert_eq!(vss_ids, expected);
+            }
+        }
+
+        #[test]
+        fn should_reorder_vehicles_when_sorted_by_discount() {
+            let small_discount = vehicle(30000.0, Some(29000.0));
+            let big_discount = vehicle(50000.0, Some(40000.0));
+            let vehicles: HashMap<uuid::Uuid, Vehicle> =
+                [small_discount.clone(), big_discount.clone()]
+                    .into_iter()
+                    .map(|vehicle| (vehicle.vss_id, vehicle))
+                    .collect();
+
+            let by_price: Vec<uuid::Uuid> =
+                filter_and_sort_vehicles(&vehicles, &configuration(&[]))
+                    .iter()
+                    .map(|vehicle| vehicle.vss_id)
+                    .collect();
+            let by_discount: Vec<uuid::Uuid> =
+                filter_and_sort_vehicles(&vehicles, &configuration(&["--sort-by", "discount"]))
+                    .iter()
+                    .map(|vehicle| vehicle.vss_id)
+                    .collect();
+            let by_ascending_discount: Vec<uuid::Uuid> = filter_and_sort_vehicles(
+                &vehicles,
+                &configuration(&["--sort-by", "discount", "--sort-order", "asc"]),
+            )
+            .iter()
+            .map(|vehicle| vehicle.vss_id)
+            .collect();
+
+            assert_eq!(by_price, vec![small_discount.vss_id, big_discount.vss_id]);
+            assert_eq!(
+                by_discount,
+                vec![big_discount.vss_id, small_discount.vss_id]
+            );
+            assert_eq!(
+                by_ascending_discount,
+                vec![small_discount.vss_id, big_discount.vss_id]
+            );
+        }
+
+        #[test]
+        fn should_break_price_ties_with_secondary_key() {
+            let small_discount = vehicle(31000.0, Some(30000.0));
+            let big_discount = vehicle(40000.0, Some(30000.0));
+            let not_discounted = vehicle(30000.0, None);
+            let cheaper = vehicle(25000.0, None);
+            let vehicles: HashMap<uuid::Uuid, Vehicle> = [
+                small_discount.clone(),
+                big_discount.clone(),
+                not_discounted.clone(),
+                cheaper.clone(),
+            ]
+            .into_iter()
+            .map(|vehicle| (vehicle.vss_id, vehicle))
+            .collect();
+
+            let vss_ids: Vec<uuid::Uuid> = filter_and_sort_vehicles(
+                &vehicles,
+                &configuration(&["--sort", "price,discount", "--sort-order", "desc"]),
+            )
+            .iter()
+            .map(|vehicle| vehicle.vss_id)
+            .collect();
+
+            assert_eq!(
+                vss_ids,
+                vec![
+                    big_discount.vss_id,
+                    small_discount.vss_id,
+                    not_discounted.vss_id,
+                    cheaper.vss_id
+                ]
+            );
+        }
+    }
+
+    mod compare_by_key {
+        use super::*;
+
+        #[test]
+        fn should_compare_on_the_given_key_only() {
+            let (cheap, discounted) = (vehicle(20000.0, None), vehicle(40000.0, Some(30000.0)));
+
+            assert_eq!(
+                compare_by_key(SortKey::Price, &cheap, &discounted, SortOrder::Asc),
+                Ordering::Less
+            );
+            assert_eq!(
+                compare_by_key(SortKey::Discount, &cheap, &discounted, SortOrder::Asc),
+                Ordering::Greater
+            );
+            assert_eq!(
+                compare_by_key(SortKey::Mileage, &cheap, &discounted, SortOrder::Asc),
+                Ordering::Equal
+            );
+        }
+
+        #[test]
+        fn should_put_vehicle_without_price_last_in_both_orders() {
+            let priced = vehicle(20000.0, None);
+            let mut value = vehicle_json(0.0, None);
+            value["price"] = serde_json::json!({});
+            let priceless = from_json(value);
+
+            for sort_order in [SortOrder::Asc, SortOrder::Desc] {
+                assert_eq!(
+                    compare_by_key(SortKey::Price, &priced, &priceless, sort_order),
+                    Ordering::Less
+                );
+            }
+        }
+    }
+
+    mod print_header {
+        use super::*;
+
+        fn render(configuration: &Configuration) -> String {
+            let mut output = Vec::new();
+            print_header(configuration, &mut output).expect("Failed to print header");
+            String::from_utf8(output).expect("Invalid UTF-8 output")
+        }
+
+        #[test]
+        fn should_print_search_header() {
+            let output = render(&configuration(&["--text", "--limit", "5"]));
+
+            assert!(output.starts_with("Limiting results to 5\nSearching for new vehicles"));
+        }
+
+        #[test]
+        fn should_print_nothing_when_quiet() {
+            assert_eq!(render(&configuration(&["--text", "--quiet"])), "");
+        }
+
+        #[test]
+        fn should_print_nothing_for_machine_readable_output() {
+            assert_eq!(render(&configuration(&["--output", "json"])), "");
+            assert_eq!(render(&configuration(&["--output", "ndjson"])), "");
+            assert_eq!(render(&configuration(&["--output", "csv"])), "");
+        }
+    }
+
+    mod run {
+        use super::*;
+        use crate::source::{BmwApi, FakeSource};
+
+        /// Client routed through a closed local port, so no request reaches the network.
+        fn offline_client() -> Client {
+            Client::builder()
+                .proxy(reqwest::Proxy::all("http://127.0.0.1:1").expect("Invalid proxy"))
+                .build()
+                .expect("Failed to build client")
+        }
+
+        #[tokio::test]
+        async fn should_return_error_instead_of_panicking() {
+            let path = std::env::temp_dir()
+                .join(format!("bmw_finder_{}", uuid::Uuid::new_v4()))
+                .join("results.csv");
+            let configuration = configuration(&[
+                "--retries",
+                "0",
+                "--output",
+                "csv",
+                "--output-file",
+                path.to_str().expect("Invalid temp path"),
+            ]);
+
+            let client = offline_client();
+
+            assert!(
+                run(&BmwApi::new(client.clone()), &client, &configuration)
+                    .await
+                    .is_err()
+            );
+        }
+
+        #[tokio::test]
+        async fn should_filter_and_sort_vehicles_from_source() {
+            let path =
+                std::env::temp_dir().join(format!("bmw_finder_{}.csv", uuid::Uuid::new_v4()));
+            let configuration = configuration(&[
+                "--min-price",
+                "30000",
+                "--sort-order",
+                "desc",
+                "--output",
+                "csv",
+                "--output-file",
+                path.to_str().expect("Invalid temp path"),
+            ]);
+            let (cheap, middle, expensive) = (
+                vehicle(20000.0, None),
+                vehicle(35000.0, None),
+                vehicle(50000.0, Some(45000.0)),
+            );
+            let source = FakeSource::new(vec![cheap.clone(), middle.clone(), expensive.clone()]);
+
+            run(&source, &offline_client(), &configuration)
+                .await
+                .expect("Run should succeed");
+
+            let content = std::fs::read_to_string(&path).expect("Failed to read output file");
+            std::fs::remove_file(&path).expect("Failed to remove output file");
+            assert_eq!(
+                content,
+                format!(
+                    "vss_id,price,discount,savings,link\n{}\n{}\n",
+                    format_csv_row(&expensive, &OutputField::DEFAULT, &configuration),
+                    format_csv_row(&middle, &OutputField::DEFAULT, &configuration)
+                )
+            );
+        }
+
+        #[tokio::test]
+        async fn should_write_every_format_to_output_dir() {
+            let dir = std::env::temp_dir()
+                .join(format!("bmw_finder_{}", uuid::Uuid::new_v4()))
+                .join("results");
+            let configuration = configuration(&[
+                "--min-price",
+                "30000",
+                "--output-dir",
+                dir.to_str().expect("Invalid temp path"),
+            ]);
+            let (cheap, middle, expensive) = (
+                vehicle(20000.0, None),
+                vehicle(35000.0, None),
+                vehicle(50000.0, Some(45000.0)),
+            );
+            let source = FakeSource::new(vec![cheap.clone(), middle.clone(), expensive.clone()]);
+
+            run(&source, &offline_client(), &configuration)
+                .await
+                .expect("Run should succeed");
+
+            let read = |name: &str| {
+                std::fs::read_to_string(dir.join(name)).expect("Failed to read output file")
+            };
+            let (text, json, csv) = (
+                read("results.txt"),
+                read("results.json"),
+                read("results.csv"),
+            );
+            std::fs::remove_dir_all(dir.parent().expect("Missing parent"))
+                .expect("Failed to remove output directory");
+
+            let expected = [middle.vss_id.to_string(), expensive.vss_id.to_string()];
+            let text_position = |vss_id: &str| text.find(vss_id).expect("Missing vehicle in text");
+            assert!(text_position(&expected[0]) < text_position(&expected[1]));
+            assert!(!text.contains(&cheap.vss_id.to_string()));
+            assert!(text.contains("\n2 vehicles | "));
+            let reports: serde_json::Value = serde_json::from_str(&json).expect("Invalid JSON");
+            let json_vss_ids: Vec<&str> = reports
+                .as_array()
+                .expect("Expected an array")
+                .iter()
+                .map(|report| report["vehicle"]["vssId"].as_str().expect("Missing VSS ID"))
+                .collect();
+            assert_eq!(json_vss_ids, expected);
+            let csv_vss_ids: Vec<&str> = csv
+                .lines()
+                .skip(1)
+                .map(|row| row.split(',').next().expect("Empty CSV row"))
+                .collect();
+            assert_eq!(csv_vss_ids, expected);
+        }
+    }
+
+    mod stream_output {
+        use super::*;
+        use crate::source::FakeSource;
+
+        fn source() -> FakeSource {
+            FakeSource::new(vec![
+                vehicle(20000.0, None),
+                vehicle(35000.0, None),
+                vehicle(50000.0, Some(45000.0)),
+            ])
+        }
+
+        #[tokio::test]
+        async fn should_write_one_ndjson_line_per_matching_vehicle() {
+            let mut output = Vec::new();
+            let count = stream_output(
+                &source(),
+                &configuration(&["--output", "ndjson", "--stream", "--min-price", "30000"]),
+                &mut output,
+            )
+            .await
+            .expect("Stream should succeed");
+
+            let output = String::from_utf8(output).expect("Output should be UTF-8");
+            assert_eq!(count, 2);
+            assert_eq!(output.lines().count(), 2);
+        }
+
+        #[tokio::test]
+        async fn should_write_text_header_then_rows() {
+            let mut output = Vec::new();
+            let count = stream_output(
+                &source(),
+                &configuration(&["--text", "--stream", "--fields", "price"]),
+                &mut output,
+            )
+            .await
+            .expect("Stream should succeed");
+
+            let output = String::from_utf8(output).expect("Output should be UTF-8");
+            let lines: Vec<&str> = output.lines().collect();
+            assert_eq!(count, 3);
+            assert_eq!(lines.len(), 4);
+            assert_eq!(lines[0], "Price");
+        }
+    }
+
+    mod count_vehicles {
+        use super::*;
+        use crate::source::FakeSource;
+
+        fn source() -> FakeSource {
+            FakeSource::new(vec![
+                vehicle(20000.0, None),
+                vehicle(35000.0, None),
+                vehicle(50000.0, Some(45000.0)),
+            ])
+            .with_total_count(120)
+        }
+
+        #[tokio::test]
+        async fn should_use_the_total_count_without_local_filters() {
+            let count = count_vehicles(&source(), &configuration(&["--count-only"]))
+                .await
+                .expect("Count should succeed");
+
+            assert_eq!(count, 120);
+        }
+
+        #[tokio::test]
+        async fn should_count_the_filtered_vehicles_with_local_filters() {
+            let count = count_vehicles(
+                &source(),
+                &configuration(&["--count-only", "--min-price", "30000"]),
+            )
+            .await
+            .expect("Count should succeed");
+
+            assert_eq!(count, 2);
+        }
+    }
+
+    mod write_output {
+        use super::*;
+
+        #[test]
+        fn should_write_output_to_file() {
+            let path =
+                std::env::temp_dir().join(format!("bmw_finder_{}.csv", uuid::Uuid::new_v4()));
+            let configuration = configuration(&[
+                "--output",
+                "csv",
+                "--output-file",
+                path.to_str().expect("Invalid temp path"),
+            ]);
+            let vehicle = vehicle(100.0, Some(75.0));
+
+            write_output(&configuration, &[&vehicle], None).expect("Failed to write output");
+
+            let content = std::fs::read_to_string(&path).expect("Failed to read output file");
+            std::fs::remove_file(&path).expect("Failed to remove output file");
+            assert_eq!(
+                content,
+                format!(
+                    "vss_id,price,discount,savings,link\n{}\n",
+                    format_csv_row(&vehicle, &OutputField::DEFAULT, &configuration)
+                )
+            );
+        }
+
+        #[test]
+        fn should_return_error_when_file_cannot_be_created() {
+            let path = std::env::temp_dir()
+                .join(format!("bmw_finder_{}", uuid::Uuid::new_v4()))
+                .join("results.csv");
+            let configuration = configuration(&[
+                "--output",
+                "csv",
+                "--output-file",
+                path.to_str().expect("Invalid temp path"),
+            ]);
+
+            assert!(write_output(&configuration, &[], None).is_err());
+        }
+    }
+
+    mod print_text_output {
+        use super::*;
+
+        fn render(vehicles: &[&Vehicle], previous_state: Option<&State>) -> String {
+            let mut output = Vec::new();
+            print_text_output(vehicles, &configuration(&[]), previous_state, &mut output)
+                .expect("Failed to print output");
+            String::from_utf8(output).expect("Invalid UTF-8 output")
+        }
+
+        #[test]
+        fn should_annotate_new_vehicles_and_price_drops() {
+            let (dropped, new) = (vehicle(42000.0, Some(40000.0)), vehicle(42000.0, None));
+            let previous_state: State = serde_json::from_value(
+                json!({ "prices": { dropped.vss_id.to_string(): 41000.0 } }),
+            )
+            .expect("Failed to build state");
+
+            let output = render(&[&dropped, &new], Some(&previous_state));
+
+            let lines: Vec<&str> = output.lines().collect();
+            assert!(lines[1].ends_with(" | PRICE DROP (was 41 000,00 €)"));
+            assert!(lines[2].ends_with(" | NEW"));
+        }
+
+        #[test]
+        fn should_end_with_summary() {
+            let (cheap, expensive) = (vehicle(100.0, Some(80.0)), vehicle(200.0, None));
+
+            let output = render(&[&cheap, &expensive], None);
+
+            assert_eq!(
+                output.lines().last(),
+                Some(
+                    "2 vehicles | min 80,00 € | max 200,00 € | avg 140,00 € | avg discount 20,00 %"
+                )
+            );
+        }
+
+        #[test]
+        fn should_print_the_selected_fields_in_order() {
+            let vehicle = used_vehicle(Some(12500));
+            let mut output = Vec::new();
+
+            print_text_output(
+                &[&vehicle],
+                &configuration(&["--fields", "mileage,price"]),
+                None,
+                &mut output,
+            )
+            .expect("Failed to print output");
+
+            let output = String::from_utf8(output).expect("Invalid UTF-8 output");
+            let lines: Vec<&str> = output.lines().collect();
+            assert_eq!(lines[0], "Mileage    | Price");
+            assert_eq!(lines[1], "12500 km   | 30 000,00 €");
+        }
+
+        #[test]
+        fn should_not_annotate_without_previous_state() {
+            let output = render(&[&vehicle(42000.0, None)], None);
+
+            assert!(!output.contains("NEW"));
+            assert!(!output.contains("PRICE DROP"));
+        }
+    }
+
+    mod discount_style {
+        use super::*;
+
+        #[test]
+        fn should_color_discounts_above_threshold_green() {
+            assert_eq!(discount_style(10.01), Style::new().green());
+            assert_eq!(discount_style(35.0), Style::new().green());
+        }
+
+        #[test]
+        fn should_leave_discounts_up_to_threshold_plain() {
+            assert_eq!(discount_style(HIGHLIGHT_DISCOUNT_PERCENTAGE), Style::new());
+            assert_eq!(discount_style(4.5), Style::new());
+            assert_eq!(discount_style(0.0), Style::new());
+        }
+    }
+
+    mod top_discount_vehicle {
+        use super::*;
+
+        #[test]
+        fn should_pick_the_highest_discount() {
+            let vehicles = [
+                vehicle(100.0, Some(90.0)),
+                vehicle(100.0, Some(70.0)),
+                vehicle(100.0, None),
+            ];
+            let vehicles: Vec<&Vehicle> = vehicles.iter().collect();
+
+            assert_eq!(top_discount_vehicle(&vehicles), Some(vehicles[1].vss_id));
+        }
+
+        #[test]
+        fn should_pick_none_without_discount() {
+            let vehicle = vehicle(100.0, None);
+
+            assert_eq!(top_discount_vehicle(&[&vehicle]), None);
+        }
+
+        #[test]
+        fn should_bold_the_top_discount_row_and_color_its_discount() {
+            let (top, plain) = (vehicle(100.0, Some(70.0)), vehicle(100.0, None));
+            let configuration = configuration(&["--fields", "discount,price"]);
+            let mut output = Vec::new();
+
+            print_text_output(&[&top, &plain], &configuration, None, &mut output)
+                .expect("Failed to print output");
+
+            let output = String::from_utf8(output).expect("Invalid UTF-8 output");
+            let lines: Vec<&str> = output.lines().collect();
+            assert_eq!(
+                lines[1],
+                format!(
+                    "{} | {}",
+                    "30,00 % ".style(Style::new().green().bold()),
+                    "70,00 €".style(Style::new().bold())
+                )
+            );
+            assert_eq!(lines[2], "-        | 100,00 €");
+        }
+    }
+
+    mod group_by_model {
+        use super::*;
+
+        #[test]
+        fn should_partition_vehicles_by_model_with_unknown_last() {
+            let vehicles = [
+                model_vehicle("iX1_U11E", 40000.0),
+                vehicle(41000.0, None),
+                model_vehicle("i4_G26E", 42000.0),
+                model_vehicle("iX1_U11E", 43000.0),
+            ];
+            let vehicles: Vec<&Vehicle> = vehicles.iter().collect();
+
+            let groups = group_by_model(&vehicles);
+
+            assert_eq!(
+                groups
+                    .iter()
+                    .map(|(model, group)| (
+                        *model,
+                        group
+                            .iter()
+                            .filter_map(|vehicle| vehicle.get_price())
+                            .collect()
+                    ))
+                    .collect::<Vec<(&str, Vec<f32>)>>(),
+                vec![
+                    ("i4_G26E", vec![42000.0]),
+                    ("iX1_U11E", vec![40000.0, 43000.0]),
+                    ("Unknown", vec![41000.0]),
+                ]
+            );
+        }
+
+        #[test]
+        fn should_print_a_heading_per_model() {
+            let (ix1, unknown) = (model_vehicle("iX1_U11E", 40000.0), vehicle(41000.0, None));
+            let mut output = Vec::new();
+
+            print_text_output(
+                &[&ix1, &unknown],
+                &configuration(&["--group-by", "model"]),
+                None,
+                &mut output,
+            )
+            .expect("Failed to print output");
+
+            let output = String::from_utf8(output).expect("Invalid UTF-8 output");
+            let lines: Vec<&str> = output.lines().collect();
+            assert_eq!(lines[2], "iX1_U11E (1 vehicles)");
+            assert!(lines[3].starts_with(&ix1.vss_id.to_string()));
+            assert_eq!(lines[5], "Unknown (1 vehicles)");
+            assert!(lines[6].starts_with(&unknown.vss_id.to_string()));
+        }
+    }
+
+    mod print_json_output {
+        use super::*;
+
+        fn print(args: &[&str]) -> String {
+            let vehicles = [vehicle(100.0, Some(75.0)), vehicle(200.0, None)];
+            let vehicles: Vec<&Vehicle> = vehicles.iter().collect();
+            let mut output = Vec::new();
+
+            print_json_output(&vehicles, &configuration(args), None, &mut output)
+                .expect("Failed to print output");
+
+            String::from_utf8(output).expect("Invalid UTF-8 output")
+        }
+
+        #[test]
+        fn should_pretty_print_by_default() {
+            let output = print(&["--json"]);
+
+            assert!(output.lines().count() > 2);
+            let reports: serde_json::Value =
+                serde_json::from_str(&output).expect("Output is not valid JSON");
+            assert_eq!(reports.as_array().map(Vec::len), Some(2));
+        }
+
+        #[test]
+        fn should_write_a_single_line_when_compact() {
+            let output = print(&["--json", "--json-compact"]);
+
+            assert_eq!(output.lines().count(), 1);
+            let reports: serde_json::Value =
+                serde_json::from_str(&output).expect("Output is not valid JSON");
+            assert_eq!(reports.as_array().map(Vec::len), Some(2));
+        }
+    }
+
+    mod print_summary_json_output {
+        use super::*;
+
+        #[test]
+        fn should_write_only_the_summary_object() {
+            let vehicles = [vehicle(100.0, Some(75.0)), vehicle(200.0, None)];
+            let vehicles: Vec<&Vehicle> = vehicles.iter().collect();
+            let mut output = Vec::new();
+
+            print_summary_json_output(
+                &vehicles,
+                &configuration(&["--json", "--summary-only", "--json-compact"]),
+                &mut output,
+            )
+            .expect("Failed to print output");
+
+            let output = String::from_utf8(output).expect("Invalid UTF-8 output");
+            assert_eq!(output.lines().count(), 1);
+            let summary: serde_json::Value =
+                serde_json::from_str(&output).expect("Output is not valid JSON");
+            assert_eq!(summary["count"], json!(2));
+            assert_eq!(summary["min_price"], json!(75.0));
+            assert_eq!(summary["max_price"], json!(200.0));
+            assert_eq!(summary["average_price"], json!(137.5));
+            assert_eq!(summary["average_discount"], json!(25.0));
+            assert!(summary.get("vehicles").is_none());
+        }
+    }
+
+    mod print_ndjson_output {
+        use super::*;
+
+        #[test]
+        fn should_write_one_json_object_per_line() {
+            let vehicles = [vehicle(100.0, Some(75.0)), vehicle(200.0, None)];
+            let vehicles: Vec<&Vehicle> = vehicles.iter().collect();
+            let mut output = Vec::new();
+
+            print_ndjson_output(&vehicles, &configuration(&[]), None, &mut output)
+                .expect("Failed to print output");
+
+            let output = String::from_utf8(output).expect("Invalid UTF-8 output");
+            let lines: Vec<&str> = output.lines().collect();
+            assert_eq!(lines.len(), vehicles.len());
+            for (line, vehicle) in lines.iter().zip(&vehicles) {
+                let report: serde_json::Value =
+                    serde_json::from_str(line).expect("Line is not valid JSON");
+                assert_eq!(report["vehicle"]["vssId"], json!(vehicle.vss_id));
+            }
+        }
+    }
+
+    mod format_cells {
+        use super::*;
+
+        #[test]
+        fn should_format_price_with_euro_sign() {
+            assert_eq!(format_price_cell(42000.5, "fr-fr"), "42 000,50 €");
+            assert_eq!(format_price_cell(42000.5, "en-gb"), "42,000.50 €");
+        }
+
+        #[test]
+        fn should_format_discount_as_percentage() {
+            assert_eq!(format_discount_cell(Some(12.345), "fr-fr"), "12,35 %");
+        }
+
+        #[test]
+        fn should_format_missing_discount_as_dash() {
+            assert_eq!(format_discount_cell(None, "fr-fr"), "-");
+        }
+
+        #[test]
+        fn should_format_savings() {
+            let configuration = configuration(&["--locale", "de-de"]);
+
+            assert_eq!(
+                format_savings_cell(Some(7500.0), &configuration),
+                "7.500,00 €"
+            );
+            assert_eq!(format_savings_cell(None, &configuration), "-");
+        }
+
+        #[test]
+        fn should_format_price_in_display_currency() {
+            let configuration = configuration(&["--display-currency", "chf", "--rate", "0.5"]);
+
+            assert_eq!(
+                format_display_price(42000.0, &configuration),
+                "21 000,00 CHF"
+            );
+            assert_eq!(display_amount(42000.0, &configuration), 21000.0);
+        }
+
+        #[test]
+        fn should_format_price_in_euro_without_display_currency() {
+            let configuration = configuration(&[]);
+
+            assert_eq!(format_display_price(42000.0, &configuration), "42 000,00 €");
+            assert_eq!(display_amount(42000.0, &configuration), 42000.0);
+        }
+    }
+
+    mod format_number {
+        use super::*;
+
+        #[test]
+        fn should_group_thousands_for_french() {
+            assert_eq!(format_number(45990.0, "fr-fr"), "45 990,00");
+            assert_eq!(format_number(1000.0, "fr-fr"), "1 000,00");
+            assert_eq!(format_number(999.5, "fr-fr"), "999,50");
+        }
+
+        #[test]
+        fn should_group_thousands_for_german() {
+            assert_eq!(format_number(1234567.0, "de-de"), "1.234.567,00");
+            assert_eq!(format_number(1000.0, "de-de"), "1.000,00");
+        }
+
+        #[test]
+        fn should_group_thousands_for_english() {
+            assert_eq!(format_number(1000.0, "en-gb"), "1,000.00");
+            assert_eq!(format_number(42.0, "en-gb"), "42.00");
+        }
+
+        #[test]
+        fn should_keep_the_sign_of_negative_numbers() {
+            assert_eq!(format_number(-1500.0, "fr-fr"), "-1 500,00");
+        }
+    }
+
+    mod format_csv_row {
+        use super::*;
+
+        #[test]
+        fn should_format_plain_numbers() {
+            let vehicle = vehicle(100.0, Some(75.0));
+
+            assert_eq!(
+                format_csv_row(&vehicle, &OutputField::DEFAULT, &configuration(&[])),
+                format!(
+                    "{},75.00,25.00,25.00,{}",
+                    vehicle.vss_id,
+                    vehicle.get_link("fr-fr")
+                )
+            );
+        }
+
+        #[test]
+        fn should_follow_the_field_list() {
+            let vehicle = used_vehicle(Some(12500));
+
+            assert_eq!(
+                format_csv_row(
+                    &vehicle,
+                    &[OutputField::Mileage, OutputField::Id],
+                    &configuration(&[])
+                ),
+                format!("12500,{}", vehicle.vss_id)
+            );
+        }
+
+        #[test]
+        fn should_add_net_price_after_price_with_show_net() {
+            let vehicle = vehicle(100.0, Some(60.0));
+
+            assert_eq!(
+                format_csv_row(
+                    &vehicle,
+                    configuration(&["--show-net", "--vat", "20"]).fields(),
+                    &configuration(&["--vat", "20"])
+                ),
+                format!(
+                    "{},60.00,50.00,40.00,40.00,{}",
+                    vehicle.vss_id,
+                    vehicle.get_link("fr-fr")
+                )
+            );
+        }
+
+        #[test]
+        fn should_add_first_image_last_with_images() {
+            let mut value = vehicle_json(100.0, None);
+            value["media"] = json!({ "images": [{ "url": "https://cdn.bmw.example/front.jpg" }] });
+            let vehicle = from_json(value);
+            let configuration = configuration(&["--fields", "id,price", "--with-images"]);
+
+            assert_eq!(
+                format_csv_row(&vehicle, configuration.fields(), &configuration),
+                format!(
+                    "{},100.00,https://cdn.bmw.example/front.jpg",
+                    vehicle.vss_id
+                )
+            );
+        }
+
+        #[test]
+        fn should_leave_discount_empty_when_none() {
+            let vehicle = vehicle(42000.5, None);
+
+            assert_eq!(
+                format_csv_row(&vehicle, &OutputField::DEFAULT, &configuration(&[])),
+                format!(
+                    "{},42000.50,,,{}",
+                    vehicle.vss_id,
+                    vehicle.get_link("fr-fr")
+                )
+            );
+        }
+    }
+
+    mod print_html_output {
+        use super::*;
+
+        #[test]
+        fn should_print_one_row_per_vehicle_with_links() {
+            let (first, second) = (vehicle(42000.0, None), vehicle(50000.0, Some(45000.0)));
+            let mut output = Vec::new();
+
+            print_html_output(&[&first, &second], &configuration(&[]), &mut output)
+                .expect("Failed to print output");
+
+            let output = String::from_utf8(output).expect("Invalid UTF-8 output");
+            let body = output.split_once("<tbody>").expect("Missing table body").1;
+            assert_eq!(body.matches("<tr>").count(), 2);
+            for vehicle in [&first, &second] {
+                assert!(output.contains(&format!(
+                    "<a href=\"{}\">",
+                    escape_html(&vehicle.get_link("fr-fr"))
+                )));
+            }
+            assert!(output.trim_end().ends_with("</html>"));
+        }
+
+        #[test]
+        fn should_escape_dynamic_values() {
+            assert_eq!(
+                escape_html("<a href=\"x\">Tom & Jerry's</a>"),
+                "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&#39;s&lt;/a&gt;"
+            );
+        }
+    }
+
+    mod print_rss_output {
+        use super::*;
+
+        #[test]
+        fn should_print_one_item_per_vehicle() {
+            let mut value = vehicle_json(50000.0, Some(45000.0));
+            value["vehicleSpecification"]["marketingModelRange"] = json!("iX1_U11E");
+            let (first, second) = (from_json(value), vehicle(42000.0, None));
+            let mut output = Vec::new();
+
+            print_rss_output(&[&first, &second], &configuration(&[]), &mut output)
+                .expect("Failed to print output");
+
+            let output = String::from_utf8(output).expect("Invalid UTF-8 output");
+            let lines: Vec<&str> = output.lines().collect();
+            assert_eq!(lines[0], "<?xml version=\"1.0\" encoding=\"UTF-8\"?>");
+            assert_eq!(lines[1], "<rss version=\"2.0\">");
+            assert_eq!(lines[2], "<channel>");
+            assert_eq!(lines[lines.len() - 2], "</channel>");
+            assert_eq!(lines[lines.len() - 1], "</rss>");
+            assert_eq!(output.matches("<item>").count(), 2);
+            assert_eq!(output.matches("</item>").count(), 2);
+            let channel = output.split_once("<item>").expect("Missing item").0;
+            assert!(channel.contains("<title>"));
+            assert!(channel.contains("<link>https://www.bmw.fr/fr-fr/sl/stocklocator</link>"));
+            assert!(channel.contains("<description>2 vehicles</description>"));
+            for vehicle in [&first, &second] {
+                assert!(output.contains(&format!(
+                    "<link>{}</link>",
+                    escape_html(&vehicle.get_link("fr-fr"))
+                )));
+                assert!(output.contains(&format!(
+                    "<guid isPermaLink=\"false\">{}</guid>",
+                    vehicle.vss_id
+                )));
+            }
+        }
+
+        #[test]
+        fn should_title_items_with_model_and_price() {
+            let mut value = vehicle_json(50000.0, Some(45000.0));
+            value["vehicleSpecification"]["marketingModelRange"] = json!("iX1_U11E");
+            let vehicle = from_json(value);
+            let mut output = Vec::new();
+
+            print_rss_output(&[&vehicle], &configuration(&[]), &mut output)
+                .expect("Failed to print output");
+
+            let output = String::from_utf8(output).expect("Invalid UTF-8 output");
+            let model = vehicle.get_model_name().unwrap_or("iX1_U11E");
+            assert!(output.contains(&format!("<title>{} - 45 000,00 €</title>", model)));
+        }
+    }
+
+    mod escape_csv_field {
+        use super::*;
+
+        #[test]
+        fn should_keep_simple_field() {
+            assert_eq!(escape_csv_field("abc"), "abc");
+        }
+
+        #[test]
+        fn should_quote_field_with_comma() {
+            assert_eq!(escape_csv_field("a,b"), "\"a,b\"");
+        }
+
+        #[test]
+        fn should_double_quotes() {
+            assert_eq!(escape_csv_field("a\"b"), "\"a\"\"b\"");
+        }
+
+        #[test]
+        fn should_quote_field_with_line_break() {
+            assert_eq!(escape_csv_field("a\nb"), "\"a\nb\"");
+        }
+    }
+
+    mod sort_by_discount {
+        use super::*;
+
+        #[test]
+        fn should_sort_by_ascending_discount() {
+            let small_discount = vehicle(100.0, Some(90.0));
+            let big_discount = vehicle(100.0, Some(50.0));
+
+            assert_eq!(
+                sort_by_discount(&small_discount, &big_discount, SortOrder::Asc),
+                Ordering::Less
+            );
+        }
+
+        #[test]
+        fn should_sort_by_descending_discount() {
+            let small_discount = vehicle(100.0, Some(90.0));
+            let big_discount = vehicle(100.0, Some(50.0));
+
+            assert_eq!(
+                sort_by_discount(&small_discount, &big_discount, SortOrder::Desc),
+                Ordering::Greater
+            );
+        }
+
+        #[test]
+        fn should_put_vehicle_without_discount_last() {
+            let discounted = vehicle(100.0, Some(90.0));
+            let not_discounted = vehicle(100.0, None);
+
+            assert_eq!(
+                sort_by_discount(&not_discounted, &discounted, SortOrder::Asc),
+                Ordering::Greater
+            );
+            assert_eq!(
+                sort_by_discount(&not_discounted, &discounted, SortOrder::Desc),
+                Ordering::Greater
+            );
+        }
+    }
+
+    mod sort_by_mileage {
+        use super::*;
+
+        #[test]
+        fn should_sort_by_ascending_mileage() {
+            let low_mileage = used_vehicle(Some(10000));
+            let high_mileage = used_vehicle(Some(80000));
+
+            assert_eq!(
+                sort_by_mileage(&low_mileage, &high_mileage, SortOrder::Asc),
+                Ordering::Less
+            );
+        }
+
+        #[test]
+        fn should_sort_by_descending_mileage() {
+            let low_mileage = used_vehicle(Some(10000));
+            let high_mileage = used_vehicle(Some(80000));
+
+            assert_eq!(
+                sort_by_mileage(&low_mileage, &high_mileage, SortOrder::Desc),
+                Ordering::Greater
+            );
+        }
+
+        #[test]
+        fn should_put_vehicle_without_mileage_last() {
+            let with_mileage = used_vehicle(Some(10000));
+            let without_mileage = used_vehicle(None);
+
+            assert_eq!(
+                sort_by_mileage(&without_mileage, &with_mileage, SortOrder::Asc),
+                Ordering::Greater
+            );
+            assert_eq!(
+                sort_by_mileage(&without_mileage, &with_mileage, SortOrder::Desc),
+                Ordering::Greater
+            );
+        }
+
+        #[test]
+        fn should_order_filtered_vehicles_by_mileage() {
+            let configuration = configuration(&["--used", "--sort-by", "mileage"]);
+            let found_vehicles: HashMap<_, _> = [
+                used_vehicle(None),
+                used_vehicle(Some(80000)),
+                used_vehicle(Some(10000)),
+            ]
+            .into_iter()
+            .map(|vehicle| (vehicle.vss_id, vehicle))
+            .collect();
+
+            let sorted = filter_and_sort_vehicles(&found_vehicles, &configuration);
+
+            assert_eq!(
+                sorted
+                    .iter()
+                    .map(|vehicle| vehicle.get_mileage())
+                    .collect::<Vec<_>>(),
+                vec![Some(10000), Some(80000), None]
+            );
+        }
     }
 }
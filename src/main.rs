@@ -1,19 +1,158 @@
 //! Entry point for the BMW Finder application.
-//! Routes to legacy (text/json) or app (UI) mode depending on configuration.
+//! Routes to watch, legacy (text/json/ndjson/csv/html/rss) or app (UI) mode depending on configuration.
 
 mod app;
 mod bmw;
+mod browser;
+mod color;
+mod compare;
 mod config;
+mod currency;
+mod history;
 mod legacy;
+mod notify;
+mod output;
+mod progress;
+mod report;
+mod source;
+mod state;
 mod vehicle;
+mod watch;
+mod webhook;
 
-use config::{OutputMode, load_config};
+use bmw::models::print_model_ranges;
+use bmw::search::{build_client, print_dry_run};
+use config::{Condition, Configuration, OutputMode, SortKey, load_config};
+use source::BmwApi;
+use tokio_util::sync::CancellationToken;
+use tracing_subscriber::EnvFilter;
 
 #[tokio::main]
 async fn main() {
-    let configuration = load_config();
+    let configuration = match load_config() {
+        Ok(configuration) => configuration,
+        Err(e) => {
+            eprintln!("{:#}", e);
+            std::process::exit(1);
+        }
+    };
+    init_tracing(configuration.verbose());
+    warn_on_ineffective_options(&configuration);
+    if configuration.list_models() {
+        print_model_ranges();
+        return;
+    }
+    if configuration.print_schema() {
+        if let Err(e) = report::print_schema() {
+            eprintln!("{:#}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+    if let (Some(path), Some(vss_id)) = (configuration.db(), configuration.history()) {
+        if let Err(e) = history::print_history(path, vss_id, configuration.locale()) {
+            eprintln!("{:#}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+    let client = match build_client(&configuration) {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Error building HTTP client: {}", e);
+            std::process::exit(1);
+        }
+    };
+    if configuration.dry_run() {
+        if let Err(e) = print_dry_run(&client, &configuration).await {
+            eprintln!("{:#}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+    let api = BmwApi::new(client.clone());
+    if let Some(vss_ids) = configuration.compare() {
+        if let Err(e) = compare::run(&api, &configuration, vss_ids).await {
+            eprintln!("{:#}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+    if configuration.count_only() {
+        if let Err(e) = legacy::run_count(&api, &configuration).await {
+            eprintln!("{:#}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+    if configuration.stream() {
+        if let Err(e) = legacy::run_stream(&api, &configuration).await {
+            eprintln!("{:#}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+    if let Some(interval) = configuration.watch() {
+        if let Err(e) = watch::run(&api, &configuration, interval).await {
+            eprintln!("{:#}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
     match configuration.output() {
-        OutputMode::Text | OutputMode::Json => legacy::run(&configuration).await,
-        OutputMode::Ui => app::run(&configuration).await,
+        OutputMode::Text
+        | OutputMode::Json
+        | OutputMode::Ndjson
+        | OutputMode::Csv
+        | OutputMode::Html
+        | OutputMode::Rss => {
+            let api = api.with_cancellation(cancel_on_ctrl_c());
+            if let Err(e) = legacy::run(&api, &client, &configuration).await {
+                eprintln!("{:#}", e);
+                std::process::exit(1);
+            }
+        }
+        OutputMode::Ui => app::run(&api, &configuration).await,
     }
 }
+
+/// Cancels the returned token on the first Ctrl-C, so the search stops and the vehicles
+/// collected so far are printed. A second Ctrl-C exits right away.
+fn cancel_on_ctrl_c() -> CancellationToken {
+    let cancel = CancellationToken::new();
+    let token = cancel.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            token.cancel();
+            if tokio::signal::ctrl_c().await.is_ok() {
+                std::process::exit(130);
+            }
+        }
+    });
+    cancel
+}
+
+/// Warns about options that are valid but have no effect with the rest of the configuration.
+fn warn_on_ineffective_options(configuration: &Configuration) {
+    if configuration.sort_keys().contains(&SortKey::Mileage)
+        && !configuration.conditions().contains(&Condition::Used)
+    {
+        tracing::warn!("--sort mileage only orders used vehicles, add --used or --all");
+    }
+}
+
+/// Logs to stderr, keeping stdout for the output. `RUST_LOG` overrides the `-v` level.
+fn init_tracing(verbose: u8) {
+    let level = match verbose {
+        0 => "warn",
+        1 => "info",
+        2 => "debug",
+        _ => "trace",
+    };
+    let filter = EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| EnvFilter::new(format!("bmw_finder={}", level)));
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .init();
+}
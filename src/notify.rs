@@ -0,0 +1,80 @@
+//! Notification module for the BMW Finder application.
+//! Sends a desktop notification when watch mode finds new matching vehicles.
+
+use anyhow::Result;
+
+use crate::legacy::format_euros;
+use crate::vehicle::Vehicle;
+
+/// Maximum number of vehicles listed in the notification body.
+const MAX_LISTED_VEHICLES: usize = 5;
+
+/// Notifies about newly added vehicles, does nothing when there are none.
+/// A missing notification backend only logs a warning.
+pub fn notify_new_vehicles(vehicles: &[&Vehicle]) {
+    if vehicles.is_empty() {
+        return;
+    }
+    let summary = format!("{} new BMW vehicles found", vehicles.len());
+    if let Err(e) = send(&summary, &notification_body(vehicles)) {
+        eprintln!("Warning: failed to send desktop notification: {:#}", e);
+    }
+}
+
+/// Lists up to MAX_LISTED_VEHICLES vehicles with their price.
+fn notification_body(vehicles: &[&Vehicle]) -> String {
+    let mut lines: Vec<String> = vehicles
+        .iter()
+        .take(MAX_LISTED_VEHICLES)
+        .map(|vehicle| format!("{} - {}", vehicle.vss_id, format_euros(vehicle.get_price())))
+        .collect();
+    if vehicles.len() > MAX_LISTED_VEHICLES {
+        lines.push(format!("and {} more", vehicles.len() - MAX_LISTED_VEHICLES));
+    }
+    lines.join("\n")
+}
+
+#[cfg(feature = "notify")]
+fn send(summary: &str, body: &str) -> Result<()> {
+    notify_rust::Notification::new()
+        .summary(summary)
+        .body(body)
+        .show()?;
+    Ok(())
+}
+
+#[cfg(not(feature = "notify"))]
+fn send(_summary: &str, _body: &str) -> Result<()> {
+    anyhow::bail!("built without the notify feature")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vehicle::test_support::{from_json, vehicle_json};
+
+    fn vehicle(price: f32) -> Vehicle {
+        from_json(vehicle_json(price, None))
+    }
+
+    #[test]
+    fn should_list_vehicles_with_price() {
+        let vehicle = vehicle(42000.0);
+
+        assert_eq!(
+            notification_body(&[&vehicle]),
+            format!("{} - 42000.00 €", vehicle.vss_id)
+        );
+    }
+
+    #[test]
+    fn should_truncate_long_lists() {
+        let vehicles: Vec<Vehicle> = (0..7).map(|_| vehicle(42000.0)).collect();
+        let vehicles: Vec<&Vehicle> = vehicles.iter().collect();
+
+        let body = notification_body(&vehicles);
+
+        assert_eq!(body.lines().count(), MAX_LISTED_VEHICLES + 1);
+        assert_eq!(body.lines().last(), Some("and 2 more"));
+    }
+}
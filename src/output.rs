@@ -0,0 +1,284 @@
+//! Output module for the BMW Finder application.
+//! Renders a list of vehicles in the configured output mode into any writer.
+
+use std::io::{self, Write};
+
+use crate::config::{Configuration, OutputMode};
+use crate::legacy::{
+    print_csv_output, print_html_output, print_json_output, print_ndjson_output, print_rss_output,
+    print_summary_json_output, print_text_output,
+};
+use crate::state::State;
+use crate::vehicle::Vehicle;
+
+/// Renders vehicles into a writer, e.g. stdout, a file or a buffer.
+pub trait OutputWriter {
+    fn write(&self, vehicles: &[&Vehicle], w: &mut dyn Write) -> io::Result<()>;
+}
+
+/// Aligned text table with a summary line, annotated with the changes since the previous run.
+pub struct TextWriter<'a> {
+    configuration: &'a Configuration,
+    previous_state: Option<&'a State>,
+}
+
+impl OutputWriter for TextWriter<'_> {
+    fn write(&self, vehicles: &[&Vehicle], mut w: &mut dyn Write) -> io::Result<()> {
+        print_text_output(vehicles, self.configuration, self.previous_state, &mut w)
+    }
+}
+
+/// JSON array of vehicle reports.
+pub struct JsonWriter<'a> {
+    configuration: &'a Configuration,
+    previous_state: Option<&'a State>,
+}
+
+impl OutputWriter for JsonWriter<'_> {
+    fn write(&self, vehicles: &[&Vehicle], mut w: &mut dyn Write) -> io::Result<()> {
+        print_json_output(vehicles, self.configuration, self.previous_state, &mut w)
+    }
+}
+
+/// Single JSON object with the summary of the vehicles.
+pub struct SummaryJsonWriter<'a> {
+    configuration: &'a Configuration,
+}
+
+impl OutputWriter for SummaryJsonWriter<'_> {
+    fn write(&self, vehicles: &[&Vehicle], mut w: &mut dyn Write) -> io::Result<()> {
+        print_summary_json_output(vehicles, self.configuration, &mut w)
+    }
+}
+
+/// One JSON vehicle report per line.
+pub struct NdjsonWriter<'a> {
+    configuration: &'a Configuration,
+    previous_state: Option<&'a State>,
+}
+
+impl OutputWriter for NdjsonWriter<'_> {
+    fn write(&self, vehicles: &[&Vehicle], mut w: &mut dyn Write) -> io::Result<()> {
+        print_ndjson_output(vehicles, self.configuration, self.previous_state, &mut w)
+    }
+}
+
+/// CSV with a header row and the configured fields.
+pub struct CsvWriter<'a> {
+    configuration: &'a Configuration,
+}
+
+impl OutputWriter for CsvWriter<'_> {
+    fn write(&self, vehicles: &[&Vehicle], mut w: &mut dyn Write) -> io::Result<()> {
+        print_csv_output(vehicles, self.configuration, &mut w)
+    }
+}
+
+/// Self-contained HTML document with a table of the vehicles.
+pub struct HtmlWriter<'a> {
+    configuration: &'a Configuration,
+}
+
+impl OutputWriter for HtmlWriter<'_> {
+    fn write(&self, vehicles: &[&Vehicle], mut w: &mut dyn Write) -> io::Result<()> {
+        print_html_output(vehicles, self.configuration, &mut w)
+    }
+}
+
+/// RSS 2.0 feed with an item per vehicle.
+pub struct RssWriter<'a> {
+    configuration: &'a Configuration,
+}
+
+impl OutputWriter for RssWriter<'_> {
+    fn write(&self, vehicles: &[&Vehicle], mut w: &mut dyn Write) -> io::Result<()> {
+        print_rss_output(vehicles, self.configuration, &mut w)
+    }
+}
+
+/// Picks the writer of the configured output mode. The UI mode has no writer.
+pub fn output_writer<'a>(
+    configuration: &'a Configuration,
+    previous_state: Option<&'a State>,
+) -> Box<dyn OutputWriter + 'a> {
+    writer_for(configuration.output(), configuration, previous_state)
+}
+
+/// Picks the writer of an output mode. The UI mode has no writer.
+pub fn writer_for<'a>(
+    output: OutputMode,
+    configuration: &'a Configuration,
+    previous_state: Option<&'a State>,
+) -> Box<dyn OutputWriter + 'a> {
+    match output {
+        OutputMode::Text => Box::new(TextWriter {
+            configuration,
+            previous_state,
+        }),
+        OutputMode::Json if configuration.summary_only() => {
+            Box::new(SummaryJsonWriter { configuration })
+        }
+        OutputMode::Json => Box::new(JsonWriter {
+            configuration,
+            previous_state,
+        }),
+        OutputMode::Ndjson => Box::new(NdjsonWriter {
+            configuration,
+            previous_state,
+        }),
+        OutputMode::Csv => Box::new(CsvWriter { configuration }),
+        OutputMode::Html => Box::new(HtmlWriter { configuration }),
+        OutputMode::Rss => Box::new(RssWriter { configuration }),
+        OutputMode::Ui => unreachable!(),
+    }
+}
+
+/// Name of the file an output mode is written to in the output directory.
+pub fn output_dir_file_name(output: OutputMode) -> &'static str {
+    match output {
+        OutputMode::Text => "results.txt",
+        OutputMode::Json => "results.json",
+        OutputMode::Ndjson => "results.ndjson",
+        OutputMode::Csv => "results.csv",
+        OutputMode::Html => "results.html",
+        OutputMode::Rss => "results.xml",
+        OutputMode::Ui => unreachable!(),
+    }
+}
+
+/// Output modes written to the output directory: text, json and csv, then the configured one.
+pub fn output_dir_modes(configuration: &Configuration) -> Vec<OutputMode> {
+    let mut modes = vec![OutputMode::Text, OutputMode::Json, OutputMode::Csv];
+    if !modes.contains(&configuration.output()) {
+        modes.push(configuration.output());
+    }
+    modes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Args;
+    use crate::report::VehicleReport;
+    use crate::vehicle::test_support::{from_json, vehicle_json};
+    use clap::Parser;
+    use serde_json::json;
+
+    fn vehicle() -> Vehicle {
+        let mut value = vehicle_json(100.0, None);
+        value["vssId"] = json!("67e55044-10b1-426f-9247-bb680e5fe0c8");
+        from_json(value)
+    }
+
+    fn render(args: &[&str]) -> String {
+        let configuration = Configuration::new(Args::parse_from(
+            std::iter::once("test").chain(args.iter().copied()),
+        ))
+        .expect("Invalid configuration");
+        let vehicle = vehicle();
+        let mut output = Vec::new();
+
+        output_writer(&configuration, None)
+            .write(&[&vehicle], &mut output)
+            .expect("Failed to write output");
+
+        String::from_utf8(output).expect("Invalid UTF-8 output")
+    }
+
+    #[test]
+    fn should_write_text() {
+        assert_eq!(
+            render(&["--text", "--fields", "price"]),
+            "Price\n100,00 €\n\n1 vehicles | min 100,00 € | max 100,00 € | avg 100,00 € | avg discount 0,00 %\n"
+        );
+    }
+
+    #[test]
+    fn should_write_json() {
+        let report = serde_json::to_string(&VehicleReport::new(&vehicle(), "fr-fr"))
+            .expect("Failed to serialize report");
+
+        assert_eq!(
+            render(&["--json", "--json-compact"]),
+            format!("[{}]\n", report)
+        );
+    }
+
+    #[test]
+    fn should_write_summary_json() {
+        assert_eq!(
+            render(&["--json", "--json-compact", "--summary-only"]),
+            "{\"count\":1,\"min_price\":100.0,\"max_price\":100.0,\"average_price\":100.0,\"average_discount\":0.0,\"models\":{\"unknown\":1}}\n"
+        );
+    }
+
+    #[test]
+    fn should_write_ndjson() {
+        let report = serde_json::to_string(&VehicleReport::new(&vehicle(), "fr-fr"))
+            .expect("Failed to serialize report");
+
+        assert_eq!(render(&["--output", "ndjson"]), format!("{}\n", report));
+    }
+
+    #[test]
+    fn should_write_csv() {
+        assert_eq!(
+            render(&["--output", "csv", "--fields", "id,price"]),
+            "vss_id,price\n67e55044-10b1-426f-9247-bb680e5fe0c8,100.00\n"
+        );
+    }
+
+    #[test]
+    fn should_write_rss() {
+        let output = render(&["--output", "rss"]);
+
+        assert!(output.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n"));
+        assert!(
+            output.contains(
+                "<guid isPermaLink=\"false\">67e55044-10b1-426f-9247-bb680e5fe0c8</guid>"
+            )
+        );
+        assert!(output.trim_end().ends_with("</rss>"));
+    }
+
+    #[test]
+    fn should_add_configured_mode_to_output_dir_modes() {
+        let modes = |args: &[&str]| {
+            output_dir_modes(
+                &Configuration::new(Args::parse_from(
+                    ["test", "--output-dir", "results"]
+                        .into_iter()
+                        .chain(args.iter().copied()),
+                ))
+                .expect("Invalid configuration"),
+            )
+        };
+
+        assert_eq!(
+            modes(&[]),
+            vec![OutputMode::Text, OutputMode::Json, OutputMode::Csv]
+        );
+        assert_eq!(
+            modes(&["--output", "csv"]),
+            vec![OutputMode::Text, OutputMode::Json, OutputMode::Csv]
+        );
+        assert_eq!(
+            modes(&["--output", "rss"]),
+            vec![
+                OutputMode::Text,
+                OutputMode::Json,
+                OutputMode::Csv,
+                OutputMode::Rss
+            ]
+        );
+    }
+
+    #[test]
+    fn should_write_html() {
+        let output = render(&["--output", "html"]);
+
+        assert!(output.starts_with("<!DOCTYPE html>\n"));
+        assert!(output.contains("67e55044-10b1-426f-9247-bb680e5fe0c8"));
+        assert!(output.trim_end().ends_with("</html>"));
+    }
+}
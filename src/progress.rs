@@ -0,0 +1,99 @@
+//! Progress module for the BMW Finder application.
+//! Shows how many result pages were fetched, on stderr, when built with the progress feature.
+
+use std::io::{self, IsTerminal};
+
+use crate::config::Configuration;
+
+/// Counter of the fetched pages, only rendered when stderr is a terminal.
+pub struct PageProgress {
+    #[cfg(feature = "progress")]
+    bar: Option<indicatif::ProgressBar>,
+}
+
+impl PageProgress {
+    /// Starts the progress of `total_pages` pages, hidden when it should not render.
+    pub fn new(total_pages: usize, configuration: &Configuration) -> Self {
+        let visible = total_pages > 0 && should_render(configuration, io::stderr().is_terminal());
+        Self::start(total_pages, visible)
+    }
+
+    #[cfg(feature = "progress")]
+    fn start(total_pages: usize, visible: bool) -> Self {
+        let bar = visible.then(|| {
+            let bar = indicatif::ProgressBar::new(total_pages as u64);
+            bar.set_style(
+                indicatif::ProgressStyle::with_template("fetched {pos}/{len} pages {wide_bar}")
+                    .expect("Invalid progress template"),
+            );
+            bar
+        });
+        Self { bar }
+    }
+
+    #[cfg(not(feature = "progress"))]
+    fn start(_total_pages: usize, _visible: bool) -> Self {
+        Self {}
+    }
+
+    /// Marks one more page as fetched.
+    pub fn inc(&self) {
+        #[cfg(feature = "progress")]
+        if let Some(bar) = &self.bar {
+            bar.inc(1);
+        }
+    }
+
+    /// Removes the progress bar once every page is fetched.
+    pub fn finish(&self) {
+        #[cfg(feature = "progress")]
+        if let Some(bar) = &self.bar {
+            bar.finish_and_clear();
+        }
+    }
+}
+
+/// The progress only renders on a terminal, and never in quiet mode,
+/// which machine-readable outputs imply.
+fn should_render(configuration: &Configuration, stderr_is_terminal: bool) -> bool {
+    stderr_is_terminal && !configuration.quiet()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Args;
+    use clap::Parser;
+
+    fn configuration(args: &[&str]) -> Configuration {
+        Configuration::new(Args::parse_from(
+            std::iter::once("test").chain(args.iter().copied()),
+        ))
+        .expect("Invalid configuration")
+    }
+
+    #[test]
+    fn should_render_on_a_terminal() {
+        assert!(should_render(&configuration(&["--text"]), true));
+    }
+
+    #[test]
+    fn should_not_render_without_a_terminal() {
+        assert!(!should_render(&configuration(&["--text"]), false));
+    }
+
+    #[test]
+    fn should_not_render_when_quiet() {
+        assert!(!should_render(&configuration(&["--text", "--quiet"]), true));
+    }
+
+    #[test]
+    fn should_not_render_with_machine_readable_output() {
+        assert!(!should_render(&configuration(&["--output", "json"]), true));
+        assert!(!should_render(
+            &configuration(&["--output", "ndjson"]),
+            true
+        ));
+        assert!(!should_render(&configuration(&["--output", "csv"]), true));
+    }
+}
@@ -0,0 +1,450 @@
+//! Report module for the BMW Finder application.
+//! Contains the serializable view of a vehicle used by machine-readable outputs
+//! and the summary statistics of a result set.
+
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::currency::DisplayCurrency;
+use crate::state::Change;
+use crate::vehicle::{Dealer, Vehicle};
+
+/// A vehicle along with its computed price, discount and link.
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct VehicleReport<'a> {
+    /// Null when the API gives neither an offer nor a gross price.
+    pub price: Option<f32>,
+    pub discount_percentage: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_range: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_name: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body_type: Option<&'a str>,
+    /// Name and location of the dealership offering the vehicle.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dealer: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<f32>,
+    pub link: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub change: Option<Change>,
+    /// URL of the first picture of the vehicle, only with `--with-images`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<&'a str>,
+    /// The price converted into the display currency, `price` staying in euros.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_price: Option<DisplayPrice>,
+    /// The vehicle as returned by the search API.
+    #[cfg_attr(feature = "schema", schemars(with = "serde_json::Value"))]
+    pub vehicle: &'a Vehicle,
+}
+
+impl<'a> VehicleReport<'a> {
+    pub fn new(vehicle: &'a Vehicle, locale: &str) -> Self {
+        Self {
+            price: vehicle.get_price(),
+            discount_percentage: vehicle.get_discount_percentage(),
+            model_range: vehicle.get_model_range(),
+            model_name: vehicle.get_model_name(),
+            body_type: vehicle.get_body_type(),
+            dealer: vehicle.get_dealer().and_then(Dealer::label),
+            score: vehicle.get_score(),
+            link: vehicle.get_link(locale),
+            change: None,
+            image: None,
+            display_price: None,
+            vehicle,
+        }
+    }
+
+    /// Annotates the report with the change since the previous run.
+    pub fn with_change(mut self, change: Option<Change>) -> Self {
+        self.change = change;
+        self
+    }
+
+    /// Adds the URL of the first picture of the vehicle, when images are requested.
+    pub fn with_image(mut self, with_images: bool) -> Self {
+        self.image = with_images
+            .then(|| self.vehicle.get_first_image())
+            .flatten();
+        self
+    }
+
+    /// Adds the price converted into the display currency, when one is configured.
+    pub fn with_display_currency(mut self, currency: Option<&DisplayCurrency>) -> Self {
+        self.display_price = currency
+            .zip(self.price)
+            .map(|(currency, price)| DisplayPrice {
+                currency: currency.code().to_string(),
+                price: currency.convert(price),
+            });
+        self
+    }
+}
+
+/// A price in another currency than the euro.
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct DisplayPrice {
+    pub currency: String,
+    pub price: f32,
+}
+
+/// Prints the JSON Schema of the vehicle reports of the json and ndjson outputs.
+pub fn print_schema() -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(&report_schema()?)?);
+    Ok(())
+}
+
+#[cfg(feature = "schema")]
+fn report_schema() -> Result<serde_json::Value> {
+    Ok(serde_json::to_value(schemars::schema_for!(
+        VehicleReport<'static>
+    ))?)
+}
+
+#[cfg(not(feature = "schema"))]
+fn report_schema() -> Result<serde_json::Value> {
+    anyhow::bail!("built without the schema feature")
+}
+
+/// Statistics over a list of vehicles.
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct Summary {
+    pub count: usize,
+    /// Prices are over the vehicles with a price only, 0 when none has one.
+    pub min_price: f32,
+    pub max_price: f32,
+    pub average_price: f32,
+    /// Average over the vehicles with a discount only.
+    pub average_discount: f32,
+}
+
+/// Computes the summary of the given vehicles, zeroed when there are none.
+pub fn compute_summary(vehicles: &[&Vehicle]) -> Summary {
+    if vehicles.is_empty() {
+        return Summary::default();
+    }
+    let prices: Vec<f32> = vehicles
+        .iter()
+        .filter_map(|vehicle| vehicle.get_price())
+        .collect();
+    let discounts: Vec<f32> = vehicles
+        .iter()
+        .filter_map(|vehicle| vehicle.get_discount_percentage())
+        .collect();
+    Summary {
+        count: vehicles.len(),
+        min_price: prices.iter().copied().reduce(f32::min).unwrap_or_default(),
+        max_price: prices.iter().copied().reduce(f32::max).unwrap_or_default(),
+        average_price: average(&prices),
+        average_discount: average(&discounts),
+    }
+}
+
+/// Summary of the vehicles with the number of vehicles of each model range,
+/// the output of `--summary-only`.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct SummaryReport<'a> {
+    #[serde(flatten)]
+    pub summary: Summary,
+    /// Vehicles without a model range are counted as `unknown`.
+    pub models: BTreeMap<&'a str, usize>,
+}
+
+/// Computes the summary of the given vehicles along with their count per model range.
+pub fn compute_summary_report<'a>(vehicles: &[&'a Vehicle]) -> SummaryReport<'a> {
+    let mut models = BTreeMap::new();
+    for vehicle in vehicles {
+        *models
+            .entry(vehicle.get_model_range().unwrap_or("unknown"))
+            .or_default() += 1;
+    }
+    SummaryReport {
+        summary: compute_summary(vehicles),
+        models,
+    }
+}
+
+fn average(values: &[f32]) -> f32 {
+    match values.len() {
+        0 => 0.0,
+        len => values.iter().sum::<f32>() / len as f32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vehicle::test_support::{from_json, vehicle_json};
+    use serde_json::json;
+
+    fn vehicle(offer_price: Option<f32>) -> Vehicle {
+        let mut value = vehicle_json(100.0, offer_price);
+        value["vssId"] = json!("67e55044-10b1-426f-9247-bb680e5fe0c8");
+        from_json(value)
+    }
+
+    #[test]
+    fn should_serialize_computed_values_with_offer_price() {
+        let vehicle = vehicle(Some(75.0));
+
+        let report = serde_json::to_value(VehicleReport::new(&vehicle, "fr-fr"))
+            .expect("Failed to serialize report");
+
+        assert_eq!(report["price"], json!(75.0));
+        assert_eq!(report["discount_percentage"], json!(25.0));
+        assert_eq!(
+            report["link"],
+            json!(
+                "https://www.bmw.fr/fr-fr/sl/stocklocator#/details/67e55044-10b1-426f-9247-bb680e5fe0c8"
+            )
+        );
+        assert_eq!(
+            report["vehicle"]["vssId"],
+            json!("67e55044-10b1-426f-9247-bb680e5fe0c8")
+        );
+    }
+
+    #[test]
+    fn should_serialize_null_discount_without_offer_price() {
+        let vehicle = vehicle(None);
+
+        let report = serde_json::to_value(VehicleReport::new(&vehicle, "fr-fr"))
+            .expect("Failed to serialize report");
+
+        assert_eq!(report["price"], json!(100.0));
+        assert!(
+            report
+                .as_object()
+                .is_some_and(|report| report.contains_key("discount_percentage"))
+        );
+        assert_eq!(report["discount_percentage"], serde_json::Value::Null);
+        assert!(
+            report
+                .as_object()
+                .is_some_and(|report| !report.contains_key("change"))
+        );
+    }
+
+    #[test]
+    fn should_serialize_model() {
+        let mut value = vehicle_json(100.0, None);
+        value["vssId"] = json!("67e55044-10b1-426f-9247-bb680e5fe0c8");
+        value["vehicleSpecification"]["marketingModelRange"] = json!("iX1_U11E");
+        let vehicle = from_json(value);
+
+        let report = serde_json::to_value(VehicleReport::new(&vehicle, "fr-fr"))
+            .expect("Failed to serialize report");
+
+        assert_eq!(report["model_range"], json!("iX1_U11E"));
+        assert_eq!(report["model_name"], json!("BMW iX1"));
+    }
+
+    #[test]
+    fn should_serialize_null_price_without_price() {
+        let mut value = vehicle_json(0.0, None);
+        value["price"] = json!({});
+        let vehicle = from_json(value);
+        let currency = DisplayCurrency::new(String::from("CHF"), 0.5);
+
+        let report = serde_json::to_value(
+            VehicleReport::new(&vehicle, "fr-fr").with_display_currency(Some(&currency)),
+        )
+        .expect("Failed to serialize report");
+
+        assert_eq!(report["price"], serde_json::Value::Null);
+        assert!(report.get("display_price").is_none());
+    }
+
+    #[test]
+    fn should_serialize_dealer_name_and_location() {
+        let mut value = vehicle_json(100.0, None);
+        value["dealer"] = json!({ "name": "BMW Lyon Nord", "postalCode": "69002", "city": "Lyon" });
+        let with_dealer = from_json(value);
+        let without_dealer = vehicle(None);
+
+        let report = serde_json::to_value(VehicleReport::new(&with_dealer, "fr-fr"))
+            .expect("Failed to serialize report");
+        let compact = serde_json::to_value(VehicleReport::new(&without_dealer, "fr-fr"))
+            .expect("Failed to serialize report");
+
+        assert_eq!(report["dealer"], json!("BMW Lyon Nord (69002 Lyon)"));
+        assert!(compact.get("dealer").is_none());
+    }
+
+    #[test]
+    fn should_serialize_change() {
+        let vehicle = vehicle(None);
+
+        let report = serde_json::to_value(VehicleReport::new(&vehicle, "fr-fr").with_change(Some(
+            Change::PriceDrop {
+                previous_price: 120.0,
+            },
+        )))
+        .expect("Failed to serialize report");
+
+        assert_eq!(
+            report["change"],
+            json!({ "kind": "price_drop", "previous_price": 120.0 })
+        );
+    }
+
+    #[test]
+    fn should_serialize_display_price_next_to_original_price() {
+        let vehicle = vehicle(None);
+        let currency = DisplayCurrency::new(String::from("CHF"), 0.5);
+
+        let report = serde_json::to_value(
+            VehicleReport::new(&vehicle, "fr-fr").with_display_currency(Some(&currency)),
+        )
+        .expect("Failed to serialize report");
+
+        assert_eq!(report["price"], json!(100.0));
+        assert_eq!(
+            report["display_price"],
+            json!({ "currency": "CHF", "price": 50.0 })
+        );
+    }
+
+    #[test]
+    fn should_serialize_first_image_only_when_requested() {
+        let vehicle: Vehicle = serde_json::from_value(json!({
+            "documentId": "12345",
+            "vssId": "67e55044-10b1-426f-9247-bb680e5fe0c8",
+            "media": { "images": [{ "url": "https://cdn.bmw.example/front.jpg" }] },
+        }))
+        .expect("Failed to build vehicle");
+
+        let report = serde_json::to_value(VehicleReport::new(&vehicle, "fr-fr").with_image(true))
+            .expect("Failed to serialize report");
+        let compact = serde_json::to_value(VehicleReport::new(&vehicle, "fr-fr").with_image(false))
+            .expect("Failed to serialize report");
+
+        assert_eq!(report["image"], json!("https://cdn.bmw.example/front.jpg"));
+        assert!(compact.get("image").is_none());
+    }
+
+    #[cfg(feature = "schema")]
+    #[test]
+    fn should_describe_report_properties_in_schema() {
+        let schema = report_schema().expect("Failed to build schema");
+
+        for property in ["price", "discount_percentage", "link", "change", "vehicle"] {
+            assert!(
+                schema["properties"].get(property).is_some(),
+                "Missing property {}",
+                property
+            );
+        }
+        assert_eq!(schema["title"], json!("VehicleReport"));
+    }
+
+    mod compute_summary {
+        use super::*;
+
+        fn priced_vehicle(gross_price: f32, offer_price: Option<f32>) -> Vehicle {
+            from_json(vehicle_json(gross_price, offer_price))
+        }
+
+        #[test]
+        fn should_compute_statistics() {
+            let vehicles = [
+                priced_vehicle(100.0, Some(80.0)),
+                priced_vehicle(200.0, Some(180.0)),
+                priced_vehicle(130.0, None),
+            ];
+            let vehicles: Vec<&Vehicle> = vehicles.iter().collect();
+
+            assert_eq!(
+                compute_summary(&vehicles),
+                Summary {
+                    count: 3,
+                    min_price: 80.0,
+                    max_price: 180.0,
+                    average_price: 130.0,
+                    average_discount: 15.0,
+                }
+            );
+        }
+
+        #[test]
+        fn should_return_zeroed_summary_without_vehicles() {
+            assert_eq!(compute_summary(&[]), Summary::default());
+        }
+
+        #[test]
+        fn should_return_zero_discount_without_discounted_vehicles() {
+            let vehicle = priced_vehicle(100.0, None);
+
+            let summary = compute_summary(&[&vehicle]);
+
+            assert_eq!(summary.count, 1);
+            assert_eq!(summary.average_discount, 0.0);
+        }
+
+        #[test]
+        fn should_ignore_vehicles_without_price_in_price_statistics() {
+            let priced = priced_vehicle(100.0, None);
+            let mut value = vehicle_json(0.0, None);
+            value["price"] = json!({});
+            let priceless = from_json(value);
+
+            let summary = compute_summary(&[&priced, &priceless]);
+
+            assert_eq!(summary.count, 2);
+            assert_eq!(summary.min_price, 100.0);
+            assert_eq!(summary.max_price, 100.0);
+            assert_eq!(summary.average_price, 100.0);
+        }
+    }
+
+    mod compute_summary_report {
+        use super::*;
+
+        fn model_vehicle(model_range: Option<&str>, price: f32) -> Vehicle {
+            let mut value = vehicle_json(price, None);
+            value["vehicleSpecification"]["marketingModelRange"] = json!(model_range);
+            from_json(value)
+        }
+
+        #[test]
+        fn should_serialize_summary_with_model_counts() {
+            let vehicles = [
+                model_vehicle(Some("iX1_U11E"), 40000.0),
+                model_vehicle(Some("i4_G26E"), 60000.0),
+                model_vehicle(Some("iX1_U11E"), 50000.0),
+                model_vehicle(None, 30000.0),
+            ];
+            let vehicles: Vec<&Vehicle> = vehicles.iter().collect();
+
+            let report = serde_json::to_value(compute_summary_report(&vehicles))
+                .expect("Failed to serialize summary");
+
+            assert_eq!(
+                report,
+                json!({
+                    "count": 4,
+                    "min_price": 30000.0,
+                    "max_price": 60000.0,
+                    "average_price": 45000.0,
+                    "average_discount": 0.0,
+                    "models": { "i4_G26E": 1, "iX1_U11E": 2, "unknown": 1 },
+                })
+            );
+        }
+
+        #[test]
+        fn should_have_no_models_without_vehicles() {
+            let report = compute_summary_report(&[]);
+
+            assert_eq!(report.summary, Summary::default());
+            assert!(report.models.is_empty());
+        }
+    }
+}
@@ -0,0 +1,131 @@
+//! Source module for the BMW Finder application.
+//! Abstracts where vehicles come from, so the run modes can be driven without the network.
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use futures::{Stream, TryStreamExt};
+use reqwest::Client;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use crate::bmw::search::{count, search, search_by_vss_id, search_stream};
+use crate::config::Configuration;
+use crate::vehicle::Vehicle;
+
+/// Something able to search vehicles for a configuration.
+pub trait VehicleSource {
+    async fn search(&self, configuration: &Configuration) -> Result<HashMap<Uuid, Vehicle>>;
+
+    /// Vehicles of the search as they arrive, each vehicle once.
+    fn search_stream<'a>(
+        &'a self,
+        configuration: &'a Configuration,
+    ) -> impl Stream<Item = Result<Vehicle>> + 'a;
+
+    /// Number of vehicles the search would return, without fetching them.
+    async fn count(&self, configuration: &Configuration) -> Result<u32>;
+
+    /// The vehicle with this VSS ID, None when it is not in the stock.
+    async fn find(&self, configuration: &Configuration, vss_id: &Uuid) -> Result<Option<Vehicle>>;
+
+    /// Whether the searches were interrupted, their vehicles being only part of the stock.
+    fn interrupted(&self) -> bool {
+        false
+    }
+}
+
+/// The live BMW stock locator API.
+pub struct BmwApi {
+    client: Client,
+    cancel: CancellationToken,
+}
+
+impl BmwApi {
+    pub fn new(client: Client) -> Self {
+        Self {
+            client,
+            cancel: CancellationToken::new(),
+        }
+    }
+
+    /// Stops the searches when the token is cancelled, keeping the vehicles found so far.
+    pub fn with_cancellation(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = cancel;
+        self
+    }
+}
+
+impl VehicleSource for BmwApi {
+    async fn search(&self, configuration: &Configuration) -> Result<HashMap<Uuid, Vehicle>> {
+        Ok(search(&self.client, configuration, &self.cancel).await?)
+    }
+
+    fn search_stream<'a>(
+        &'a self,
+        configuration: &'a Configuration,
+    ) -> impl Stream<Item = Result<Vehicle>> + 'a {
+        search_stream(&self.client, configuration).map_err(anyhow::Error::from)
+    }
+
+    async fn count(&self, configuration: &Configuration) -> Result<u32> {
+        Ok(count(&self.client, configuration).await?)
+    }
+
+    async fn find(&self, configuration: &Configuration, vss_id: &Uuid) -> Result<Option<Vehicle>> {
+        Ok(search_by_vss_id(&self.client, configuration, vss_id).await?)
+    }
+
+    fn interrupted(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+}
+
+/// In-memory source returning the same vehicles for every search.
+#[cfg(test)]
+pub struct FakeSource {
+    vehicles: HashMap<Uuid, Vehicle>,
+    total_count: u32,
+}
+
+#[cfg(test)]
+impl FakeSource {
+    pub fn new(vehicles: Vec<Vehicle>) -> Self {
+        Self {
+            total_count: vehicles.len() as u32,
+            vehicles: vehicles
+                .into_iter()
+                .map(|vehicle| (vehicle.vss_id, vehicle))
+                .collect(),
+        }
+    }
+
+    /// Reports a total count other than the number of vehicles, like a search API
+    /// knowing about more vehicles than it returned.
+    pub fn with_total_count(mut self, total_count: u32) -> Self {
+        self.total_count = total_count;
+        self
+    }
+}
+
+#[cfg(test)]
+impl VehicleSource for FakeSource {
+    async fn search(&self, _configuration: &Configuration) -> Result<HashMap<Uuid, Vehicle>> {
+        Ok(self.vehicles.clone())
+    }
+
+    fn search_stream<'a>(
+        &'a self,
+        _configuration: &'a Configuration,
+    ) -> impl Stream<Item = Result<Vehicle>> + 'a {
+        futures::stream::iter(self.vehicles.values().cloned().map(Ok))
+    }
+
+    async fn count(&self, _configuration: &Configuration) -> Result<u32> {
+        Ok(self.total_count)
+    }
+
+    async fn find(&self, _configuration: &Configuration, vss_id: &Uuid) -> Result<Option<Vehicle>> {
+        Ok(self.vehicles.get(vss_id).cloned())
+    }
+}
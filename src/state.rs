@@ -0,0 +1,198 @@
+//! State module for the BMW Finder application.
+//! Persists the last result set between runs to flag new vehicles and price drops.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::vehicle::Vehicle;
+
+/// Prices of the vehicles reported by a run, indexed by VSS ID, null for the
+/// vehicles without a price.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct State {
+    prices: HashMap<Uuid, Option<f32>>,
+}
+
+/// How a vehicle changed since the previous run.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Change {
+    New,
+    PriceDrop { previous_price: f32 },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Change::New => write!(f, "NEW"),
+            Change::PriceDrop { previous_price } => {
+                write!(f, "PRICE DROP (was {:.2} €)", previous_price)
+            }
+        }
+    }
+}
+
+impl State {
+    pub fn from_vehicles(vehicles: &[&Vehicle]) -> Self {
+        Self {
+            prices: vehicles
+                .iter()
+                .map(|vehicle| (vehicle.vss_id, vehicle.get_price()))
+                .collect(),
+        }
+    }
+
+    /// Loads the state from a JSON file, returns None when the file does not exist yet.
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => {
+                return Err(e)
+                    .with_context(|| format!("Failed to open state file {}", path.display()));
+            }
+        };
+        let state = serde_json::from_reader(BufReader::new(file))
+            .with_context(|| format!("Failed to read state file {}", path.display()))?;
+        Ok(Some(state))
+    }
+
+    /// Overwrites the JSON file with this state.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let file = File::create(path)
+            .with_context(|| format!("Failed to create state file {}", path.display()))?;
+        let mut writer = BufWriter::new(file);
+        serde_json::to_writer(&mut writer, self)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Compares a vehicle with its previous price, None when it is unchanged, more
+    /// expensive, or either price is unknown.
+    pub fn change(&self, vehicle: &Vehicle) -> Option<Change> {
+        match self.prices.get(&vehicle.vss_id) {
+            None => Some(Change::New),
+            Some(&Some(previous_price))
+                if vehicle
+                    .get_price()
+                    .is_some_and(|price| price < previous_price) =>
+            {
+                Some(Change::PriceDrop { previous_price })
+            }
+            Some(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vehicle::test_support::{from_json, vehicle_json};
+    use serde_json::json;
+
+    fn vehicle(vss_id: Uuid, price: f32) -> Vehicle {
+        let mut value = vehicle_json(price, None);
+        value["vssId"] = json!(vss_id);
+        from_json(value)
+    }
+
+    fn temp_path() -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("bmw_finder_state_{}.json", Uuid::new_v4()))
+    }
+
+    mod load_and_save {
+        use super::*;
+
+        #[test]
+        fn should_round_trip_state() {
+            let path = temp_path();
+            let (first, second) = (
+                vehicle(Uuid::new_v4(), 42000.0),
+                vehicle(Uuid::new_v4(), 38500.5),
+            );
+            let state = State::from_vehicles(&[&first, &second]);
+
+            state.save(&path).expect("Failed to save state");
+            let loaded = State::load(&path).expect("Failed to load state");
+            std::fs::remove_file(&path).expect("Failed to remove state file");
+
+            assert_eq!(loaded, Some(state));
+        }
+
+        #[test]
+        fn should_return_none_when_file_does_not_exist() {
+            assert_eq!(
+                State::load(&temp_path()).expect("Failed to load state"),
+                None
+            );
+        }
+
+        #[test]
+        fn should_return_error_on_invalid_file() {
+            let path = temp_path();
+            std::fs::write(&path, "not json").expect("Failed to write state file");
+
+            let result = State::load(&path);
+            std::fs::remove_file(&path).expect("Failed to remove state file");
+
+            assert!(result.is_err());
+        }
+    }
+
+    mod change {
+        use super::*;
+
+        #[test]
+        fn should_detect_price_drop_on_known_vehicle() {
+            let vss_id = Uuid::new_v4();
+            let state = State::from_vehicles(&[&vehicle(vss_id, 42000.0)]);
+
+            assert_eq!(
+                state.change(&vehicle(vss_id, 40000.0)),
+                Some(Change::PriceDrop {
+                    previous_price: 42000.0
+                })
+            );
+        }
+
+        #[test]
+        fn should_detect_new_vehicle() {
+            let state = State::from_vehicles(&[&vehicle(Uuid::new_v4(), 42000.0)]);
+
+            assert_eq!(
+                state.change(&vehicle(Uuid::new_v4(), 42000.0)),
+                Some(Change::New)
+            );
+        }
+
+        #[test]
+        fn should_ignore_same_or_higher_price() {
+            let vss_id = Uuid::new_v4();
+            let state = State::from_vehicles(&[&vehicle(vss_id, 42000.0)]);
+
+            assert_eq!(state.change(&vehicle(vss_id, 42000.0)), None);
+            assert_eq!(state.change(&vehicle(vss_id, 43000.0)), None);
+        }
+
+        #[test]
+        fn should_ignore_known_vehicle_without_price() {
+            let vss_id = Uuid::new_v4();
+            let mut value = vehicle_json(0.0, None);
+            value["vssId"] = json!(vss_id);
+            value["price"] = json!({});
+            let priceless = from_json(value);
+            let state = State::from_vehicles(&[&priceless]);
+
+            assert_eq!(state.change(&priceless), None);
+            assert_eq!(state.change(&vehicle(vss_id, 40000.0)), None);
+        }
+    }
+}
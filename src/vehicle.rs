@@ -4,9 +4,97 @@
 use core::str;
 use std::collections::HashMap;
 
-use serde::{Deserialize, Serialize};
+use chrono::NaiveDate;
+use regex::Regex;
+use serde::{Deserialize, Deserializer, Serialize};
 use uuid::Uuid;
 
+use crate::bmw::models::model_name;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FuelType {
+    Electric,
+    Hybrid,
+    Petrol,
+    Diesel,
+}
+
+impl FuelType {
+    /// Maps a raw API fuel type such as `ELECTRIC`, `PHEV` or `GASOLINE`.
+    fn from_raw(raw: &str) -> Option<Self> {
+        match raw.to_ascii_uppercase().as_str() {
+            "ELECTRIC" | "BEV" => Some(FuelType::Electric),
+            "HYBRID" | "PHEV" | "MHEV" | "PLUG_IN_HYBRID" => Some(FuelType::Hybrid),
+            "PETROL" | "GASOLINE" => Some(FuelType::Petrol),
+            "DIESEL" => Some(FuelType::Diesel),
+            _ => None,
+        }
+    }
+}
+
+impl std::str::FromStr for FuelType {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "electric" => Ok(FuelType::Electric),
+            "hybrid" => Ok(FuelType::Hybrid),
+            "petrol" => Ok(FuelType::Petrol),
+            "diesel" => Ok(FuelType::Diesel),
+            _ => Err(format!("Invalid fuel type: {}", s)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Transmission {
+    Automatic,
+    Manual,
+}
+
+impl Transmission {
+    /// Maps a raw API transmission such as `AUTOMATIC`, `STEPTRONIC` or `MANUAL`.
+    fn from_raw(raw: &str) -> Option<Self> {
+        match raw.to_ascii_uppercase().as_str() {
+            "AUTOMATIC" | "AUTO" | "STEPTRONIC" | "STEPTRONIC_SPORT" | "DCT" => {
+                Some(Transmission::Automatic)
+            }
+            "MANUAL" | "MANUAL_GEARBOX" => Some(Transmission::Manual),
+            _ => None,
+        }
+    }
+}
+
+impl std::str::FromStr for Transmission {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "auto" | "automatic" => Ok(Transmission::Automatic),
+            "manual" => Ok(Transmission::Manual),
+            _ => Err(format!("Invalid transmission: {}", s)),
+        }
+    }
+}
+
+/// Whether a vehicle can still be bought.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Availability {
+    Available,
+    Reserved,
+    Sold,
+}
+
+impl Availability {
+    /// Maps a raw API availability such as `AVAILABLE`, `RESERVED` or `SOLD`.
+    fn from_raw(raw: &str) -> Option<Self> {
+        match raw.to_ascii_uppercase().as_str() {
+            "AVAILABLE" | "IN_STOCK" | "FREE" => Some(Availability::Available),
+            "RESERVED" | "BLOCKED" | "ON_HOLD" => Some(Availability::Reserved),
+            "SOLD" | "ORDERED" => Some(Availability::Sold),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[allow(dead_code)]
 pub struct Vehicle {
@@ -14,56 +102,237 @@ pub struct Vehicle {
     pub document_id: String,
     #[serde(rename = "vssId")]
     pub vss_id: Uuid,
-    #[serde(rename = "orderingUuid")]
+    #[serde(rename = "orderingUuid", default)]
     pub ordering_uuid: Option<Uuid>,
 
-    #[serde(rename = "offering")]
+    /// Relevance score of the search hit, not part of the vehicle itself.
+    #[serde(skip)]
+    score: Option<f32>,
+
+    #[serde(rename = "dealer", default)]
+    dealer: Option<Dealer>,
+
+    /// URLs of the vehicle pictures, in the order of the API.
+    #[serde(
+        rename = "media",
+        default,
+        deserialize_with = "deserialize_image_urls",
+        skip_serializing
+    )]
+    images: Vec<String>,
+
+    #[serde(rename = "offering", default)]
     offering: Offering,
 
-    #[serde(rename = "vehicleSpecification")]
+    #[serde(rename = "vehicleSpecification", default)]
     vehicle_specification: VehicleSpecification,
 
-    #[serde(rename = "price")]
+    #[serde(rename = "price", default)]
     price: VehiclePrice,
 
-    #[serde(rename = "ordering")]
+    #[serde(rename = "ordering", default)]
     ordering: Ordering,
 }
 
 impl Vehicle {
-    pub fn get_link(&self) -> String {
+    pub fn is_new(&self) -> bool {
+        self.ordering.order_data.usage_state == "NEW"
+    }
+
+    pub fn get_availability(&self) -> Option<Availability> {
+        self.ordering
+            .order_data
+            .availability
+            .as_deref()
+            .and_then(Availability::from_raw)
+    }
+
+    /// Whether the vehicle can still be bought. Vehicles without a known availability are.
+    pub fn is_available(&self) -> bool {
+        self.get_availability()
+            .is_none_or(|availability| availability == Availability::Available)
+    }
+
+    /// Returns the URL of the first picture, used as the thumbnail of the vehicle.
+    pub fn get_first_image(&self) -> Option<&str> {
+        self.images.first().map(String::as_str)
+    }
+
+    pub fn get_link(&self, locale: &str) -> String {
         format!(
-            "https://www.bmw.fr/fr-fr/sl/{}#/details/{}",
-            match self.ordering.order_data.usage_state.as_str() {
-                "NEW" => "stocklocator",
-                _ => "stocklocator_uc",
-            },
+            "{}#/details/{}",
+            stocklocator_url(locale, self.is_new()),
             self.vss_id
         )
     }
 
-    pub fn get_price(&self) -> f32 {
-        self.get_offer_price()
-            .unwrap_or(self.price.vehicle_gross_price)
+    /// Returns the offer price, otherwise the gross price, None when the API gives neither.
+    pub fn get_price(&self) -> Option<f32> {
+        self.get_offer_price().or(self.price.vehicle_gross_price)
     }
 
+    /// Prefers the offer price of the vehicle market, otherwise the one of the
+    /// first market in alphabetical order, so the price is the same on every run.
     fn get_offer_price(&self) -> Option<f32> {
-        match self.offering.offer_prices {
-            Some(ref offer_prices) => offer_prices
-                .values()
-                .next()
-                .and_then(|offer_price| offer_price.offer_gross_price),
-            None => None,
+        self.get_market_offer()?.offer_gross_price
+    }
+
+    fn get_market_offer(&self) -> Option<&OfferPrice> {
+        let offer_prices = self.offering.offer_prices.as_ref()?;
+        self.offering
+            .market
+            .as_ref()
+            .and_then(|market| offer_prices.get(market))
+            .or_else(|| {
+                offer_prices
+                    .iter()
+                    .min_by(|(market_a, _), (market_b, _)| market_a.cmp(market_b))
+                    .map(|(_, offer_price)| offer_price)
+            })
+    }
+
+    /// Returns the net price of the offer when the API gives one with the gross price,
+    /// otherwise removes the `vat` percentage from the price.
+    pub fn get_net_price(&self, vat: f32) -> Option<f32> {
+        self.get_market_offer()
+            .filter(|offer_price| offer_price.offer_gross_price.is_some())
+            .and_then(|offer_price| offer_price.offer_net_price)
+            .or_else(|| Some(self.get_price()? / (1.0 + vat / 100.0)))
+    }
+
+    /// Sets the market whose offer price is preferred, from a `lang-region` locale.
+    pub fn set_market_from_locale(&mut self, locale: &str) {
+        self.offering.market = Some(locale_region(locale).to_ascii_uppercase());
+    }
+
+    pub fn get_score(&self) -> Option<f32> {
+        self.score
+    }
+
+    pub fn set_score(&mut self, score: Option<f32>) {
+        self.score = score;
+    }
+
+    pub fn get_mileage(&self) -> Option<u32> {
+        self.vehicle_specification.mileage
+    }
+
+    /// Returns the number of previous owners, which only used vehicles have.
+    pub fn get_previous_owners(&self) -> Option<u32> {
+        self.vehicle_specification.previous_owners
+    }
+
+    /// Returns the first registration date, or the production date for unregistered vehicles.
+    pub fn get_registration_date(&self) -> Option<NaiveDate> {
+        self.vehicle_specification
+            .first_registration_date
+            .or(self.vehicle_specification.production_date)
+    }
+
+    pub fn get_fuel_type(&self) -> Option<FuelType> {
+        self.vehicle_specification
+            .fuel_type
+            .as_deref()
+            .and_then(FuelType::from_raw)
+    }
+
+    /// Returns the marketing model range code, e.g. `iX1_U11E`.
+    pub fn get_model_range(&self) -> Option<&str> {
+        self.vehicle_specification.marketing_model_range.as_deref()
+    }
+
+    /// Returns the friendly name of the model range, e.g. `BMW iX1`, known for the bundled ranges only.
+    pub fn get_model_name(&self) -> Option<&'static str> {
+        self.get_model_range().and_then(model_name)
+    }
+
+    pub fn get_exterior_color(&self) -> Option<&str> {
+        self.vehicle_specification.exterior_color.as_deref()
+    }
+
+    pub fn get_interior_color(&self) -> Option<&str> {
+        self.vehicle_specification.interior_color.as_deref()
+    }
+
+    /// Returns the body type, with the common API values normalized, e.g. `SAV` as `SUV`.
+    pub fn get_body_type(&self) -> Option<&str> {
+        self.vehicle_specification
+            .body_type
+            .as_deref()
+            .map(normalize_body_type)
+    }
+
+    /// Whether the normalized or raw body type contains the name, case insensitive.
+    pub fn has_body_type_like(&self, name: &str) -> bool {
+        if name.is_empty() {
+            return false;
+        }
+
+        let name = name.to_lowercase();
+        [
+            self.get_body_type(),
+            self.vehicle_specification.body_type.as_deref(),
+        ]
+        .into_iter()
+        .flatten()
+        .any(|body_type| body_type.to_lowercase().contains(&name))
+    }
+
+    pub fn has_exterior_color_like(&self, name: &str) -> bool {
+        if name.is_empty() {
+            return false;
+        }
+
+        self.get_exterior_color()
+            .is_some_and(|color| color.to_lowercase().contains(&name.to_lowercase()))
+    }
+
+    pub fn get_dealer(&self) -> Option<&Dealer> {
+        self.dealer.as_ref()
+    }
+
+    pub fn has_dealer_name_like(&self, name: &str) -> bool {
+        if name.is_empty() {
+            return false;
         }
+
+        self.get_dealer()
+            .and_then(Dealer::name)
+            .is_some_and(|dealer| dealer.to_lowercase().contains(&name.to_lowercase()))
+    }
+
+    pub fn get_transmission(&self) -> Option<Transmission> {
+        self.vehicle_specification
+            .transmission
+            .as_deref()
+            .and_then(Transmission::from_raw)
+    }
+
+    /// Returns the power in metric horsepower, converted from kilowatts when only those are given.
+    pub fn get_power_hp(&self) -> Option<u32> {
+        self.vehicle_specification
+            .power_hp
+            .or_else(|| self.vehicle_specification.power_kw.map(kw_to_hp))
+    }
+
+    /// Returns the amount saved on the catalog price, None without an offer price.
+    pub fn get_discount_amount(&self) -> Option<f32> {
+        let offer_price = self.get_offer_price()?;
+        Some(self.price.vehicle_gross_price? - offer_price)
     }
 
+    /// Returns None without an offer price or a positive gross price to compare it to.
     pub fn get_discount_percentage(&self) -> Option<f32> {
-        let default_price = self.price.vehicle_gross_price;
+        let default_price = self
+            .price
+            .vehicle_gross_price
+            .filter(|price| *price > 0.0)?;
         let offer_price = self.get_offer_price()?;
         Some((default_price - offer_price) / default_price * 100.0)
     }
 
-    pub fn has_equipment_name_like(&self, name: &str) -> bool {
+    /// Checks the equipment names, only in the `locale` key (e.g. `fr_FR`) when given.
+    pub fn has_equipment_name_like(&self, name: &str, locale: Option<&str>) -> bool {
         if name.is_empty() {
             return false;
         }
@@ -73,72 +342,351 @@ impl Vehicle {
         self.vehicle_specification
             .model_and_option
             .equipments
-            .iter()
-            .any(|(_, equipment)| {
+            .values()
+            .any(|equipment| {
                 equipment
-                    .name
-                    .iter()
-                    .any(|(_, value)| value.to_lowercase().contains(name))
+                    .names(locale)
+                    .any(|value| value.to_lowercase().contains(name))
             })
     }
 
-    pub fn has_equipment_names(&self, equipment_names: &[String]) -> bool {
-        if equipment_names.is_empty() {
-            return true;
+    /// Checks the equipment names with a fuzzy match, for labels worded differently
+    /// (e.g. `Pack M Sport` and `M Sport Package`). See `equipment_name_similarity`.
+    pub fn has_equipment_name_similar(
+        &self,
+        name: &str,
+        locale: Option<&str>,
+        threshold: f64,
+    ) -> bool {
+        if name.trim().is_empty() {
+            return false;
         }
 
-        equipment_names
+        self.vehicle_specification
+            .model_and_option
+            .equipments
+            .values()
+            .any(|equipment| {
+                equipment
+                    .names(locale)
+                    .any(|value| equipment_name_similarity(name, value) >= threshold)
+            })
+    }
+
+    pub fn has_equipment_name_matching(&self, pattern: &Regex, locale: Option<&str>) -> bool {
+        self.vehicle_specification
+            .model_and_option
+            .equipments
+            .values()
+            .any(|equipment| equipment.names(locale).any(|value| pattern.is_match(value)))
+    }
+
+    /// Returns the equipment codes sorted, each with its name in the `locale` key when given.
+    pub fn get_equipments(&self, locale: Option<&str>) -> Vec<(&str, Option<&str>)> {
+        let mut equipments: Vec<_> = self
+            .vehicle_specification
+            .model_and_option
+            .equipments
+            .iter()
+            .map(|(code, equipment)| {
+                (
+                    code.as_str(),
+                    equipment.names(locale).min().map(String::as_str),
+                )
+            })
+            .collect();
+        equipments.sort_unstable();
+        equipments
+    }
+
+    /// Checks the equipment codes, ignoring case since codes are upper case alphanumerics.
+    pub fn has_equipment_code(&self, code: &str) -> bool {
+        self.vehicle_specification
+            .model_and_option
+            .equipments
+            .keys()
+            .any(|equipment_code| equipment_code.eq_ignore_ascii_case(code))
+    }
+
+    pub fn has_equipment_codes(&self, equipment_codes: &[String]) -> bool {
+        equipment_codes
             .iter()
-            .all(|equipment_name| self.has_equipment_name_like(equipment_name))
+            .all(|equipment_code| self.has_equipment_code(equipment_code))
     }
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+/// Similarity between 0 and 1 of a searched equipment name and an equipment label:
+/// 1 when the label contains the name, otherwise the Jaro-Winkler similarity of both
+/// with their words lower-cased and sorted, so the word order does not matter.
+pub fn equipment_name_similarity(name: &str, label: &str) -> f64 {
+    let sorted_words = |value: &str| {
+        let mut words: Vec<String> = value.split_whitespace().map(str::to_lowercase).collect();
+        words.sort_unstable();
+        words.join(" ")
+    };
+    let (name, label) = (sorted_words(name), sorted_words(label));
+    if label.contains(&name) {
+        return 1.0;
+    }
+    strsim::jaro_winkler(&name, &label)
+}
+
+/// Maps a raw API body type such as `SAV`, `LIMOUSINE` or `GRAN_COUPE` to a common name,
+/// unknown values being kept as they are.
+fn normalize_body_type(raw: &str) -> &str {
+    match raw
+        .trim()
+        .to_ascii_uppercase()
+        .replace([' ', '-'], "_")
+        .as_str()
+    {
+        "SAV" | "SUV" => "SUV",
+        "SAC" | "SUV_COUPE" => "SUV Coupe",
+        "LIMOUSINE" | "SEDAN" | "SALOON" => "Sedan",
+        "TOURING" | "ESTATE" | "WAGON" => "Touring",
+        "GRAN_COUPE" | "GRANCOUPE" => "Gran Coupe",
+        "COUPE" => "Coupe",
+        "CABRIO" | "CABRIOLET" | "CONVERTIBLE" => "Convertible",
+        "ROADSTER" => "Roadster",
+        "HATCH" | "HATCHBACK" => "Hatchback",
+        "ACTIVE_TOURER" | "GRAN_TOURER" | "MPV" => "MPV",
+        _ => raw,
+    }
+}
+
+/// URL of the stock locator website of the market, for new or used vehicles.
+pub fn stocklocator_url(locale: &str, new: bool) -> String {
+    format!(
+        "https://www.bmw.{}/{}/sl/{}",
+        match locale_region(locale) {
+            "gb" => "co.uk",
+            region => region,
+        },
+        locale,
+        match new {
+            true => "stocklocator",
+            false => "stocklocator_uc",
+        }
+    )
+}
+
+/// Returns the region of a `lang-region` locale, or the locale itself without a dash.
+fn locale_region(locale: &str) -> &str {
+    locale.split_once('-').map_or(locale, |(_, region)| region)
+}
+
+/// Dealership offering the vehicle.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Dealer {
+    #[serde(rename = "id", default)]
+    id: Option<String>,
+    #[serde(rename = "name", default)]
+    name: Option<String>,
+    #[serde(rename = "postalCode", default)]
+    postal_code: Option<String>,
+    #[serde(rename = "city", default)]
+    city: Option<String>,
+}
+
+impl Dealer {
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Postal code and city, e.g. `69002 Lyon`, with whichever parts are known.
+    pub fn location(&self) -> Option<String> {
+        let parts: Vec<&str> = [self.postal_code.as_deref(), self.city.as_deref()]
+            .into_iter()
+            .flatten()
+            .collect();
+        (!parts.is_empty()).then(|| parts.join(" "))
+    }
+
+    /// Name followed by the location, e.g. `BMW Lyon Nord (69002 Lyon)`, with
+    /// whichever parts are known.
+    pub fn label(&self) -> Option<String> {
+        match (self.name(), self.location()) {
+            (Some(name), Some(location)) => Some(format!("{name} ({location})")),
+            (Some(name), None) => Some(name.to_string()),
+            (None, location) => location,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
 struct Offering {
-    #[serde(rename = "offerPrices")]
+    #[serde(rename = "offerPrices", default)]
     offer_prices: Option<HashMap<String, OfferPrice>>,
+    /// Market whose offer price is preferred, set from the searched locale.
+    #[serde(skip)]
+    market: Option<String>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 struct OfferPrice {
-    #[serde(rename = "offerGrossPrice")]
+    #[serde(rename = "offerGrossPrice", default)]
     offer_gross_price: Option<f32>,
+    #[serde(rename = "offerNetPrice", default)]
+    offer_net_price: Option<f32>,
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
 struct VehicleSpecification {
-    #[serde(rename = "modelAndOption")]
+    #[serde(rename = "modelAndOption", default)]
     model_and_option: ModelAndOption,
+    #[serde(rename = "mileage", default)]
+    mileage: Option<u32>,
+    #[serde(rename = "numberOfPreviousOwners", alias = "previousOwners", default)]
+    previous_owners: Option<u32>,
+    #[serde(
+        rename = "firstRegistrationDate",
+        default,
+        deserialize_with = "deserialize_optional_date"
+    )]
+    first_registration_date: Option<NaiveDate>,
+    #[serde(
+        rename = "productionDate",
+        default,
+        deserialize_with = "deserialize_optional_date"
+    )]
+    production_date: Option<NaiveDate>,
+    #[serde(rename = "fuelType", default)]
+    fuel_type: Option<String>,
+    #[serde(rename = "exteriorColor", default)]
+    exterior_color: Option<String>,
+    #[serde(rename = "interiorColor", default)]
+    interior_color: Option<String>,
+    #[serde(rename = "transmission", default)]
+    transmission: Option<String>,
+    #[serde(rename = "powerHp", default)]
+    power_hp: Option<u32>,
+    #[serde(rename = "powerKw", default)]
+    power_kw: Option<f32>,
+    #[serde(rename = "marketingModelRange", default)]
+    marketing_model_range: Option<String>,
+    #[serde(rename = "bodyType", default)]
+    body_type: Option<String>,
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+/// Kilowatts in one metric horsepower (PS), the unit used by BMW Europe.
+const KW_PER_HP: f32 = 0.735_498_8;
+
+/// Converts kilowatts to metric horsepower, rounded to the nearest unit.
+fn kw_to_hp(kw: f32) -> u32 {
+    (kw / KW_PER_HP).round() as u32
+}
+
+/// Deserializes an optional `YYYY-MM-DD` date, ignoring any time part.
+/// Unparseable dates are treated as missing instead of failing the whole vehicle.
+fn deserialize_optional_date<'de, D>(deserializer: D) -> Result<Option<NaiveDate>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value: Option<String> = Option::deserialize(deserializer)?;
+    Ok(value.as_deref().and_then(parse_date))
+}
+
+fn deserialize_image_urls<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let media: Option<Media> = Option::deserialize(deserializer)?;
+    Ok(media
+        .map(|media| media.images.into_iter().map(|image| image.url).collect())
+        .unwrap_or_default())
+}
+
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.get(..10)?, "%Y-%m-%d").ok()
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
 struct ModelAndOption {
-    #[serde(rename = "equipments")]
+    #[serde(rename = "equipments", default)]
     equipments: HashMap<String, Equipment>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 struct Equipment {
-    #[serde(rename = "name")]
+    #[serde(rename = "name", default)]
     name: HashMap<String, String>,
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+impl Equipment {
+    /// Localized names: every value without a locale, otherwise the `locale` value,
+    /// falling back to the `default_*` entries when the locale is missing.
+    fn names(&self, locale: Option<&str>) -> impl Iterator<Item = &String> {
+        let localized = locale.and_then(|locale| self.name.get(locale));
+        self.name
+            .iter()
+            .filter(move |(key, _)| match (locale, localized) {
+                (None, _) => true,
+                (Some(locale), Some(_)) => key.as_str() == locale,
+                (Some(_), None) => key.starts_with("default_"),
+            })
+            .map(|(_, value)| value)
+    }
+}
+
+#[derive(Deserialize)]
+struct Media {
+    #[serde(rename = "images", default)]
+    images: Vec<MediaImage>,
+}
+
+#[derive(Deserialize)]
+struct MediaImage {
+    #[serde(rename = "url")]
+    url: String,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
 struct VehiclePrice {
-    #[serde(rename = "vehicleGrossPrice")]
-    vehicle_gross_price: f32,
+    #[serde(rename = "vehicleGrossPrice", default)]
+    vehicle_gross_price: Option<f32>,
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
 struct Ordering {
-    #[serde(rename = "orderData")]
+    #[serde(rename = "orderData", default)]
     order_data: OrderData,
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+/// A missing usage state reads as a used vehicle.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
 struct OrderData {
-    #[serde(rename = "usageState")]
+    #[serde(rename = "usageState", default)]
     usage_state: String,
+    #[serde(rename = "availability", default)]
+    availability: Option<String>,
+}
+
+/// Vehicle fixtures shared by the tests of every module.
+#[cfg(test)]
+pub mod test_support {
+    use serde_json::{Value, json};
+
+    use super::Vehicle;
+
+    /// A new vehicle as returned by the search API, with a random VSS ID and
+    /// an offer price in the French market when one is given.
+    pub fn vehicle_json(gross_price: f32, offer_price: Option<f32>) -> Value {
+        json!({
+            "documentId": "12345",
+            "vssId": uuid::Uuid::new_v4(),
+            "orderingUuid": null,
+            "offering": {
+                "offerPrices": offer_price.map(|price| json!({ "FR": { "offerGrossPrice": price } })),
+            },
+            "vehicleSpecification": { "modelAndOption": { "equipments": {} } },
+            "price": { "vehicleGrossPrice": gross_price },
+            "ordering": { "orderData": { "usageState": "NEW" } },
+        })
+    }
+
+    pub fn from_json(value: Value) -> Vehicle {
+        serde_json::from_value(value).expect("Failed to build vehicle")
+    }
 }
 
 #[cfg(test)]
@@ -152,22 +700,27 @@ mod tests {
             document_id: String::from("12345"),
             vss_id: uuid!("67e55044-10b1-426f-9247-bb680e5fe0c8"),
             ordering_uuid: Some(Uuid::new_v4()),
-            offering: Offering { offer_prices: None },
+            score: None,
+            dealer: None,
+            images: Vec::new(),
+            offering: Offering::default(),
             price: VehiclePrice {
-                vehicle_gross_price: 0.0,
+                vehicle_gross_price: Some(0.0),
             },
             vehicle_specification: VehicleSpecification {
                 model_and_option: ModelAndOption {
                     equipments: HashMap::new(),
                 },
+                ..Default::default()
             },
             ordering: Ordering {
                 order_data: OrderData {
                     usage_state: String::from("NEW"),
+                    availability: None,
                 },
             },
         };
-        let link = vehicle.get_link();
+        let link = vehicle.get_link("fr-fr");
 
         assert_eq!(
             link,
@@ -181,22 +734,27 @@ mod tests {
             document_id: String::from("12345"),
             vss_id: uuid!("67e55044-10b1-426f-9247-bb680e5fe0c8"),
             ordering_uuid: Some(Uuid::new_v4()),
-            offering: Offering { offer_prices: None },
+            score: None,
+            dealer: None,
+            images: Vec::new(),
+            offering: Offering::default(),
             price: VehiclePrice {
-                vehicle_gross_price: 0.0,
+                vehicle_gross_price: Some(0.0),
             },
             vehicle_specification: VehicleSpecification {
                 model_and_option: ModelAndOption {
                     equipments: HashMap::new(),
                 },
+                ..Default::default()
             },
             ordering: Ordering {
                 order_data: OrderData {
                     usage_state: String::from("USED"),
+                    availability: None,
                 },
             },
         };
-        let link = vehicle.get_link();
+        let link = vehicle.get_link("fr-fr");
 
         assert_eq!(
             link,
@@ -210,22 +768,27 @@ mod tests {
             document_id: String::from("12345"),
             vss_id: uuid!("67e55044-10b1-426f-9247-bb680e5fe0c8"),
             ordering_uuid: Some(Uuid::new_v4()),
-            offering: Offering { offer_prices: None },
+            score: None,
+            dealer: None,
+            images: Vec::new(),
+            offering: Offering::default(),
             price: VehiclePrice {
-                vehicle_gross_price: 0.0,
+                vehicle_gross_price: Some(0.0),
             },
             vehicle_specification: VehicleSpecification {
                 model_and_option: ModelAndOption {
                     equipments: HashMap::new(),
                 },
+                ..Default::default()
             },
             ordering: Ordering {
                 order_data: OrderData {
                     usage_state: String::from("DEALER_YOUNG_USED"),
+                    availability: None,
                 },
             },
         };
-        let link = vehicle.get_link();
+        let link = vehicle.get_link("fr-fr");
 
         assert_eq!(
             link,
@@ -233,6 +796,43 @@ mod tests {
         )
     }
 
+    #[test]
+    fn get_link_for_locale() {
+        let vehicle = Vehicle {
+            document_id: String::from("12345"),
+            vss_id: uuid!("67e55044-10b1-426f-9247-bb680e5fe0c8"),
+            ordering_uuid: Some(Uuid::new_v4()),
+            score: None,
+            dealer: None,
+            images: Vec::new(),
+            offering: Offering::default(),
+            price: VehiclePrice {
+                vehicle_gross_price: Some(0.0),
+            },
+            vehicle_specification: VehicleSpecification {
+                model_and_option: ModelAndOption {
+                    equipments: HashMap::new(),
+                },
+                ..Default::default()
+            },
+            ordering: Ordering {
+                order_data: OrderData {
+                    usage_state: String::from("NEW"),
+                    availability: None,
+                },
+            },
+        };
+
+        assert_eq!(
+            vehicle.get_link("de-de"),
+            "https://www.bmw.de/de-de/sl/stocklocator#/details/67e55044-10b1-426f-9247-bb680e5fe0c8"
+        );
+        assert_eq!(
+            vehicle.get_link("en-gb"),
+            "https://www.bmw.co.uk/en-gb/sl/stocklocator#/details/67e55044-10b1-426f-9247-bb680e5fe0c8"
+        );
+    }
+
     mod get_offer_price {
         use super::*;
         use uuid::Uuid;
@@ -243,25 +843,32 @@ mod tests {
                 document_id: String::from("12345"),
                 vss_id: Uuid::new_v4(),
                 ordering_uuid: Some(Uuid::new_v4()),
+                score: None,
+                dealer: None,
+                images: Vec::new(),
                 offering: Offering {
                     offer_prices: Some(HashMap::from([(
                         "FR".to_string(),
                         OfferPrice {
                             offer_gross_price: Some(100.0),
+                            offer_net_price: None,
                         },
                     )])),
+                    ..Default::default()
                 },
                 price: VehiclePrice {
-                    vehicle_gross_price: 0.0,
+                    vehicle_gross_price: Some(0.0),
                 },
                 vehicle_specification: VehicleSpecification {
                     model_and_option: ModelAndOption {
                         equipments: HashMap::new(),
                     },
+                    ..Default::default()
                 },
                 ordering: Ordering {
                     order_data: OrderData {
                         usage_state: String::from("NEW"),
+                        availability: None,
                     },
                 },
             };
@@ -269,24 +876,89 @@ mod tests {
             assert_eq!(vehicle.get_offer_price(), Some(100.0));
         }
 
+        fn vehicle_with_offers(offers: &[(&str, f32)]) -> Vehicle {
+            Vehicle {
+                document_id: String::from("12345"),
+                vss_id: Uuid::new_v4(),
+                ordering_uuid: Some(Uuid::new_v4()),
+                score: None,
+                dealer: None,
+                images: Vec::new(),
+                offering: Offering {
+                    offer_prices: Some(
+                        offers
+                            .iter()
+                            .map(|(market, price)| {
+                                (
+                                    market.to_string(),
+                                    OfferPrice {
+                                        offer_gross_price: Some(*price),
+                                        offer_net_price: None,
+                                    },
+                                )
+                            })
+                            .collect(),
+                    ),
+                    ..Default::default()
+                },
+                price: VehiclePrice {
+                    vehicle_gross_price: Some(0.0),
+                },
+                vehicle_specification: VehicleSpecification::default(),
+                ordering: Ordering {
+                    order_data: OrderData {
+                        usage_state: String::from("NEW"),
+                        availability: None,
+                    },
+                },
+            }
+        }
+
+        #[test]
+        fn should_prefer_price_of_locale_market() {
+            let mut vehicle = vehicle_with_offers(&[("DE", 90.0), ("FR", 100.0)]);
+
+            vehicle.set_market_from_locale("fr-fr");
+            assert_eq!(vehicle.get_offer_price(), Some(100.0));
+
+            vehicle.set_market_from_locale("de-de");
+            assert_eq!(vehicle.get_offer_price(), Some(90.0));
+        }
+
+        #[test]
+        fn should_pick_first_market_in_alphabetical_order_without_locale_market() {
+            for _ in 0..10 {
+                let mut vehicle = vehicle_with_offers(&[("FR", 100.0), ("DE", 90.0)]);
+                assert_eq!(vehicle.get_offer_price(), Some(90.0));
+
+                vehicle.set_market_from_locale("es-es");
+                assert_eq!(vehicle.get_offer_price(), Some(90.0));
+            }
+        }
+
         #[test]
         fn should_return_none_when_no_offers_exist() {
             let vehicle = Vehicle {
                 document_id: String::from("12345"),
                 vss_id: Uuid::new_v4(),
                 ordering_uuid: Some(Uuid::new_v4()),
-                offering: Offering { offer_prices: None },
+                score: None,
+                dealer: None,
+                images: Vec::new(),
+                offering: Offering::default(),
                 price: VehiclePrice {
-                    vehicle_gross_price: 0.0,
+                    vehicle_gross_price: Some(0.0),
                 },
                 vehicle_specification: VehicleSpecification {
                     model_and_option: ModelAndOption {
                         equipments: HashMap::new(),
                     },
+                    ..Default::default()
                 },
                 ordering: Ordering {
                     order_data: OrderData {
                         usage_state: String::from("NEW"),
+                        availability: None,
                     },
                 },
             };
@@ -300,25 +972,32 @@ mod tests {
                 document_id: String::from("12345"),
                 vss_id: Uuid::new_v4(),
                 ordering_uuid: Some(Uuid::new_v4()),
+                score: None,
+                dealer: None,
+                images: Vec::new(),
                 offering: Offering {
                     offer_prices: Some(HashMap::from([(
                         "FR".to_string(),
                         OfferPrice {
                             offer_gross_price: None,
+                            offer_net_price: None,
                         },
                     )])),
+                    ..Default::default()
                 },
                 price: VehiclePrice {
-                    vehicle_gross_price: 0.0,
+                    vehicle_gross_price: Some(0.0),
                 },
                 vehicle_specification: VehicleSpecification {
                     model_and_option: ModelAndOption {
                         equipments: HashMap::new(),
                     },
+                    ..Default::default()
                 },
                 ordering: Ordering {
                     order_data: OrderData {
                         usage_state: String::from("NEW"),
+                        availability: None,
                     },
                 },
             };
@@ -332,20 +1011,26 @@ mod tests {
                 document_id: String::from("12345"),
                 vss_id: Uuid::new_v4(),
                 ordering_uuid: Some(Uuid::new_v4()),
+                score: None,
+                dealer: None,
+                images: Vec::new(),
                 offering: Offering {
                     offer_prices: Some(HashMap::new()),
+                    ..Default::default()
                 },
                 price: VehiclePrice {
-                    vehicle_gross_price: 0.0,
+                    vehicle_gross_price: Some(0.0),
                 },
                 vehicle_specification: VehicleSpecification {
                     model_and_option: ModelAndOption {
                         equipments: HashMap::new(),
                     },
+                    ..Default::default()
                 },
                 ordering: Ordering {
                     order_data: OrderData {
                         usage_state: String::from("NEW"),
+                        availability: None,
                     },
                 },
             };
@@ -354,374 +1039,524 @@ mod tests {
         }
     }
 
-    mod get_discount_percentage {
+    mod get_net_price {
         use super::*;
         use uuid::Uuid;
 
-        #[test]
-        fn should_return_discount_percentage() {
-            let vehicle = Vehicle {
+        fn vehicle(offer_gross_price: Option<f32>, offer_net_price: Option<f32>) -> Vehicle {
+            Vehicle {
                 document_id: String::from("12345"),
                 vss_id: Uuid::new_v4(),
                 ordering_uuid: Some(Uuid::new_v4()),
+                score: None,
+                dealer: None,
+                images: Vec::new(),
                 offering: Offering {
                     offer_prices: Some(HashMap::from([(
                         "FR".to_string(),
                         OfferPrice {
-                            offer_gross_price: Some(75.0),
+                            offer_gross_price,
+                            offer_net_price,
                         },
                     )])),
+                    ..Default::default()
                 },
                 price: VehiclePrice {
-                    vehicle_gross_price: 100.0,
-                },
-                vehicle_specification: VehicleSpecification {
-                    model_and_option: ModelAndOption {
-                        equipments: HashMap::new(),
-                    },
+                    vehicle_gross_price: Some(60000.0),
                 },
+                vehicle_specification: VehicleSpecification::default(),
                 ordering: Ordering {
                     order_data: OrderData {
                         usage_state: String::from("NEW"),
+                        availability: None,
                     },
                 },
-            };
+            }
+        }
 
-            assert_eq!(vehicle.get_discount_percentage(), Some(25.0));
+        #[test]
+        fn should_remove_vat_from_the_price() {
+            assert_eq!(
+                vehicle(Some(48000.0), None).get_net_price(25.0),
+                Some(38400.0)
+            );
         }
 
         #[test]
-        fn should_return_none_if_price_doesnt_exist() {
-            let vehicle = Vehicle {
+        fn should_remove_vat_from_the_gross_price_without_offer() {
+            assert_eq!(vehicle(None, None).get_net_price(25.0), Some(48000.0));
+        }
+
+        #[test]
+        fn should_prefer_the_net_price_of_the_offer() {
+            assert_eq!(
+                vehicle(Some(48000.0), Some(40500.0)).get_net_price(25.0),
+                Some(40500.0)
+            );
+        }
+
+        #[test]
+        fn should_ignore_net_price_without_gross_offer_price() {
+            assert_eq!(
+                vehicle(None, Some(40500.0)).get_net_price(25.0),
+                Some(48000.0)
+            );
+        }
+    }
+
+    mod get_discount_amount {
+        use super::*;
+        use uuid::Uuid;
+
+        fn vehicle(gross_price: f32, offer_price: Option<f32>) -> Vehicle {
+            Vehicle {
                 document_id: String::from("12345"),
                 vss_id: Uuid::new_v4(),
                 ordering_uuid: Some(Uuid::new_v4()),
-                offering: Offering { offer_prices: None },
+                score: None,
+                dealer: None,
+                images: Vec::new(),
+                offering: Offering {
+                    offer_prices: offer_price.map(|price| {
+                        HashMap::from([(
+                            "FR".to_string(),
+                            OfferPrice {
+                                offer_gross_price: Some(price),
+                                offer_net_price: None,
+                            },
+                        )])
+                    }),
+                    ..Default::default()
+                },
                 price: VehiclePrice {
-                    vehicle_gross_price: 0.0,
-                },
-                vehicle_specification: VehicleSpecification {
-                    model_and_option: ModelAndOption {
-                        equipments: HashMap::new(),
-                    },
+                    vehicle_gross_price: Some(gross_price),
                 },
+                vehicle_specification: VehicleSpecification::default(),
                 ordering: Ordering {
                     order_data: OrderData {
                         usage_state: String::from("NEW"),
+                        availability: None,
                     },
                 },
-            };
+            }
+        }
 
-            assert_eq!(vehicle.get_discount_percentage(), None);
+        #[test]
+        fn should_return_amount_saved() {
+            assert_eq!(
+                vehicle(50000.0, Some(42500.0)).get_discount_amount(),
+                Some(7500.0)
+            );
+        }
+
+        #[test]
+        fn should_return_zero_without_discount() {
+            assert_eq!(
+                vehicle(50000.0, Some(50000.0)).get_discount_amount(),
+                Some(0.0)
+            );
+        }
+
+        #[test]
+        fn should_return_none_without_offer_price() {
+            assert_eq!(vehicle(50000.0, None).get_discount_amount(), None);
         }
     }
 
-    mod has_equipment_name_like {
+    mod get_discount_percentage {
         use super::*;
         use uuid::Uuid;
 
         #[test]
-        fn should_return_true_if_name_exists() {
+        fn should_return_discount_percentage() {
             let vehicle = Vehicle {
                 document_id: String::from("12345"),
                 vss_id: Uuid::new_v4(),
                 ordering_uuid: Some(Uuid::new_v4()),
-                offering: Offering { offer_prices: None },
+                score: None,
+                dealer: None,
+                images: Vec::new(),
+                offering: Offering {
+                    offer_prices: Some(HashMap::from([(
+                        "FR".to_string(),
+                        OfferPrice {
+                            offer_gross_price: Some(75.0),
+                            offer_net_price: None,
+                        },
+                    )])),
+                    ..Default::default()
+                },
                 price: VehiclePrice {
-                    vehicle_gross_price: 0.0,
+                    vehicle_gross_price: Some(100.0),
                 },
                 vehicle_specification: VehicleSpecification {
                     model_and_option: ModelAndOption {
-                        equipments: HashMap::from([(
-                            String::from("TEST42"),
-                            Equipment {
-                                name: HashMap::from([
-                                    (String::from("default_FR"), String::from("Test asdasdasd")),
-                                    (String::from("fr_FR"), String::from("Another name")),
-                                ]),
-                            },
-                        )]),
+                        equipments: HashMap::new(),
                     },
+                    ..Default::default()
                 },
                 ordering: Ordering {
                     order_data: OrderData {
                         usage_state: String::from("NEW"),
+                        availability: None,
                     },
                 },
             };
 
-            let result = vehicle.has_equipment_name_like("Test");
-
-            assert_eq!(result, true);
+            assert_eq!(vehicle.get_discount_percentage(), Some(25.0));
         }
 
         #[test]
-        fn should_return_false_if_name_doesnt_exist() {
+        fn should_return_none_if_price_doesnt_exist() {
             let vehicle = Vehicle {
                 document_id: String::from("12345"),
                 vss_id: Uuid::new_v4(),
                 ordering_uuid: Some(Uuid::new_v4()),
-                offering: Offering { offer_prices: None },
+                score: None,
+                dealer: None,
+                images: Vec::new(),
+                offering: Offering::default(),
                 price: VehiclePrice {
-                    vehicle_gross_price: 0.0,
+                    vehicle_gross_price: Some(0.0),
                 },
                 vehicle_specification: VehicleSpecification {
                     model_and_option: ModelAndOption {
                         equipments: HashMap::new(),
                     },
+                    ..Default::default()
                 },
                 ordering: Ordering {
                     order_data: OrderData {
                         usage_state: String::from("NEW"),
+                        availability: None,
                     },
                 },
             };
 
-            assert_eq!(vehicle.has_equipment_name_like("Test"), false);
+            assert_eq!(vehicle.get_discount_percentage(), None);
         }
+    }
+
+    mod has_equipment_name_like {
+        use super::*;
+        use uuid::Uuid;
 
         #[test]
-        fn should_return_false_if_name_is_empty() {
+        fn should_return_true_if_name_exists() {
             let vehicle = Vehicle {
                 document_id: String::from("12345"),
                 vss_id: Uuid::new_v4(),
                 ordering_uuid: Some(Uuid::new_v4()),
-                offering: Offering { offer_prices: None },
+                score: None,
+                dealer: None,
+                images: Vec::new(),
+                offering: Offering::default(),
                 price: VehiclePrice {
-                    vehicle_gross_price: 0.0,
+                    vehicle_gross_price: Some(0.0),
                 },
                 vehicle_specification: VehicleSpecification {
                     model_and_option: ModelAndOption {
-                        equipments: HashMap::new(),
+                        equipments: HashMap::from([(
+                            String::from("TEST42"),
+                            Equipment {
+                                name: HashMap::from([
+                                    (String::from("default_FR"), String::from("Test asdasdasd")),
+                                    (String::from("fr_FR"), String::from("Another name")),
+                                ]),
+                            },
+                        )]),
                     },
+                    ..Default::default()
                 },
                 ordering: Ordering {
                     order_data: OrderData {
                         usage_state: String::from("NEW"),
+                        availability: None,
                     },
                 },
             };
 
-            assert_eq!(vehicle.has_equipment_name_like(""), false);
-        }
-    }
+            let result = vehicle.has_equipment_name_like("Test", None);
 
-    mod has_equipment_names {
-        use super::*;
-        use uuid::Uuid;
+            assert_eq!(result, true);
+        }
 
         #[test]
-        fn should_return_true_when_equipment_names_exist() {
+        fn should_return_false_if_name_doesnt_exist() {
             let vehicle = Vehicle {
                 document_id: String::from("12345"),
                 vss_id: Uuid::new_v4(),
                 ordering_uuid: Some(Uuid::new_v4()),
-                offering: Offering { offer_prices: None },
+                score: None,
+                dealer: None,
+                images: Vec::new(),
+                offering: Offering::default(),
                 price: VehiclePrice {
-                    vehicle_gross_price: 0.0,
+                    vehicle_gross_price: Some(0.0),
                 },
                 vehicle_specification: VehicleSpecification {
                     model_and_option: ModelAndOption {
-                        equipments: HashMap::from([
-                            (
-                                String::from("TEST42"),
-                                Equipment {
-                                    name: HashMap::from([
-                                        (
-                                            String::from("default_FR"),
-                                            String::from("Test asdasdasd"),
-                                        ),
-                                        (String::from("fr_FR"), String::from("Another name")),
-                                    ]),
-                                },
-                            ),
-                            (
-                                String::from("TEST43"),
-                                Equipment {
-                                    name: HashMap::from([
-                                        (String::from("default_FR"), String::from("My equipment")),
-                                        (String::from("fr_FR"), String::from("Another name2")),
-                                    ]),
-                                },
-                            ),
-                            (
-                                String::from("TEST44"),
-                                Equipment {
-                                    name: HashMap::from([
-                                        (
-                                            String::from("default_FR"),
-                                            String::from("My second equipment"),
-                                        ),
-                                        (String::from("fr_FR"), String::from("Another name3")),
-                                    ]),
-                                },
-                            ),
-                        ]),
+                        equipments: HashMap::new(),
                     },
+                    ..Default::default()
                 },
                 ordering: Ordering {
                     order_data: OrderData {
                         usage_state: String::from("NEW"),
+                        availability: None,
                     },
                 },
             };
 
-            let names = vec![String::from("Test"), String::from("My equipment")];
-            let result = vehicle.has_equipment_names(&names);
-
-            assert_eq!(result, true);
+            assert!(!vehicle.has_equipment_name_like("Test", None));
         }
 
         #[test]
-        fn should_return_false_if_equipment_list_is_empty() {
+        fn should_return_false_if_name_is_empty() {
             let vehicle = Vehicle {
                 document_id: String::from("12345"),
                 vss_id: Uuid::new_v4(),
                 ordering_uuid: Some(Uuid::new_v4()),
-                offering: Offering { offer_prices: None },
+                score: None,
+                dealer: None,
+                images: Vec::new(),
+                offering: Offering::default(),
                 price: VehiclePrice {
-                    vehicle_gross_price: 0.0,
+                    vehicle_gross_price: Some(0.0),
                 },
                 vehicle_specification: VehicleSpecification {
                     model_and_option: ModelAndOption {
                         equipments: HashMap::new(),
                     },
+                    ..Default::default()
                 },
                 ordering: Ordering {
                     order_data: OrderData {
                         usage_state: String::from("NEW"),
+                        availability: None,
                     },
                 },
             };
 
-            let names = vec![String::from("Test")];
-            let result = vehicle.has_equipment_names(&names);
-
-            assert_eq!(result, false);
+            assert!(!vehicle.has_equipment_name_like("", None));
         }
 
-        #[test]
-        fn should_return_true_if_equipment_names_is_empty() {
-            let vehicle = Vehicle {
+        fn localized_vehicle() -> Vehicle {
+            Vehicle {
                 document_id: String::from("12345"),
                 vss_id: Uuid::new_v4(),
                 ordering_uuid: Some(Uuid::new_v4()),
-                offering: Offering { offer_prices: None },
+                score: None,
+                dealer: None,
+                images: Vec::new(),
+                offering: Offering::default(),
                 price: VehiclePrice {
-                    vehicle_gross_price: 0.0,
+                    vehicle_gross_price: Some(0.0),
                 },
                 vehicle_specification: VehicleSpecification {
                     model_and_option: ModelAndOption {
                         equipments: HashMap::from([
                             (
-                                String::from("TEST42"),
+                                String::from("S3AC"),
                                 Equipment {
                                     name: HashMap::from([
-                                        (
-                                            String::from("default_FR"),
-                                            String::from("Test asdasdasd"),
-                                        ),
-                                        (String::from("fr_FR"), String::from("Another name")),
+                                        (String::from("fr_FR"), String::from("Attelage")),
+                                        (String::from("de_DE"), String::from("Anhängerkupplung")),
                                     ]),
                                 },
                             ),
                             (
-                                String::from("TEST43"),
+                                String::from("S402A"),
                                 Equipment {
-                                    name: HashMap::from([
-                                        (String::from("default_FR"), String::from("My equipment")),
-                                        (String::from("fr_FR"), String::from("Another name2")),
-                                    ]),
-                                },
-                            ),
-                            (
-                                String::from("TEST44"),
-                                Equipment {
-                                    name: HashMap::from([
-                                        (
-                                            String::from("default_FR"),
-                                            String::from("My second equipment"),
-                                        ),
-                                        (String::from("fr_FR"), String::from("Another name3")),
-                                    ]),
+                                    name: HashMap::from([(
+                                        String::from("default_FR"),
+                                        String::from("Toit ouvrant"),
+                                    )]),
                                 },
                             ),
                         ]),
                     },
+                    ..Default::default()
                 },
                 ordering: Ordering {
                     order_data: OrderData {
                         usage_state: String::from("NEW"),
+                        availability: None,
                     },
                 },
-            };
+            }
+        }
+
+        #[test]
+        fn should_match_every_locale_without_locale() {
+            assert!(localized_vehicle().has_equipment_name_like("anhänger", None));
+        }
 
-            let names: Vec<String> = vec![];
-            let result = vehicle.has_equipment_names(&names);
+        #[test]
+        fn should_only_match_the_given_locale() {
+            let vehicle = localized_vehicle();
 
-            assert_eq!(result, true);
+            assert!(vehicle.has_equipment_name_like("attelage", Some("fr_FR")));
+            assert!(!vehicle.has_equipment_name_like("anhänger", Some("fr_FR")));
         }
 
         #[test]
-        fn should_return_false_if_one_or_many_equipment_names_not_be_found() {
-            let vehicle = Vehicle {
+        fn should_fall_back_to_default_name_without_the_locale() {
+            let vehicle = localized_vehicle();
+
+            assert!(vehicle.has_equipment_name_like("toit", Some("fr_FR")));
+        }
+    }
+
+    mod has_equipment_code {
+        use super::*;
+        use uuid::Uuid;
+
+        fn vehicle(codes: &[&str]) -> Vehicle {
+            Vehicle {
                 document_id: String::from("12345"),
                 vss_id: Uuid::new_v4(),
                 ordering_uuid: Some(Uuid::new_v4()),
-                offering: Offering { offer_prices: None },
+                score: None,
+                dealer: None,
+                images: Vec::new(),
+                offering: Offering::default(),
                 price: VehiclePrice {
-                    vehicle_gross_price: 0.0,
+                    vehicle_gross_price: Some(0.0),
                 },
                 vehicle_specification: VehicleSpecification {
                     model_and_option: ModelAndOption {
-                        equipments: HashMap::from([
-                            (
-                                String::from("TEST42"),
-                                Equipment {
-                                    name: HashMap::from([
-                                        (
-                                            String::from("default_FR"),
-                                            String::from("Test asdasdasd"),
-                                        ),
-                                        (String::from("fr_FR"), String::from("Another name")),
-                                    ]),
-                                },
-                            ),
-                            (
-                                String::from("TEST43"),
-                                Equipment {
-                                    name: HashMap::from([
-                                        (String::from("default_FR"), String::from("My equipment")),
-                                        (String::from("fr_FR"), String::from("Another name2")),
-                                    ]),
-                                },
-                            ),
-                            (
-                                String::from("TEST44"),
-                                Equipment {
-                                    name: HashMap::from([
-                                        (
-                                            String::from("default_FR"),
-                                            String::from("My second equipment"),
-                                        ),
-                                        (String::from("fr_FR"), String::from("Another name3")),
-                                    ]),
-                                },
-                            ),
-                        ]),
+                        equipments: codes
+                            .iter()
+                            .map(|code| {
+                                (
+                                    code.to_string(),
+                                    Equipment {
+                                        name: HashMap::from([(
+                                            String::from("fr_FR"),
+                                            String::from("Some equipment"),
+                                        )]),
+                                    },
+                                )
+                            })
+                            .collect(),
                     },
+                    ..Default::default()
                 },
                 ordering: Ordering {
                     order_data: OrderData {
                         usage_state: String::from("NEW"),
+                        availability: None,
                     },
                 },
-            };
+            }
+        }
+
+        #[test]
+        fn should_return_true_if_code_exists() {
+            assert!(vehicle(&["S402A", "S4NB"]).has_equipment_code("S4NB"));
+        }
+
+        #[test]
+        fn should_return_false_if_code_doesnt_exist() {
+            assert!(!vehicle(&["S402A", "S4NB"]).has_equipment_code("S430A"));
+        }
+
+        #[test]
+        fn should_ignore_case() {
+            assert!(vehicle(&["S402A"]).has_equipment_code("s402a"));
+        }
+
+        #[test]
+        fn should_not_match_part_of_a_code() {
+            assert!(!vehicle(&["S402A"]).has_equipment_code("S402"));
+        }
+
+        #[test]
+        fn should_list_equipments_sorted_by_code() {
+            assert_eq!(
+                vehicle(&["S4NB", "S402A"]).get_equipments(Some("fr_FR")),
+                vec![
+                    ("S402A", Some("Some equipment")),
+                    ("S4NB", Some("Some equipment"))
+                ]
+            );
+            assert_eq!(
+                vehicle(&["S402A"]).get_equipments(Some("de_DE")),
+                vec![("S402A", None)]
+            );
+        }
+
+        #[test]
+        fn should_require_every_code() {
+            let vehicle = vehicle(&["S402A", "S4NB"]);
+
+            assert!(vehicle.has_equipment_codes(&[String::from("S402A"), String::from("S4NB")]));
+            assert!(!vehicle.has_equipment_codes(&[String::from("S402A"), String::from("S430A")]));
+            assert!(vehicle.has_equipment_codes(&[]));
+        }
+    }
+
+    mod has_equipment_name_similar {
+        use super::*;
+
+        fn vehicle(names: &[&str]) -> Vehicle {
+            serde_json::from_value(serde_json::json!({
+                "documentId": "12345",
+                "vssId": "67e55044-10b1-426f-9247-bb680e5fe0c8",
+                "vehicleSpecification": {
+                    "modelAndOption": {
+                        "equipments": names
+                            .iter()
+                            .enumerate()
+                            .map(|(index, name)| {
+                                (format!("S{}", index), serde_json::json!({ "name": { "fr_FR": name } }))
+                            })
+                            .collect::<serde_json::Map<_, _>>()
+                    }
+                },
+            }))
+            .expect("Failed to deserialize vehicle")
+        }
+
+        #[test]
+        fn should_match_near_miss_label_only_when_fuzzy() {
+            let vehicle = vehicle(&["M Sport Package"]);
+
+            assert!(!vehicle.has_equipment_name_like("Pack M Sport", None));
+            assert!(vehicle.has_equipment_name_similar("Pack M Sport", None, 0.9));
+        }
+
+        #[test]
+        fn should_match_what_substring_matches() {
+            let vehicle = vehicle(&["Pack M Sport Pro"]);
+
+            assert!(vehicle.has_equipment_name_like("pack m sport", None));
+            assert!(vehicle.has_equipment_name_similar("pack m sport", None, 0.9));
+        }
+
+        #[test]
+        fn should_not_match_unrelated_label() {
+            let vehicle = vehicle(&["Toit panoramique", "Attelage"]);
+
+            assert!(!vehicle.has_equipment_name_like("Pack M Sport", None));
+            assert!(!vehicle.has_equipment_name_similar("Pack M Sport", None, 0.9));
+        }
+
+        #[test]
+        fn should_respect_threshold() {
+            let vehicle = vehicle(&["M Sport Package"]);
 
-            let names = vec![String::from("Test"), String::from("Not found")];
-            let result = vehicle.has_equipment_names(&names);
+            assert!(!vehicle.has_equipment_name_similar("Pack M Sport", None, 0.99));
+        }
 
-            assert_eq!(result, false);
+        #[test]
+        fn should_rate_identical_names_as_similar() {
+            assert_eq!(
+                equipment_name_similarity("Pack M Sport", "pack  m sport"),
+                1.0
+            );
+            assert!(equipment_name_similarity("Pack M Sport", "Toit panoramique") < 0.7);
         }
     }
 
@@ -735,29 +1570,36 @@ mod tests {
                 document_id: String::from("12345"),
                 vss_id: Uuid::new_v4(),
                 ordering_uuid: Some(Uuid::new_v4()),
+                score: None,
+                dealer: None,
+                images: Vec::new(),
                 offering: Offering {
                     offer_prices: Some(HashMap::from([(
                         "FR".to_string(),
                         OfferPrice {
                             offer_gross_price: Some(100.0),
+                            offer_net_price: None,
                         },
                     )])),
+                    ..Default::default()
                 },
                 price: VehiclePrice {
-                    vehicle_gross_price: 42.0,
+                    vehicle_gross_price: Some(42.0),
                 },
                 vehicle_specification: VehicleSpecification {
                     model_and_option: ModelAndOption {
                         equipments: HashMap::new(),
                     },
+                    ..Default::default()
                 },
                 ordering: Ordering {
                     order_data: OrderData {
                         usage_state: String::from("NEW"),
+                        availability: None,
                     },
                 },
             };
-            assert_eq!(vehicle.get_price(), 100.0);
+            assert_eq!(vehicle.get_price(), Some(100.0));
         }
 
         #[test]
@@ -766,22 +1608,27 @@ mod tests {
                 document_id: String::from("12345"),
                 vss_id: Uuid::new_v4(),
                 ordering_uuid: Some(Uuid::new_v4()),
-                offering: Offering { offer_prices: None },
+                score: None,
+                dealer: None,
+                images: Vec::new(),
+                offering: Offering::default(),
                 price: VehiclePrice {
-                    vehicle_gross_price: 42.0,
+                    vehicle_gross_price: Some(42.0),
                 },
                 vehicle_specification: VehicleSpecification {
                     model_and_option: ModelAndOption {
                         equipments: HashMap::new(),
                     },
+                    ..Default::default()
                 },
                 ordering: Ordering {
                     order_data: OrderData {
                         usage_state: String::from("NEW"),
+                        availability: None,
                     },
                 },
             };
-            assert_eq!(vehicle.get_price(), 42.0);
+            assert_eq!(vehicle.get_price(), Some(42.0));
         }
 
         #[test]
@@ -790,29 +1637,36 @@ mod tests {
                 document_id: String::from("12345"),
                 vss_id: Uuid::new_v4(),
                 ordering_uuid: Some(Uuid::new_v4()),
+                score: None,
+                dealer: None,
+                images: Vec::new(),
                 offering: Offering {
                     offer_prices: Some(HashMap::from([(
                         "FR".to_string(),
                         OfferPrice {
                             offer_gross_price: None,
+                            offer_net_price: None,
                         },
                     )])),
+                    ..Default::default()
                 },
                 price: VehiclePrice {
-                    vehicle_gross_price: 55.0,
+                    vehicle_gross_price: Some(55.0),
                 },
                 vehicle_specification: VehicleSpecification {
                     model_and_option: ModelAndOption {
                         equipments: HashMap::new(),
                     },
+                    ..Default::default()
                 },
                 ordering: Ordering {
                     order_data: OrderData {
                         usage_state: String::from("NEW"),
+                        availability: None,
                     },
                 },
             };
-            assert_eq!(vehicle.get_price(), 55.0);
+            assert_eq!(vehicle.get_price(), Some(55.0));
         }
 
         #[test]
@@ -821,24 +1675,814 @@ mod tests {
                 document_id: String::from("12345"),
                 vss_id: Uuid::new_v4(),
                 ordering_uuid: Some(Uuid::new_v4()),
+                score: None,
+                dealer: None,
+                images: Vec::new(),
                 offering: Offering {
                     offer_prices: Some(HashMap::new()),
+                    ..Default::default()
                 },
                 price: VehiclePrice {
-                    vehicle_gross_price: 77.0,
+                    vehicle_gross_price: Some(77.0),
                 },
                 vehicle_specification: VehicleSpecification {
                     model_and_option: ModelAndOption {
                         equipments: HashMap::new(),
                     },
+                    ..Default::default()
+                },
+                ordering: Ordering {
+                    order_data: OrderData {
+                        usage_state: String::from("NEW"),
+                        availability: None,
+                    },
+                },
+            };
+            assert_eq!(vehicle.get_price(), Some(77.0));
+        }
+    }
+
+    mod get_first_image {
+        use super::*;
+
+        fn vehicle(media: serde_json::Value) -> Vehicle {
+            serde_json::from_value(serde_json::json!({
+                "documentId": "12345",
+                "vssId": "67e55044-10b1-426f-9247-bb680e5fe0c8",
+                "media": media,
+            }))
+            .expect("Failed to deserialize vehicle")
+        }
+
+        #[test]
+        fn should_extract_first_image_url() {
+            let vehicle = vehicle(serde_json::json!({
+                "images": [
+                    { "url": "https://cdn.bmw.example/front.jpg", "perspective": "FRONT" },
+                    { "url": "https://cdn.bmw.example/rear.jpg", "perspective": "REAR" },
+                ]
+            }));
+
+            assert_eq!(
+                vehicle.get_first_image(),
+                Some("https://cdn.bmw.example/front.jpg")
+            );
+        }
+
+        #[test]
+        fn should_return_none_without_images() {
+            assert_eq!(
+                vehicle(serde_json::json!({ "images": [] })).get_first_image(),
+                None
+            );
+            assert_eq!(vehicle(serde_json::Value::Null).get_first_image(), None);
+        }
+    }
+
+    mod get_previous_owners {
+        use super::*;
+
+        fn specification(json: &str) -> VehicleSpecification {
+            serde_json::from_str(json).expect("Failed to deserialize specification")
+        }
+
+        #[test]
+        fn should_deserialize_previous_owners() {
+            let specification =
+                specification(r#"{"modelAndOption":{"equipments":{}},"numberOfPreviousOwners":2}"#);
+
+            assert_eq!(specification.previous_owners, Some(2));
+        }
+
+        #[test]
+        fn should_deserialize_previous_owners_alias() {
+            let specification =
+                specification(r#"{"modelAndOption":{"equipments":{}},"previousOwners":1}"#);
+
+            assert_eq!(specification.previous_owners, Some(1));
+        }
+
+        #[test]
+        fn should_default_to_none_when_previous_owners_are_missing() {
+            let specification = specification(r#"{"modelAndOption":{"equipments":{}}}"#);
+
+            assert_eq!(specification.previous_owners, None);
+        }
+
+        #[test]
+        fn should_return_previous_owners() {
+            let vehicle: Vehicle = serde_json::from_value(serde_json::json!({
+                "documentId": "12345",
+                "vssId": "67e55044-10b1-426f-9247-bb680e5fe0c8",
+                "vehicleSpecification": { "numberOfPreviousOwners": 3 },
+                "ordering": { "orderData": { "usageState": "USED" } }
+            }))
+            .expect("Failed to deserialize vehicle");
+
+            assert_eq!(vehicle.get_previous_owners(), Some(3));
+        }
+    }
+
+    mod get_mileage {
+        use super::*;
+        use uuid::Uuid;
+
+        #[test]
+        fn should_return_mileage() {
+            let vehicle = Vehicle {
+                document_id: String::from("12345"),
+                vss_id: Uuid::new_v4(),
+                ordering_uuid: Some(Uuid::new_v4()),
+                score: None,
+                dealer: None,
+                images: Vec::new(),
+                offering: Offering::default(),
+                price: VehiclePrice {
+                    vehicle_gross_price: Some(0.0),
+                },
+                vehicle_specification: VehicleSpecification {
+                    model_and_option: ModelAndOption {
+                        equipments: HashMap::new(),
+                    },
+                    mileage: Some(12500),
+                    ..Default::default()
+                },
+                ordering: Ordering {
+                    order_data: OrderData {
+                        usage_state: String::from("USED"),
+                        availability: None,
+                    },
+                },
+            };
+
+            assert_eq!(vehicle.get_mileage(), Some(12500));
+        }
+
+        #[test]
+        fn should_deserialize_mileage_from_specification() {
+            let specification: VehicleSpecification =
+                serde_json::from_str(r#"{"modelAndOption":{"equipments":{}},"mileage":42000}"#)
+                    .expect("Failed to deserialize specification");
+
+            assert_eq!(specification.mileage, Some(42000));
+        }
+
+        #[test]
+        fn should_default_to_none_when_mileage_is_missing() {
+            let specification: VehicleSpecification =
+                serde_json::from_str(r#"{"modelAndOption":{"equipments":{}}}"#)
+                    .expect("Failed to deserialize specification");
+
+            assert_eq!(specification.mileage, None);
+        }
+    }
+
+    mod get_registration_date {
+        use super::*;
+
+        fn specification(json: &str) -> VehicleSpecification {
+            serde_json::from_str(json).expect("Failed to deserialize specification")
+        }
+
+        #[test]
+        fn should_deserialize_date() {
+            let specification = specification(
+                r#"{"modelAndOption":{"equipments":{}},"firstRegistrationDate":"2023-04-12"}"#,
+            );
+
+            assert_eq!(
+                specification.first_registration_date,
+                NaiveDate::from_ymd_opt(2023, 4, 12)
+            );
+        }
+
+        #[test]
+        fn should_deserialize_date_with_time() {
+            let specification = specification(
+                r#"{"modelAndOption":{"equipments":{}},"productionDate":"2024-01-31T00:00:00.000Z"}"#,
+            );
+
+            assert_eq!(
+                specification.production_date,
+                NaiveDate::from_ymd_opt(2024, 1, 31)
+            );
+        }
+
+        #[test]
+        fn should_ignore_unparseable_date() {
+            let specification = specification(
+                r#"{"modelAndOption":{"equipments":{}},"firstRegistrationDate":"12/04/2023"}"#,
+            );
+
+            assert_eq!(specification.first_registration_date, None);
+        }
+
+        #[test]
+        fn should_ignore_null_date() {
+            let specification = specification(
+                r#"{"modelAndOption":{"equipments":{}},"firstRegistrationDate":null}"#,
+            );
+
+            assert_eq!(specification.first_registration_date, None);
+        }
+
+        #[test]
+        fn should_prefer_first_registration_over_production_date() {
+            let vehicle = Vehicle {
+                document_id: String::from("12345"),
+                vss_id: Uuid::new_v4(),
+                ordering_uuid: Some(Uuid::new_v4()),
+                score: None,
+                dealer: None,
+                images: Vec::new(),
+                offering: Offering::default(),
+                price: VehiclePrice {
+                    vehicle_gross_price: Some(0.0),
+                },
+                vehicle_specification: specification(
+                    r#"{"modelAndOption":{"equipments":{}},"firstRegistrationDate":"2023-04-12","productionDate":"2023-01-02"}"#,
+                ),
+                ordering: Ordering {
+                    order_data: OrderData {
+                        usage_state: String::from("USED"),
+                        availability: None,
+                    },
+                },
+            };
+
+            assert_eq!(
+                vehicle.get_registration_date(),
+                NaiveDate::from_ymd_opt(2023, 4, 12)
+            );
+        }
+
+        #[test]
+        fn should_fallback_to_production_date() {
+            let vehicle = Vehicle {
+                document_id: String::from("12345"),
+                vss_id: Uuid::new_v4(),
+                ordering_uuid: Some(Uuid::new_v4()),
+                score: None,
+                dealer: None,
+                images: Vec::new(),
+                offering: Offering::default(),
+                price: VehiclePrice {
+                    vehicle_gross_price: Some(0.0),
                 },
+                vehicle_specification: specification(
+                    r#"{"modelAndOption":{"equipments":{}},"productionDate":"2023-01-02"}"#,
+                ),
                 ordering: Ordering {
                     order_data: OrderData {
                         usage_state: String::from("NEW"),
+                        availability: None,
                     },
                 },
             };
-            assert_eq!(vehicle.get_price(), 77.0);
+
+            assert_eq!(
+                vehicle.get_registration_date(),
+                NaiveDate::from_ymd_opt(2023, 1, 2)
+            );
+        }
+    }
+
+    mod get_fuel_type {
+        use super::*;
+
+        fn vehicle(fuel_type: Option<&str>) -> Vehicle {
+            Vehicle {
+                document_id: String::from("12345"),
+                vss_id: Uuid::new_v4(),
+                ordering_uuid: Some(Uuid::new_v4()),
+                score: None,
+                dealer: None,
+                images: Vec::new(),
+                offering: Offering::default(),
+                price: VehiclePrice {
+                    vehicle_gross_price: Some(0.0),
+                },
+                vehicle_specification: VehicleSpecification {
+                    fuel_type: fuel_type.map(String::from),
+                    ..Default::default()
+                },
+                ordering: Ordering {
+                    order_data: OrderData {
+                        usage_state: String::from("NEW"),
+                        availability: None,
+                    },
+                },
+            }
+        }
+
+        #[test]
+        fn should_map_electric() {
+            assert_eq!(
+                vehicle(Some("ELECTRIC")).get_fuel_type(),
+                Some(FuelType::Electric)
+            );
+            assert_eq!(
+                vehicle(Some("BEV")).get_fuel_type(),
+                Some(FuelType::Electric)
+            );
+        }
+
+        #[test]
+        fn should_map_hybrid() {
+            assert_eq!(
+                vehicle(Some("PHEV")).get_fuel_type(),
+                Some(FuelType::Hybrid)
+            );
+            assert_eq!(
+                vehicle(Some("hybrid")).get_fuel_type(),
+                Some(FuelType::Hybrid)
+            );
+        }
+
+        #[test]
+        fn should_map_petrol() {
+            assert_eq!(
+                vehicle(Some("GASOLINE")).get_fuel_type(),
+                Some(FuelType::Petrol)
+            );
+            assert_eq!(
+                vehicle(Some("Petrol")).get_fuel_type(),
+                Some(FuelType::Petrol)
+            );
+        }
+
+        #[test]
+        fn should_map_diesel() {
+            assert_eq!(
+                vehicle(Some("DIESEL")).get_fuel_type(),
+                Some(FuelType::Diesel)
+            );
+        }
+
+        #[test]
+        fn should_return_none_when_fuel_type_is_absent() {
+            assert_eq!(vehicle(None).get_fuel_type(), None);
+        }
+
+        #[test]
+        fn should_return_none_when_fuel_type_is_unknown() {
+            assert_eq!(vehicle(Some("HYDROGEN")).get_fuel_type(), None);
+        }
+    }
+
+    mod get_power_hp {
+        use super::*;
+
+        fn vehicle(power_hp: Option<u32>, power_kw: Option<f32>) -> Vehicle {
+            Vehicle {
+                document_id: String::from("12345"),
+                vss_id: Uuid::new_v4(),
+                ordering_uuid: Some(Uuid::new_v4()),
+                score: None,
+                dealer: None,
+                images: Vec::new(),
+                offering: Offering::default(),
+                price: VehiclePrice {
+                    vehicle_gross_price: Some(0.0),
+                },
+                vehicle_specification: VehicleSpecification {
+                    power_hp,
+                    power_kw,
+                    ..Default::default()
+                },
+                ordering: Ordering {
+                    order_data: OrderData {
+                        usage_state: String::from("NEW"),
+                        availability: None,
+                    },
+                },
+            }
+        }
+
+        #[test]
+        fn should_convert_kw_to_hp() {
+            assert_eq!(kw_to_hp(150.0), 204);
+            assert_eq!(kw_to_hp(230.0), 313);
+            assert_eq!(kw_to_hp(0.0), 0);
+        }
+
+        #[test]
+        fn should_prefer_hp_value() {
+            assert_eq!(vehicle(Some(210), Some(150.0)).get_power_hp(), Some(210));
+        }
+
+        #[test]
+        fn should_convert_kw_value_when_hp_is_absent() {
+            assert_eq!(vehicle(None, Some(150.0)).get_power_hp(), Some(204));
+        }
+
+        #[test]
+        fn should_return_none_without_power() {
+            assert_eq!(vehicle(None, None).get_power_hp(), None);
+        }
+    }
+
+    mod deserialize {
+        use super::*;
+
+        #[test]
+        fn should_deserialize_vehicle_without_gross_price() {
+            let vehicle: Vehicle = serde_json::from_value(serde_json::json!({
+                "documentId": "12345",
+                "vssId": "67e55044-10b1-426f-9247-bb680e5fe0c8",
+                "orderingUuid": null,
+                "offering": {
+                    "offerPrices": { "FR": { "offerGrossPrice": 42000.0 } }
+                },
+                "vehicleSpecification": { "modelAndOption": { "equipments": {} } },
+                "price": {},
+                "ordering": { "orderData": { "usageState": "NEW" } }
+            }))
+            .expect("Failed to deserialize vehicle");
+
+            assert_eq!(vehicle.get_price(), Some(42000.0));
+            assert_eq!(vehicle.get_discount_amount(), None);
+            assert_eq!(vehicle.get_discount_percentage(), None);
+        }
+
+        #[test]
+        fn should_deserialize_vehicle_with_only_its_ids() {
+            let vehicle: Vehicle = serde_json::from_value(serde_json::json!({
+                "documentId": "12345",
+                "vssId": "67e55044-10b1-426f-9247-bb680e5fe0c8",
+                "newField": { "unknown": true }
+            }))
+            .expect("Failed to deserialize vehicle");
+
+            assert_eq!(vehicle.get_price(), None);
+            assert!(!vehicle.is_new());
+            assert!(vehicle.get_equipments(None).is_empty());
+        }
+    }
+
+    mod get_availability {
+        use super::*;
+
+        fn vehicle_with_availability(availability: Option<&str>) -> Vehicle {
+            serde_json::from_value(serde_json::json!({
+                "documentId": "12345",
+                "vssId": "67e55044-10b1-426f-9247-bb680e5fe0c8",
+                "orderingUuid": null,
+                "offering": { "offerPrices": null },
+                "vehicleSpecification": { "modelAndOption": { "equipments": {} } },
+                "price": { "vehicleGrossPrice": 42000.0 },
+                "ordering": {
+                    "orderData": { "usageState": "NEW", "availability": availability }
+                }
+            }))
+            .expect("Failed to deserialize vehicle")
+        }
+
+        #[test]
+        fn should_read_availability() {
+            assert_eq!(
+                vehicle_with_availability(Some("AVAILABLE")).get_availability(),
+                Some(Availability::Available)
+            );
+            assert_eq!(
+                vehicle_with_availability(Some("reserved")).get_availability(),
+                Some(Availability::Reserved)
+            );
+            assert_eq!(
+                vehicle_with_availability(Some("SOLD")).get_availability(),
+                Some(Availability::Sold)
+            );
+            assert_eq!(
+                vehicle_with_availability(Some("SOMETHING_ELSE")).get_availability(),
+                None
+            );
+        }
+
+        #[test]
+        fn should_only_be_available_when_not_reserved_or_sold() {
+            assert!(vehicle_with_availability(Some("AVAILABLE")).is_available());
+            assert!(vehicle_with_availability(None).is_available());
+            assert!(!vehicle_with_availability(Some("RESERVED")).is_available());
+            assert!(!vehicle_with_availability(Some("SOLD")).is_available());
+        }
+    }
+
+    mod get_model_range {
+        use super::*;
+
+        #[test]
+        fn should_read_marketing_model_range() {
+            let vehicle: Vehicle = serde_json::from_str(
+                r#"{
+                    "documentId": "12345",
+                    "vssId": "67e55044-10b1-426f-9247-bb680e5fe0c8",
+                    "orderingUuid": null,
+                    "offering": { "offerPrices": null },
+                    "vehicleSpecification": {
+                        "modelAndOption": { "equipments": {} },
+                        "marketingModelRange": "iX1_U11E"
+                    },
+                    "price": { "vehicleGrossPrice": 42000.0 },
+                    "ordering": { "orderData": { "usageState": "NEW" } }
+                }"#,
+            )
+            .expect("Failed to deserialize vehicle");
+
+            assert_eq!(vehicle.get_model_range(), Some("iX1_U11E"));
+            assert_eq!(vehicle.get_model_name(), Some("BMW iX1"));
+        }
+    }
+
+    mod has_exterior_color_like {
+        use super::*;
+
+        fn vehicle(exterior_color: Option<&str>) -> Vehicle {
+            Vehicle {
+                document_id: String::from("12345"),
+                vss_id: Uuid::new_v4(),
+                ordering_uuid: Some(Uuid::new_v4()),
+                score: None,
+                dealer: None,
+                images: Vec::new(),
+                offering: Offering::default(),
+                price: VehiclePrice {
+                    vehicle_gross_price: Some(0.0),
+                },
+                vehicle_specification: VehicleSpecification {
+                    exterior_color: exterior_color.map(String::from),
+                    interior_color: Some(String::from("Veganza Noir")),
+                    ..Default::default()
+                },
+                ordering: Ordering {
+                    order_data: OrderData {
+                        usage_state: String::from("NEW"),
+                        availability: None,
+                    },
+                },
+            }
+        }
+
+        #[test]
+        fn should_match_color_case_insensitive() {
+            let vehicle = vehicle(Some("Bleu Portimao métallisé"));
+
+            assert!(vehicle.has_exterior_color_like("portimao"));
+            assert!(vehicle.has_exterior_color_like("BLEU"));
+        }
+
+        #[test]
+        fn should_not_match_other_color() {
+            assert!(!vehicle(Some("Blanc Alpin")).has_exterior_color_like("noir"));
+        }
+
+        #[test]
+        fn should_not_match_interior_color() {
+            let vehicle = vehicle(Some("Blanc Alpin"));
+
+            assert_eq!(vehicle.get_interior_color(), Some("Veganza Noir"));
+            assert!(!vehicle.has_exterior_color_like("noir"));
+        }
+
+        #[test]
+        fn should_not_match_without_color() {
+            let vehicle = vehicle(None);
+
+            assert_eq!(vehicle.get_exterior_color(), None);
+            assert!(!vehicle.has_exterior_color_like("noir"));
+        }
+
+        #[test]
+        fn should_not_match_empty_name() {
+            assert!(!vehicle(Some("Blanc Alpin")).has_exterior_color_like(""));
+        }
+    }
+
+    mod get_body_type {
+        use super::*;
+
+        fn vehicle(body_type: Option<&str>) -> Vehicle {
+            serde_json::from_value(serde_json::json!({
+                "documentId": "12345",
+                "vssId": "67e55044-10b1-426f-9247-bb680e5fe0c8",
+                "vehicleSpecification": { "bodyType": body_type },
+            }))
+            .expect("Failed to deserialize vehicle")
+        }
+
+        #[test]
+        fn should_normalize_common_body_types() {
+            assert_eq!(vehicle(Some("SAV")).get_body_type(), Some("SUV"));
+            assert_eq!(vehicle(Some("limousine")).get_body_type(), Some("Sedan"));
+            assert_eq!(
+                vehicle(Some("GRAN_COUPE")).get_body_type(),
+                Some("Gran Coupe")
+            );
+            assert_eq!(
+                vehicle(Some("Gran Coupe")).get_body_type(),
+                Some("Gran Coupe")
+            );
+            assert_eq!(vehicle(Some("CABRIO")).get_body_type(), Some("Convertible"));
+        }
+
+        #[test]
+        fn should_keep_unknown_body_types() {
+            assert_eq!(
+                vehicle(Some("SHOOTING_BRAKE")).get_body_type(),
+                Some("SHOOTING_BRAKE")
+            );
+        }
+
+        #[test]
+        fn should_return_none_without_body_type() {
+            assert_eq!(vehicle(None).get_body_type(), None);
+        }
+
+        #[test]
+        fn should_match_normalized_or_raw_body_type_case_insensitive() {
+            let vehicle = vehicle(Some("SAV"));
+
+            assert!(vehicle.has_body_type_like("suv"));
+            assert!(vehicle.has_body_type_like("sav"));
+            assert!(!vehicle.has_body_type_like("sedan"));
+            assert!(!vehicle.has_body_type_like(""));
+        }
+
+        #[test]
+        fn should_not_match_without_body_type() {
+            assert!(!vehicle(None).has_body_type_like("suv"));
+        }
+    }
+
+    mod get_dealer {
+        use super::*;
+
+        fn vehicle(dealer: serde_json::Value) -> Vehicle {
+            let mut value = serde_json::json!({
+                "documentId": "12345",
+                "vssId": "67e55044-10b1-426f-9247-bb680e5fe0c8",
+                "orderingUuid": null,
+                "offering": { "offerPrices": null },
+                "vehicleSpecification": { "modelAndOption": { "equipments": {} } },
+                "price": { "vehicleGrossPrice": 42000.0 },
+                "ordering": { "orderData": { "usageState": "NEW" } }
+            });
+            if !dealer.is_null() {
+                value["dealer"] = dealer;
+            }
+            serde_json::from_value(value).expect("Failed to deserialize vehicle")
+        }
+
+        #[test]
+        fn should_parse_dealer() {
+            let vehicle = vehicle(serde_json::json!({
+                "id": "00045",
+                "name": "BMW Lyon Nord",
+                "postalCode": "69002",
+                "city": "Lyon"
+            }));
+
+            let dealer = vehicle.get_dealer().expect("Dealer should be parsed");
+            assert_eq!(dealer.id.as_deref(), Some("00045"));
+            assert_eq!(dealer.name(), Some("BMW Lyon Nord"));
+            assert_eq!(dealer.location(), Some(String::from("69002 Lyon")));
+            assert_eq!(
+                dealer.label(),
+                Some(String::from("BMW Lyon Nord (69002 Lyon)"))
+            );
+        }
+
+        #[test]
+        fn should_parse_partial_dealer() {
+            let vehicle = vehicle(serde_json::json!({ "city": "Lyon" }));
+
+            let dealer = vehicle.get_dealer().expect("Dealer should be parsed");
+            assert_eq!(dealer.name(), None);
+            assert_eq!(dealer.location(), Some(String::from("Lyon")));
+            assert_eq!(dealer.label(), Some(String::from("Lyon")));
+        }
+
+        #[test]
+        fn should_be_none_without_dealer() {
+            assert!(vehicle(serde_json::Value::Null).get_dealer().is_none());
+        }
+
+        #[test]
+        fn should_match_dealer_name_case_insensitive() {
+            let vehicle = vehicle(serde_json::json!({ "name": "BMW Lyon Nord" }));
+
+            assert!(vehicle.has_dealer_name_like("lyon"));
+            assert!(!vehicle.has_dealer_name_like("paris"));
+            assert!(!vehicle.has_dealer_name_like(""));
+        }
+
+        #[test]
+        fn should_not_match_without_dealer() {
+            assert!(!vehicle(serde_json::Value::Null).has_dealer_name_like("lyon"));
+        }
+    }
+
+    mod get_transmission {
+        use super::*;
+
+        fn vehicle(transmission: Option<&str>) -> Vehicle {
+            Vehicle {
+                document_id: String::from("12345"),
+                vss_id: Uuid::new_v4(),
+                ordering_uuid: Some(Uuid::new_v4()),
+                score: None,
+                dealer: None,
+                images: Vec::new(),
+                offering: Offering::default(),
+                price: VehiclePrice {
+                    vehicle_gross_price: Some(0.0),
+                },
+                vehicle_specification: VehicleSpecification {
+                    transmission: transmission.map(String::from),
+                    ..Default::default()
+                },
+                ordering: Ordering {
+                    order_data: OrderData {
+                        usage_state: String::from("NEW"),
+                        availability: None,
+                    },
+                },
+            }
+        }
+
+        #[test]
+        fn should_map_automatic_transmissions() {
+            assert_eq!(
+                Transmission::from_raw("AUTOMATIC"),
+                Some(Transmission::Automatic)
+            );
+            assert_eq!(
+                Transmission::from_raw("Steptronic"),
+                Some(Transmission::Automatic)
+            );
+            assert_eq!(
+                Transmission::from_raw("STEPTRONIC_SPORT"),
+                Some(Transmission::Automatic)
+            );
+        }
+
+        #[test]
+        fn should_map_manual_transmission() {
+            assert_eq!(Transmission::from_raw("MANUAL"), Some(Transmission::Manual));
+        }
+
+        #[test]
+        fn should_not_map_unknown_transmission() {
+            assert_eq!(Transmission::from_raw("CVT"), None);
+            assert_eq!(Transmission::from_raw(""), None);
+        }
+
+        #[test]
+        fn should_return_normalized_transmission() {
+            assert_eq!(
+                vehicle(Some("STEPTRONIC")).get_transmission(),
+                Some(Transmission::Automatic)
+            );
+            assert_eq!(vehicle(None).get_transmission(), None);
+        }
+    }
+
+    mod transmission_fromstr {
+        use super::*;
+        use std::str::FromStr;
+
+        #[test]
+        fn parses_case_insensitive() {
+            assert_eq!(Transmission::from_str("auto"), Ok(Transmission::Automatic));
+            assert_eq!(
+                Transmission::from_str("Automatic"),
+                Ok(Transmission::Automatic)
+            );
+            assert_eq!(Transmission::from_str("MANUAL"), Ok(Transmission::Manual));
+        }
+
+        #[test]
+        fn returns_err_on_invalid_value() {
+            assert!(Transmission::from_str("steptronic").is_err());
+            assert!(Transmission::from_str("").is_err());
+        }
+    }
+
+    mod fuel_type_fromstr {
+        use super::*;
+        use std::str::FromStr;
+
+        #[test]
+        fn parses_case_insensitive() {
+            assert_eq!(FuelType::from_str("electric"), Ok(FuelType::Electric));
+            assert_eq!(FuelType::from_str("HYBRID"), Ok(FuelType::Hybrid));
+            assert_eq!(FuelType::from_str("Petrol"), Ok(FuelType::Petrol));
+            assert_eq!(FuelType::from_str("diesel"), Ok(FuelType::Diesel));
+        }
+
+        #[test]
+        fn returns_err_on_invalid_value() {
+            assert!(FuelType::from_str("gasoline").is_err());
+            assert!(FuelType::from_str("").is_err());
         }
     }
 }
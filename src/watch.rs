@@ -0,0 +1,187 @@
+//! Watch module for the BMW Finder application.
+//! Polls the search on an interval and reports vehicles that appeared or disappeared.
+
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use anyhow::Result;
+use uuid::Uuid;
+
+use crate::config::Configuration;
+use crate::legacy::{filter_and_sort_vehicles, format_euros};
+use crate::notify::notify_new_vehicles;
+use crate::source::VehicleSource;
+use crate::vehicle::Vehicle;
+
+/// Vehicles that appeared or disappeared between two searches.
+#[derive(Debug, Default, PartialEq)]
+pub struct VehicleDiff {
+    pub added: HashSet<Uuid>,
+    pub removed: HashSet<Uuid>,
+}
+
+impl VehicleDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Runs the search every `interval` until Ctrl-C is pressed.
+pub async fn run(
+    source: &impl VehicleSource,
+    configuration: &Configuration,
+    interval: Duration,
+) -> Result<()> {
+    let mut previous: Option<HashMap<Uuid, Vehicle>> = None;
+    loop {
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => break,
+            result = source.search(configuration) => match result {
+                Ok(found_vehicles) => {
+                    let current = matching_vehicles(&found_vehicles, configuration);
+                    match &previous {
+                        Some(previous) => {
+                            let diff = diff_vehicles(previous, &current);
+                            print_diff(&diff, previous, &current, configuration);
+                            if configuration.notify() {
+                                let added: Vec<&Vehicle> = diff
+                                    .added
+                                    .iter()
+                                    .filter_map(|vss_id| current.get(vss_id))
+                                    .collect();
+                                notify_new_vehicles(&added);
+                            }
+                        }
+                        None => println!(
+                            "Watching {} vehicles, checking every {} s (Ctrl-C to stop)",
+                            current.len(),
+                            interval.as_secs()
+                        ),
+                    }
+                    previous = Some(current);
+                }
+                Err(e) => eprintln!("Error during search: {:#}", e),
+            },
+        }
+
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => break,
+            _ = tokio::time::sleep(interval) => {}
+        }
+    }
+    Ok(())
+}
+
+/// Keeps the vehicles matching the configured filters, indexed by VSS ID.
+fn matching_vehicles(
+    found_vehicles: &HashMap<Uuid, Vehicle>,
+    configuration: &Configuration,
+) -> HashMap<Uuid, Vehicle> {
+    filter_and_sort_vehicles(found_vehicles, configuration)
+        .into_iter()
+        .map(|vehicle| (vehicle.vss_id, vehicle.clone()))
+        .collect()
+}
+
+/// Computes which VSS IDs were added and removed from `previous` to `current`.
+pub fn diff_vehicles(
+    previous: &HashMap<Uuid, Vehicle>,
+    current: &HashMap<Uuid, Vehicle>,
+) -> VehicleDiff {
+    VehicleDiff {
+        added: current
+            .keys()
+            .filter(|vss_id| !previous.contains_key(vss_id))
+            .copied()
+            .collect(),
+        removed: previous
+            .keys()
+            .filter(|vss_id| !current.contains_key(vss_id))
+            .copied()
+            .collect(),
+    }
+}
+
+fn print_diff(
+    diff: &VehicleDiff,
+    previous: &HashMap<Uuid, Vehicle>,
+    current: &HashMap<Uuid, Vehicle>,
+    configuration: &Configuration,
+) {
+    if diff.is_empty() {
+        return;
+    }
+    println!(
+        "{} new, {} gone ({} vehicles)",
+        diff.added.len(),
+        diff.removed.len(),
+        current.len()
+    );
+    for vehicle in diff.added.iter().filter_map(|vss_id| current.get(vss_id)) {
+        println!(
+            "  + {} | {} | {}",
+            vehicle.vss_id,
+            format_euros(vehicle.get_price()),
+            vehicle.get_link(configuration.locale())
+        );
+    }
+    for vehicle in diff
+        .removed
+        .iter()
+        .filter_map(|vss_id| previous.get(vss_id))
+    {
+        println!(
+            "  - {} | {}",
+            vehicle.vss_id,
+            format_euros(vehicle.get_price())
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vehicle::test_support::{from_json, vehicle_json};
+    use serde_json::json;
+
+    fn vehicles(vss_ids: &[Uuid]) -> HashMap<Uuid, Vehicle> {
+        vss_ids
+            .iter()
+            .map(|vss_id| {
+                let mut value = vehicle_json(42000.0, None);
+                value["vssId"] = json!(vss_id);
+                let vehicle = from_json(value);
+                (*vss_id, vehicle)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn should_return_added_and_removed_vss_ids() {
+        let (kept, gone, new) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+
+        let diff = diff_vehicles(&vehicles(&[kept, gone]), &vehicles(&[kept, new]));
+
+        assert_eq!(diff.added, HashSet::from([new]));
+        assert_eq!(diff.removed, HashSet::from([gone]));
+    }
+
+    #[test]
+    fn should_return_empty_diff_for_same_vehicles() {
+        let vss_ids = [Uuid::new_v4(), Uuid::new_v4()];
+
+        let diff = diff_vehicles(&vehicles(&vss_ids), &vehicles(&vss_ids));
+
+        assert!(diff.is_empty());
+    }
+
+    #[test]
+    fn should_return_every_vehicle_as_added_from_empty() {
+        let vss_ids = [Uuid::new_v4(), Uuid::new_v4()];
+
+        let diff = diff_vehicles(&HashMap::new(), &vehicles(&vss_ids));
+
+        assert_eq!(diff.added, HashSet::from(vss_ids));
+        assert!(diff.removed.is_empty());
+    }
+}
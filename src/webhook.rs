@@ -0,0 +1,168 @@
+//! Webhook module for the BMW Finder application.
+//! Posts the filtered vehicles to a webhook URL, as raw JSON or as a Discord message.
+
+use anyhow::{Context, Result};
+use reqwest::Client;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::config::{Configuration, WebhookFormat};
+use crate::legacy::format_euros;
+use crate::report::VehicleReport;
+use crate::vehicle::Vehicle;
+
+/// Maximum number of vehicles listed in a Discord message, which is limited to 2000 characters.
+const MAX_DISCORD_VEHICLES: usize = 10;
+
+#[derive(Serialize)]
+struct RawPayload<'a> {
+    count: usize,
+    vehicles: Vec<VehicleReport<'a>>,
+}
+
+#[derive(Serialize)]
+struct DiscordPayload {
+    content: String,
+}
+
+/// Sends the vehicles to the webhook URL in the configured format.
+pub async fn send_webhook(
+    client: &Client,
+    url: &str,
+    configuration: &Configuration,
+    vehicles: &[&Vehicle],
+) -> Result<()> {
+    client
+        .post(url)
+        .json(&build_payload(configuration, vehicles)?)
+        .send()
+        .await
+        .context("Failed to send webhook")?
+        .error_for_status()
+        .context("Webhook rejected the payload")?;
+    Ok(())
+}
+
+/// Builds the JSON payload for the configured webhook format.
+fn build_payload(configuration: &Configuration, vehicles: &[&Vehicle]) -> Result<Value> {
+    let payload = match configuration.webhook_format() {
+        WebhookFormat::Raw => serde_json::to_value(RawPayload {
+            count: vehicles.len(),
+            vehicles: vehicles
+                .iter()
+                .map(|vehicle| VehicleReport::new(vehicle, configuration.locale()))
+                .collect(),
+        })?,
+        WebhookFormat::Discord => serde_json::to_value(DiscordPayload {
+            content: discord_content(configuration, vehicles),
+        })?,
+    };
+    Ok(payload)
+}
+
+/// Formats the summary and the first vehicles as a Discord message.
+fn discord_content(configuration: &Configuration, vehicles: &[&Vehicle]) -> String {
+    let mut lines = vec![format!(
+        "Found {} vehicles ({})",
+        vehicles.len(),
+        configuration.models().join(", ")
+    )];
+    lines.extend(vehicles.iter().take(MAX_DISCORD_VEHICLES).map(|vehicle| {
+        format!(
+            "{} - <{}>",
+            format_euros(vehicle.get_price()),
+            vehicle.get_link(configuration.locale())
+        )
+    }));
+    if vehicles.len() > MAX_DISCORD_VEHICLES {
+        lines.push(format!(
+            "and {} more",
+            vehicles.len() - MAX_DISCORD_VEHICLES
+        ));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Args;
+    use crate::vehicle::test_support::{from_json, vehicle_json};
+    use clap::Parser;
+    use serde_json::json;
+    use wiremock::matchers::{body_json, method};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn vehicle() -> Vehicle {
+        let mut value = vehicle_json(42000.0, None);
+        value["vssId"] = json!("67e55044-10b1-426f-9247-bb680e5fe0c8");
+        from_json(value)
+    }
+
+    fn configuration(args: &[&str]) -> Configuration {
+        Configuration::new(Args::parse_from(
+            std::iter::once("test").chain(args.iter().copied()),
+        ))
+        .expect("Invalid configuration")
+    }
+
+    #[tokio::test]
+    async fn should_post_raw_payload() {
+        let server = MockServer::start().await;
+        let vehicle = vehicle();
+        let configuration = configuration(&["--webhook", &server.uri()]);
+        Mock::given(method("POST"))
+            .and(body_json(json!({
+                "count": 1,
+                "vehicles": [VehicleReport::new(&vehicle, "fr-fr")],
+            })))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        send_webhook(&Client::new(), &server.uri(), &configuration, &[&vehicle])
+            .await
+            .expect("Failed to send webhook");
+    }
+
+    #[tokio::test]
+    async fn should_post_discord_payload() {
+        let server = MockServer::start().await;
+        let vehicle = vehicle();
+        let configuration = configuration(&[
+            "--model",
+            "iX1_U11E",
+            "--webhook",
+            &server.uri(),
+            "--webhook-format",
+            "discord",
+        ]);
+        Mock::given(method("POST"))
+            .and(body_json(json!({
+                "content": "Found 1 vehicles (iX1_U11E)\n42000.00 € - <https://www.bmw.fr/fr-fr/sl/stocklocator#/details/67e55044-10b1-426f-9247-bb680e5fe0c8>",
+            })))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        send_webhook(&Client::new(), &server.uri(), &configuration, &[&vehicle])
+            .await
+            .expect("Failed to send webhook");
+    }
+
+    #[tokio::test]
+    async fn should_return_error_on_rejected_payload() {
+        let server = MockServer::start().await;
+        let configuration = configuration(&["--webhook", &server.uri()]);
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(400))
+            .mount(&server)
+            .await;
+
+        let result = send_webhook(&Client::new(), &server.uri(), &configuration, &[]).await;
+
+        assert!(result.is_err());
+    }
+}